@@ -0,0 +1,11 @@
+use std::path::{Path, PathBuf};
+
+/// The path of the named WKT test case in benches/testdata.  This is
+/// resolved from the crate's directory, so it doesn't depend on the working
+/// directory.
+pub fn test_case_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("benches")
+        .join("testdata")
+        .join(format!("{}.wkt", name))
+}
@@ -1,9 +1,15 @@
+mod common;
+
+use common::test_case_path;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use seg_rtree::{Coordinate, Flatbush, Rectangle, SegRTree};
+use seg_rtree::algorithms::{
+    clip_path, point_in_convex_loop, point_in_loop, point_in_loop_with_stack,
+};
+use seg_rtree::{Coordinate, Flatbush, LineString, LinearRing, Rectangle, SegRTree};
+use std::convert::TryFrom;
 
-use seg_rtree::from_wkt::{parse_wkt, Geometry};
+use seg_rtree::from_wkt::{parse_wkt_collection, Geometry};
 use std::fs;
-use std::path::Path;
 
 pub fn construction_benchmark(c: &mut Criterion) {
     let rectangles_list = get_rectangles_list("africa");
@@ -57,20 +63,146 @@ pub fn construction_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, construction_benchmark);
+pub fn build_many_benchmark(c: &mut Criterion) {
+    let rectangles_list = get_rectangles_list("africa");
+    let mut group = c.benchmark_group("build_many");
+    group.bench_function("new_loaded_loop", |b| {
+        b.iter(|| {
+            rectangles_list
+                .iter()
+                .map(|rects| SegRTree::new_loaded(16, rects))
+                .collect::<Vec<SegRTree>>()
+        })
+    });
+    group.bench_function("build_many", |b| {
+        b.iter(|| SegRTree::build_many(16, &rectangles_list))
+    });
+    group.finish();
+}
+
+pub fn clip_benchmark(c: &mut Criterion) {
+    // A square spiral with 200 legs, 1 unit apart, in unit-length segments.
+    let mut coords = vec![Coordinate::new(0., 0.)];
+    let directions = [(1., 0.), (0., 1.), (-1., 0.), (0., -1.)];
+    for leg in 0..200 {
+        let (dx, dy) = directions[leg % 4];
+        for _ in 0..(leg / 2 + 1) {
+            let last = coords[coords.len() - 1];
+            coords.push(Coordinate::new(last.x + dx, last.y + dy));
+        }
+    }
+    let spiral = LineString::try_from(coords).unwrap();
+    // A thin strip, which cuts the spiral into about 100 pieces.
+    let clip_rect = Rectangle::new((-0.5, -100.).into(), (0.5, 100.).into());
+
+    c.bench_function("clip_spiral", |b| b.iter(|| clip_path(clip_rect, &spiral)));
+}
+
+pub fn point_in_loop_benchmark(c: &mut Criterion) {
+    // A crenellated ring with 500 teeth, probed on a 50x50 grid.
+    let mut coords = vec![Coordinate::new(0., 0.), Coordinate::new(1000., 0.)];
+    for i in (0..500).rev() {
+        let x = 2. * i as f64;
+        coords.push(Coordinate::new(x + 2., 10.));
+        coords.push(Coordinate::new(x + 2., 12.));
+        coords.push(Coordinate::new(x + 1., 12.));
+        coords.push(Coordinate::new(x + 1., 10.));
+    }
+    coords.push(Coordinate::new(0., 10.));
+    coords.push(coords[0]);
+    let ring = LinearRing::try_from(coords).unwrap();
+    let probes: Vec<Coordinate> = (0..2500)
+        .map(|i| Coordinate::new((i % 50) as f64 * 20.1, (i / 50) as f64 * 0.25))
+        .collect();
+
+    let mut group = c.benchmark_group("point_in_loop");
+    group.bench_function("allocate", |b| {
+        b.iter(|| {
+            for &probe in &probes {
+                point_in_loop(probe, &ring);
+            }
+        })
+    });
+    group.bench_function("reuse_stack", |b| {
+        let mut stack = Vec::new();
+        b.iter(|| {
+            for &probe in &probes {
+                point_in_loop_with_stack(probe, &ring, &mut stack);
+            }
+        })
+    });
+    group.finish();
+}
+
+pub fn point_in_convex_loop_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("point_in_convex_loop");
+    // Regular polygons, with integer vertices to avoid rounding noise,
+    // probed on a 50x50 grid over their envelopes.
+    let radius = 1e6;
+    for &num_sides in &[8, 32, 128, 512] {
+        let mut coords: Vec<Coordinate> = (0..num_sides)
+            .map(|i| {
+                let angle = 2. * std::f64::consts::PI * i as f64 / num_sides as f64;
+                Coordinate::new(
+                    (radius * angle.cos()).round(),
+                    (radius * angle.sin()).round(),
+                )
+            })
+            .collect();
+        coords.push(coords[0]);
+        let ring = LinearRing::try_from(coords).unwrap();
+        let probes: Vec<Coordinate> = (0..2500)
+            .map(|i| {
+                let x = (i % 50) as f64 / 24.5 - 1.;
+                let y = (i / 50) as f64 / 24.5 - 1.;
+                Coordinate::new(radius * x, radius * y)
+            })
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("scan", num_sides), &ring, |b, ring| {
+            b.iter(|| {
+                for &probe in &probes {
+                    point_in_convex_loop(probe, ring);
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("rtree", num_sides), &ring, |b, ring| {
+            let mut stack = Vec::new();
+            b.iter(|| {
+                for &probe in &probes {
+                    point_in_loop_with_stack(probe, ring, &mut stack);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    construction_benchmark,
+    build_many_benchmark,
+    clip_benchmark,
+    point_in_loop_benchmark,
+    point_in_convex_loop_benchmark
+);
 criterion_main!(benches);
 
-//// Utility functions
+// Utility functions
 
+/// Read the geometries of a test case.  If the testdata is missing, this
+/// warns and returns no geometries, so the benchmark is skipped.
 fn read_test_case(name: &str) -> Vec<Vec<Geometry>> {
-    let filename = format!("benches/testdata/{}.wkt", name);
-    let filepath = Path::new("/Users/jagill/dev/seg_rtree").join(Path::new(&filename));
-    let contents = fs::read_to_string(Path::new(&filepath)).unwrap();
-
-    contents
-        .split("\n\n")
-        .map(|f| parse_wkt(f).unwrap())
-        .collect()
+    let filepath = test_case_path(name);
+    let contents = match fs::read_to_string(&filepath) {
+        Ok(contents) => contents,
+        Err(err) => {
+            eprintln!("Skipping test case {}: {}", filepath.display(), err);
+            return Vec::new();
+        }
+    };
+
+    parse_wkt_collection(&contents).unwrap()
 }
 
 fn get_rectangles_list(name: &str) -> Vec<Vec<Rectangle>> {
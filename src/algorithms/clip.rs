@@ -1,12 +1,16 @@
 use super::min_heap::MinHeap;
 use crate::geometry_state::{HasRTree, Validated};
 use crate::{Coordinate, LineString, Rectangle, SegRTree, SegmentUnion};
+use std::ops::Range;
 
 type Heap = MinHeap<(usize, usize)>;
+pub(crate) type Section = (Vec<Coordinate>, SectionSegments);
 
 struct SectionBuilder {
     coordinates: Vec<Coordinate>,
     indices: Vec<usize>,
+    // The segments spanned by the section starting at the matching entry of indices
+    segments: Vec<Option<Range<usize>>>,
 }
 
 impl SectionBuilder {
@@ -14,6 +18,7 @@ impl SectionBuilder {
         SectionBuilder {
             coordinates: Vec::with_capacity(capacity),
             indices: Vec::with_capacity(16),
+            segments: Vec::with_capacity(16),
         }
     }
 
@@ -25,8 +30,19 @@ impl SectionBuilder {
         self.coordinates.extend_from_slice(coords);
     }
 
+    /// Record that the current section spans the segments low..high
+    pub fn add_segments(&mut self, low: usize, high: usize) {
+        if let Some(current) = self.segments.last_mut() {
+            *current = match current {
+                Some(range) => Some(range.start..high),
+                None => Some(low..high),
+            };
+        }
+    }
+
     pub fn flush(&mut self) {
         self.indices.push(self.coordinates.len());
+        self.segments.push(None);
     }
 
     /// Flush if there's unflushed coordinates
@@ -40,21 +56,23 @@ impl SectionBuilder {
         }
     }
 
-    pub fn to_vec(mut self) -> Vec<Vec<Coordinate>> {
+    pub fn into_vec(mut self) -> Vec<Section> {
         self.maybe_flush();
-        let mut results = Vec::with_capacity(self.indices.len());
-
-        let mut remaining;
-        for range in self.indices.windows(2) {
-            remaining = self.coordinates.split_off(range[1] - range[0]);
-            results.push(self.coordinates);
-            self.coordinates = remaining;
-        }
-        results
+        let coordinates = &self.coordinates;
+        self.indices
+            .windows(2)
+            .zip(self.segments)
+            .map(|(range, segments)| {
+                (
+                    coordinates[range[0]..range[1]].to_vec(),
+                    SectionSegments::Range(segments.unwrap_or_default()),
+                )
+            })
+            .collect()
     }
 }
 
-struct Clipper<'a> {
+pub(crate) struct Clipper<'a> {
     clip_rect: Rectangle,
     coords: &'a [Coordinate],
     rtree: &'a SegRTree,
@@ -62,26 +80,70 @@ struct Clipper<'a> {
 }
 
 impl<'a> Clipper<'a> {
-    pub fn new(clip_rect: Rectangle, path: &'a LineString<Validated>) -> Self {
+    pub fn new(clip_rect: Rectangle, coords: &'a [Coordinate], rtree: &'a SegRTree) -> Self {
         Clipper {
             clip_rect,
-            coords: path.coords(),
-            rtree: path.rtree(),
+            coords,
+            rtree,
             last_index: None,
         }
     }
 
-    pub fn clip(mut self) -> Vec<Vec<Coordinate>> {
-        let (contained, intersects) = self.find_relevant_segments();
-        let mut output = self.build_output(contained, intersects).to_vec();
+    pub fn clip(mut self) -> Vec<Section> {
+        let mut output = self.build_sections();
         self.reconnect_loop(&mut output);
         output
     }
 
+    /// Like clip, but without joining the pieces of a loop that wraps
+    /// around its start.
+    pub fn clip_raw(mut self) -> Vec<Section> {
+        self.build_sections()
+    }
+
+    fn build_sections(&mut self) -> Vec<Section> {
+        let (contained, intersects) = self.find_relevant_segments();
+        self.build_output(contained, intersects).into_vec()
+    }
+
+    /// The envelope of what clip would return, without building it: the
+    /// envelopes of contained subtrees, plus the clipped parts of segments
+    /// crossing the boundary.
+    pub fn envelope(&self) -> Option<Rectangle> {
+        let mut envelope = Rectangle::new_empty();
+        let mut stack = vec![self.rtree.root()];
+        while let Some((level, offset)) = stack.pop() {
+            let rect = self.rtree.get_rectangle(level, offset);
+            if !self.clip_rect.intersects(rect) {
+                continue;
+            }
+            if self.clip_rect.contains(rect) {
+                envelope.expand(rect);
+            } else if level == 0 {
+                let (low, high) = self.rtree.get_low_high(level, offset);
+                if let Some((start, end)) = self
+                    .clip_rect
+                    .intersect_segment(self.coords[low], self.coords[high])
+                {
+                    envelope.expand(Rectangle::new(start, end));
+                }
+            } else {
+                let child_level = level - 1;
+                for child_offset in self.rtree.child_offsets(level, offset) {
+                    stack.push((child_level, child_offset));
+                }
+            }
+        }
+        if envelope.is_empty() {
+            None
+        } else {
+            Some(envelope)
+        }
+    }
+
     fn find_relevant_segments(&self) -> (SegmentUnion, Heap) {
         let mut contained = SegmentUnion::new();
         let mut intersects = Heap::new();
-        let degree = self.rtree.degree();
 
         let mut stack = vec![self.rtree.root()];
         while let Some((level, offset)) = stack.pop() {
@@ -96,8 +158,7 @@ impl<'a> Clipper<'a> {
                 intersects.push((low, high));
             } else {
                 let child_level = level - 1;
-                let first_child_offset = degree * offset;
-                for child_offset in first_child_offset..(first_child_offset + degree) {
+                for child_offset in self.rtree.child_offsets(level, offset) {
                     stack.push((child_level, child_offset));
                 }
             }
@@ -141,6 +202,7 @@ impl<'a> Clipper<'a> {
             sections.flush();
         }
         sections.extend(&self.coords[low..=high]);
+        sections.add_segments(low, high);
         self.last_index = Some(high);
     }
 
@@ -156,30 +218,155 @@ impl<'a> Clipper<'a> {
             if isxn_end != isxn_start {
                 sections.push(isxn_end);
             }
+            sections.add_segments(low, high);
             if isxn_end == seg_end {
                 self.last_index = Some(high);
             }
         }
     }
 
-    fn reconnect_loop(&self, output: &mut Vec<Vec<Coordinate>>) {
+    fn reconnect_loop(&self, output: &mut Vec<Section>) {
         // Check if we have a loop that starts and ends in the rectangle, but
         // was clipped into two pieces
         if output.len() > 1
-            && output.first().and_then(|ls| ls.first()) == output.last().and_then(|ls| ls.last())
+            && output.first().and_then(|(ls, _)| ls.first())
+                == output.last().and_then(|(ls, _)| ls.last())
         {
-            let mut last_piece = output.pop().unwrap();
+            let (mut last_piece, last_segments) = output.pop().unwrap();
+            let (first_piece, first_segments) = output.first().unwrap();
             last_piece.pop();
-            last_piece.extend_from_slice(output.first().unwrap());
-            output.push(last_piece);
+            last_piece.extend_from_slice(first_piece);
+            let segments = match (last_segments, first_segments) {
+                (SectionSegments::Range(last), SectionSegments::Range(first)) => {
+                    SectionSegments::Wrapped {
+                        start: last.start,
+                        end: first.end,
+                    }
+                }
+                _ => unreachable!("Only reconnect_loop wraps sections"),
+            };
+            output.push((last_piece, segments));
             output.swap_remove(0);
         }
     }
 }
 
+/// The segment indices of the original path that a clipped section spans.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SectionSegments {
+    /// The segments in the range.
+    Range(Range<usize>),
+    /// A section of a loop that wraps around its start: the segments from
+    /// start to the end of the path, then those before end.
+    Wrapped { start: usize, end: usize },
+}
+
+/// A section of a clipped path.
+#[derive(Debug, PartialEq)]
+pub enum ClippedPiece {
+    Open(Vec<Coordinate>),
+    /// A section whose first and last coordinates coincide, eg a loop
+    /// entirely within the rectangle.
+    Closed(Vec<Coordinate>),
+}
+
 /// Clip a path by intersecting with a rectangle
 pub fn clip_path(clip_rect: Rectangle, path: &LineString<Validated>) -> Vec<Vec<Coordinate>> {
-    let clipper = Clipper::new(clip_rect, path);
+    clip_path_with_indices(clip_rect, path)
+        .into_iter()
+        .map(|(section, _)| section)
+        .collect()
+}
+
+/// Like clip_path, but without joining the first and last sections of a
+/// loop that starts inside the rectangle: they are returned as separate
+/// fragments, in path order, eg for noding.
+pub fn clip_path_raw(clip_rect: Rectangle, path: &LineString<Validated>) -> Vec<Vec<Coordinate>> {
+    Clipper::new(clip_rect, path.coords(), path.rtree())
+        .clip_raw()
+        .into_iter()
+        .map(|(section, _)| section)
+        .collect()
+}
+
+/// Clip each part of a multi-path, eg a road network, concatenating the
+/// sections in part order.
+///
+/// If merge, sections are joined where one ends at the first coordinate of
+/// another (eg where two parts meet on the rectangle's boundary), keeping
+/// their directions.  The parts may be in any order.
+pub fn clip_multi_path(
+    clip_rect: Rectangle,
+    parts: &[LineString<Validated>],
+    merge: bool,
+) -> Vec<Vec<Coordinate>> {
+    let mut sections: Vec<Vec<Coordinate>> = parts
+        .iter()
+        .flat_map(|part| clip_path(clip_rect, part))
+        .collect();
+    if !merge {
+        return sections;
+    }
+
+    let is_closed = |section: &[Coordinate]| section.len() > 1 && section.first() == section.last();
+    let mut merged = Vec::with_capacity(sections.len());
+    while !sections.is_empty() {
+        let mut section = sections.remove(0);
+        // Join the sections continuing this one, then those it continues,
+        // whichever order the parts are in.
+        while !is_closed(&section) {
+            let next = sections
+                .iter()
+                .position(|other| !is_closed(other) && other.first() == section.last());
+            if let Some(index) = next {
+                let other = sections.remove(index);
+                section.extend_from_slice(&other[1..]);
+                continue;
+            }
+            let previous = sections
+                .iter()
+                .position(|other| !is_closed(other) && other.last() == section.first());
+            match previous {
+                Some(index) => {
+                    let mut other = sections.remove(index);
+                    other.extend_from_slice(&section[1..]);
+                    section = other;
+                }
+                None => break,
+            }
+        }
+        merged.push(section);
+    }
+    merged
+}
+
+/// Like clip_path, but mark which sections are closed.
+pub fn clip_path_typed(clip_rect: Rectangle, path: &LineString<Validated>) -> Vec<ClippedPiece> {
+    clip_path(clip_rect, path)
+        .into_iter()
+        .map(|section| {
+            if section.len() > 1 && section.first() == section.last() {
+                ClippedPiece::Closed(section)
+            } else {
+                ClippedPiece::Open(section)
+            }
+        })
+        .collect()
+}
+
+/// The envelope of clip_path's output, or None if it is empty.  This skips
+/// building the clipped coordinates, eg to check if a path shows in a tile.
+pub fn clip_envelope(clip_rect: Rectangle, path: &LineString<Validated>) -> Option<Rectangle> {
+    Clipper::new(clip_rect, path.coords(), path.rtree()).envelope()
+}
+
+/// Clip a path by intersecting with a rectangle, returning each section with
+/// the segment indices of the original path that it spans.
+pub fn clip_path_with_indices(
+    clip_rect: Rectangle,
+    path: &LineString<Validated>,
+) -> Vec<(Vec<Coordinate>, SectionSegments)> {
+    let clipper = Clipper::new(clip_rect, path.coords(), path.rtree());
     clipper.clip()
 }
 
@@ -195,6 +382,7 @@ mod tests {
     fn assert_clip(rect: Rectangle, input: Vec<(f64, f64)>, output: Vec<Vec<(f64, f64)>>) {
         let input = floats_to_coords(input);
         let output: Vec<Vec<Coordinate>> = output.into_iter().map(floats_to_coords).collect();
+        assert_eq!(rect.intersect_polyline(&input), output);
         assert_eq!(
             clip_path(rect, &LineString::try_from(input).unwrap()),
             output
@@ -283,6 +471,183 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_clip_raw() {
+        let rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        // Starts inside, exits, and re-enters to close.
+        let path = LineString::try_from(vec![
+            (0.5, 0.5),
+            (1.5, 0.5),
+            (1.5, 1.5),
+            (0.5, 1.5),
+            (0.5, 0.5),
+        ])
+        .unwrap();
+        assert_eq!(
+            clip_path_raw(rect, &path),
+            vec![
+                floats_to_coords(vec![(0.5, 0.5), (1.0, 0.5)]),
+                floats_to_coords(vec![(0.5, 1.0), (0.5, 0.5)]),
+            ]
+        );
+        assert_eq!(
+            clip_path(rect, &path),
+            vec![floats_to_coords(vec![(0.5, 1.0), (0.5, 0.5), (1.0, 0.5)])]
+        );
+
+        // Without a wrapped loop, the forms agree.
+        let path =
+            LineString::try_from(vec![(-1., 0.5), (2., 0.5), (2., 0.7), (-1., 0.7)]).unwrap();
+        assert_eq!(clip_path_raw(rect, &path), clip_path(rect, &path));
+    }
+
+    #[test]
+    fn test_clip_multi_path() {
+        let rect = Rectangle::new((0., 0.).into(), (2., 2.).into());
+        let parts = vec![
+            LineString::try_from(vec![(1., 1.), (3., 1.)]).unwrap(),
+            LineString::try_from(vec![(5., 5.), (6., 6.)]).unwrap(),
+            LineString::try_from(vec![(2., 1.), (1., 1.5), (1., 3.)]).unwrap(),
+            LineString::try_from(vec![(0.5, 0.5), (0.5, 1.5)]).unwrap(),
+        ];
+        let sections = clip_multi_path(rect, &parts, false);
+        assert_eq!(
+            sections,
+            vec![
+                floats_to_coords(vec![(1., 1.), (2., 1.)]),
+                floats_to_coords(vec![(2., 1.), (1., 1.5), (1., 2.)]),
+                floats_to_coords(vec![(0.5, 0.5), (0.5, 1.5)]),
+            ]
+        );
+        assert_eq!(
+            clip_multi_path(rect, &parts, true),
+            vec![
+                floats_to_coords(vec![(1., 1.), (2., 1.), (1., 1.5), (1., 2.)]),
+                floats_to_coords(vec![(0.5, 0.5), (0.5, 1.5)]),
+            ]
+        );
+        assert!(clip_multi_path(rect, &[], true).is_empty());
+
+        // The part continuing the first comes before it.
+        let reversed = vec![parts[2].clone(), parts[0].clone()];
+        assert_eq!(
+            clip_multi_path(rect, &reversed, true),
+            vec![floats_to_coords(vec![
+                (1., 1.),
+                (2., 1.),
+                (1., 1.5),
+                (1., 2.)
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_clip_typed() {
+        let rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        let square = floats_to_coords(vec![
+            (0.25, 0.25),
+            (0.75, 0.25),
+            (0.75, 0.75),
+            (0.25, 0.75),
+            (0.25, 0.25),
+        ]);
+        let path = LineString::try_from(square.clone()).unwrap();
+        assert_eq!(
+            clip_path_typed(rect, &path),
+            vec![ClippedPiece::Closed(square)]
+        );
+
+        let path = LineString::try_from(vec![(-1., 0.5), (2., 0.5)]).unwrap();
+        assert_eq!(
+            clip_path_typed(rect, &path),
+            vec![ClippedPiece::Open(floats_to_coords(vec![
+                (0., 0.5),
+                (1., 0.5)
+            ]))]
+        );
+    }
+
+    #[test]
+    fn test_clip_envelope() {
+        // A zigzag, with many segments so some subtrees are contained.
+        let coords: Vec<(f64, f64)> = (0..100)
+            .map(|i| (i as f64 / 10., if i % 2 == 0 { 0. } else { 3. }))
+            .collect();
+        let path = LineString::try_from(coords).unwrap();
+        for &(x_min, y_min, x_max, y_max) in &[
+            (1., 1., 5., 2.),
+            (-1., -1., 20., 5.),
+            (2., 0.5, 8., 10.),
+            (4.05, 2.9, 4.1, 3.),
+            (9.9, 3., 12., 4.),
+        ] {
+            let rect = Rectangle::new((x_min, y_min).into(), (x_max, y_max).into());
+            let clipped: Vec<Coordinate> = clip_path(rect, &path).into_iter().flatten().collect();
+            assert_eq!(clip_envelope(rect, &path), Some(Rectangle::of(&clipped)));
+        }
+        let miss = Rectangle::new((0., 4.).into(), (10., 5.).into());
+        assert_eq!(clip_envelope(miss, &path), None);
+    }
+
+    #[test]
+    fn test_contained_subtrees() {
+        // Enough segments that whole subtrees of the rtree are contained
+        let rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        let mut input: Vec<(f64, f64)> = (0..=40).map(|i| (i as f64 / 100., 0.5)).collect();
+        let mut expected = input.clone();
+        assert_clip(rect, input.clone(), vec![expected.clone()]);
+
+        input.push((2.0, 0.5));
+        expected.push((1.0, 0.5));
+        assert_clip(rect, input, vec![expected]);
+    }
+
+    #[test]
+    fn test_clip_indices() {
+        let rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        let path = LineString::try_from(vec![
+            (-0.5, 0.5),
+            (0.5, 0.5),
+            (0.5, 1.5),
+            (0.75, 1.5),
+            (0.75, 0.25),
+            (0.9, 0.25),
+            (1.5, 0.25),
+        ])
+        .unwrap();
+        let output = clip_path_with_indices(rect, &path);
+        assert_eq!(
+            output,
+            vec![
+                (
+                    floats_to_coords(vec![(0., 0.5), (0.5, 0.5), (0.5, 1.)]),
+                    SectionSegments::Range(0..2)
+                ),
+                (
+                    floats_to_coords(vec![(0.75, 1.), (0.75, 0.25), (0.9, 0.25), (1., 0.25)]),
+                    SectionSegments::Range(3..6)
+                ),
+            ]
+        );
+
+        // A loop starting inside wraps around its start.
+        let path = LineString::try_from(vec![
+            (0.5, 0.5),
+            (1.5, 0.5),
+            (1.5, 1.5),
+            (0.5, 1.5),
+            (0.5, 0.5),
+        ])
+        .unwrap();
+        assert_eq!(
+            clip_path_with_indices(rect, &path),
+            vec![(
+                floats_to_coords(vec![(0.5, 1.0), (0.5, 0.5), (1.0, 0.5)]),
+                SectionSegments::Wrapped { start: 3, end: 1 }
+            )]
+        );
+    }
+
     #[allow(dead_code)]
     fn test_numerical_precision() {
         let rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
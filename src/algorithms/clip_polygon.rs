@@ -0,0 +1,453 @@
+use super::clip::Clipper;
+use super::point_in_polygon::{point_in_coords_loop, point_in_polygon, ContainRelation};
+use crate::geometry_state::{HasRTree, Raw, Validated};
+use crate::utils::signed_area;
+use crate::{Coordinate, HasEnvelope, LineString, LinearRing, Polygon, Rectangle};
+
+/// Positions along the boundary of a rectangle, measured counter-clockwise
+/// from its lower-left corner.
+struct Perimeter {
+    rect: Rectangle,
+    width: f64,
+    height: f64,
+    length: f64,
+}
+
+impl Perimeter {
+    fn new(rect: Rectangle) -> Self {
+        let width = rect.x_max - rect.x_min;
+        let height = rect.y_max - rect.y_min;
+        Perimeter {
+            rect,
+            width,
+            height,
+            length: 2. * (width + height),
+        }
+    }
+
+    /// The position of a coordinate on (or very near) the boundary.
+    fn position(&self, coord: Coordinate) -> f64 {
+        let rect = self.rect;
+        let offsets = [
+            (coord.y - rect.y_min).abs(),
+            (rect.x_max - coord.x).abs(),
+            (rect.y_max - coord.y).abs(),
+            (coord.x - rect.x_min).abs(),
+        ];
+        let mut side = 0;
+        for (index, &offset) in offsets.iter().enumerate() {
+            if offset < offsets[side] {
+                side = index;
+            }
+        }
+        let position = match side {
+            0 => coord.x - rect.x_min,
+            1 => self.width + coord.y - rect.y_min,
+            2 => self.width + self.height + rect.x_max - coord.x,
+            _ => 2. * self.width + self.height + rect.y_max - coord.y,
+        };
+        self.normalize(position)
+    }
+
+    fn normalize(&self, position: f64) -> f64 {
+        if position < 0. {
+            position + self.length
+        } else if position >= self.length {
+            position - self.length
+        } else {
+            position
+        }
+    }
+
+    /// The counter-clockwise distance along the boundary from start to end.
+    fn distance(&self, start: f64, end: f64) -> f64 {
+        self.normalize(end - start)
+    }
+
+    /// The corners strictly between start and end, counter-clockwise.
+    fn corners_between(&self, start: f64, end: f64) -> Vec<Coordinate> {
+        let rect = self.rect;
+        let corners = [
+            (0., Coordinate::new(rect.x_min, rect.y_min)),
+            (self.width, Coordinate::new(rect.x_max, rect.y_min)),
+            (
+                self.width + self.height,
+                Coordinate::new(rect.x_max, rect.y_max),
+            ),
+            (
+                2. * self.width + self.height,
+                Coordinate::new(rect.x_min, rect.y_max),
+            ),
+        ];
+        let span = self.distance(start, end);
+        let mut between: Vec<(f64, Coordinate)> = corners
+            .iter()
+            .map(|&(position, corner)| (self.distance(start, position), corner))
+            .filter(|&(distance, _)| distance > 0. && distance < span)
+            .collect();
+        between.sort_by(|a, b| a.0.total_cmp(&b.0));
+        between.into_iter().map(|(_, corner)| corner).collect()
+    }
+
+    /// Twice the area swept, relative to the lower-left corner, walking
+    /// counter-clockwise from the lower-left corner to coord on the boundary.
+    fn swept_area_2x(&self, coord: Coordinate) -> f64 {
+        let rect = self.rect;
+        let origin = Coordinate::new(rect.x_min, rect.y_min);
+        let corners = [
+            (self.width, Coordinate::new(rect.x_max, rect.y_min)),
+            (
+                self.width + self.height,
+                Coordinate::new(rect.x_max, rect.y_max),
+            ),
+            (
+                2. * self.width + self.height,
+                Coordinate::new(rect.x_min, rect.y_max),
+            ),
+        ];
+        let position = self.position(coord);
+        let mut previous = origin;
+        let mut area = 0.;
+        for &(corner_position, corner) in &corners {
+            if corner_position <= position {
+                area += (previous - origin).cross(corner - origin);
+                previous = corner;
+            }
+        }
+        area + (previous - origin).cross(coord - origin)
+    }
+
+    fn ring(&self) -> Vec<Coordinate> {
+        let rect = self.rect;
+        vec![
+            Coordinate::new(rect.x_min, rect.y_min),
+            Coordinate::new(rect.x_max, rect.y_min),
+            Coordinate::new(rect.x_max, rect.y_max),
+            Coordinate::new(rect.x_min, rect.y_max),
+            Coordinate::new(rect.x_min, rect.y_min),
+        ]
+    }
+}
+
+/// Clip a polygon by intersecting with a rectangle.
+///
+/// The rings are clipped into sections, which are joined into new shells by
+/// walking counter-clockwise around the rectangle.  Output shells are
+/// counter-clockwise and output holes are clockwise.  Where the polygon
+/// boundary runs along the rectangle's boundary, some outputs may be
+/// degenerate; validate the results to filter these.
+pub fn clip_polygon(clip_rect: Rectangle, polygon: &Polygon<Validated>) -> Vec<Polygon<Raw>> {
+    let envelope = polygon.envelope();
+    if !clip_rect.intersects(envelope) {
+        return Vec::new();
+    }
+    if clip_rect.contains(envelope) {
+        return vec![polygon.to_raw()];
+    }
+
+    let mut open_sections: Vec<Vec<Coordinate>> = Vec::new();
+    let mut shells: Vec<Vec<Coordinate>> = Vec::new();
+    let mut holes: Vec<Vec<Coordinate>> = Vec::new();
+    let rings = std::iter::once(polygon.shell()).chain(polygon.holes());
+    for (index, ring) in rings.enumerate() {
+        let is_shell = index == 0;
+        // Orient so the polygon interior is always to the left.
+        let reverse = (signed_area(ring.coords()) > 0.) != is_shell;
+        let clipper = Clipper::new(clip_rect, ring.coords(), ring.rtree());
+        for (mut section, _) in clipper.clip() {
+            if section.len() < 2 {
+                continue;
+            }
+            if reverse {
+                section.reverse();
+            }
+            if section.first() != section.last() {
+                open_sections.push(section);
+            } else if is_shell {
+                shells.push(section);
+            } else {
+                holes.push(section);
+            }
+        }
+    }
+
+    let perimeter = Perimeter::new(clip_rect);
+    if !open_sections.is_empty() {
+        shells.extend(connect_sections(&perimeter, open_sections));
+    } else if rectangle_is_inside(clip_rect, polygon) {
+        shells.push(perimeter.ring());
+    }
+
+    assign_holes(shells, holes)
+}
+
+/// The area of the part of the polygon inside the rectangle, eg for the
+/// coverage of a tile.
+///
+/// This integrates the clipped sections of the rings, and the parts of the
+/// rectangle's boundary between them, without building the clipped
+/// polygons.
+pub fn clipped_area(clip_rect: Rectangle, polygon: &Polygon<Validated>) -> f64 {
+    let envelope = polygon.envelope();
+    if !clip_rect.intersects(envelope) {
+        return 0.;
+    }
+    if clip_rect.contains(envelope) {
+        return polygon.area();
+    }
+
+    let perimeter = Perimeter::new(clip_rect);
+    let origin = Coordinate::new(clip_rect.x_min, clip_rect.y_min);
+    let mut area_2x = 0.;
+    // The boundary positions where sections enter and exit the rectangle
+    let mut events: Vec<(f64, bool)> = Vec::new();
+    let rings = std::iter::once(polygon.shell()).chain(polygon.holes());
+    for (index, ring) in rings.enumerate() {
+        // Orient so the polygon interior is always to the left.
+        let reverse = (signed_area(ring.coords()) > 0.) != (index == 0);
+        let clipper = Clipper::new(clip_rect, ring.coords(), ring.rtree());
+        for (mut section, _) in clipper.clip() {
+            if section.len() < 2 {
+                continue;
+            }
+            if reverse {
+                section.reverse();
+            }
+            area_2x += section
+                .windows(2)
+                .map(|w| (w[0] - origin).cross(w[1] - origin))
+                .sum::<f64>();
+            let (first, last) = (section[0], section[section.len() - 1]);
+            if first != last {
+                // The boundary from each exit to the next entry is inside.
+                area_2x += perimeter.swept_area_2x(first) - perimeter.swept_area_2x(last);
+                events.push((perimeter.position(first), true));
+                events.push((perimeter.position(last), false));
+            }
+        }
+    }
+
+    let rect_area = clip_rect.area();
+    let first_event = events.iter().min_by(|a, b| a.0.total_cmp(&b.0));
+    match first_event {
+        // The boundary after the lower-left corner is inside, so one of the
+        // walks from an exit to an entry wraps past it.
+        Some(&(_, true)) => area_2x / 2. + rect_area,
+        Some(&(_, false)) => area_2x / 2.,
+        None if rectangle_is_inside(clip_rect, polygon) => area_2x / 2. + rect_area,
+        None => area_2x / 2.,
+    }
+}
+
+/// Join sections that start and end on the boundary into closed rings.
+fn connect_sections(perimeter: &Perimeter, sections: Vec<Vec<Coordinate>>) -> Vec<Vec<Coordinate>> {
+    let entries: Vec<f64> = sections
+        .iter()
+        .map(|section| perimeter.position(section[0]))
+        .collect();
+    let mut used = vec![false; sections.len()];
+    let mut rings = Vec::new();
+
+    for first in 0..sections.len() {
+        if used[first] {
+            continue;
+        }
+        let mut ring: Vec<Coordinate> = Vec::new();
+        let mut current = first;
+        loop {
+            used[current] = true;
+            for &coord in &sections[current] {
+                if ring.last() != Some(&coord) {
+                    ring.push(coord);
+                }
+            }
+            let exit = perimeter.position(*ring.last().unwrap());
+            // The next section is the first one entered walking
+            // counter-clockwise from here, or we close the ring.
+            let next = (0..sections.len())
+                .filter(|&i| !used[i] || i == first)
+                .min_by(|&a, &b| {
+                    perimeter
+                        .distance(exit, entries[a])
+                        .total_cmp(&perimeter.distance(exit, entries[b]))
+                })
+                .unwrap();
+            ring.extend(perimeter.corners_between(exit, entries[next]));
+            if next == first {
+                break;
+            }
+            current = next;
+        }
+        if ring.first() != ring.last() {
+            ring.push(ring[0]);
+        }
+        rings.push(ring);
+    }
+    rings
+}
+
+/// Whether the rectangle is within the polygon, for the case where no ring
+/// crosses its boundary.
+fn rectangle_is_inside(rect: Rectangle, polygon: &Polygon<Validated>) -> bool {
+    let center = rect.center();
+    let probes = [
+        Coordinate::new(rect.x_min, rect.y_min),
+        Coordinate::new(rect.x_max, rect.y_max),
+        Coordinate::new(rect.x_min, center.y),
+        Coordinate::new(center.x, rect.y_max),
+    ];
+    probes
+        .iter()
+        .map(|&probe| point_in_polygon(probe, polygon))
+        .find(|relation| *relation != ContainRelation::Boundary)
+        == Some(ContainRelation::Interior)
+}
+
+/// Build polygons from the shells, placing each hole in the shell that
+/// contains it.
+fn assign_holes(shells: Vec<Vec<Coordinate>>, holes: Vec<Vec<Coordinate>>) -> Vec<Polygon<Raw>> {
+    let shells: Vec<LinearRing<_>> = shells
+        .into_iter()
+        .filter_map(|shell| LineString::new(shell).prepare().into_ring().ok())
+        .collect();
+    let mut shell_holes: Vec<Vec<LinearRing<Raw>>> = vec![Vec::new(); shells.len()];
+
+    for hole in holes {
+        let containing_shell = shells.iter().position(|shell| {
+            hole.iter()
+                .map(|&coord| point_in_coords_loop(coord, shell.coords(), shell.rtree()))
+                .find(|relation| *relation != ContainRelation::Boundary)
+                == Some(ContainRelation::Interior)
+        });
+        if let Some(index) = containing_shell {
+            if let Ok(ring) = LineString::new(hole).into_ring() {
+                shell_holes[index].push(ring);
+            }
+        }
+    }
+
+    shells
+        .into_iter()
+        .zip(shell_holes)
+        .map(|(shell, holes)| Polygon::<Raw>::new(shell.to_raw(), holes))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn make_polygon(shell: Vec<(f64, f64)>, holes: Vec<Vec<(f64, f64)>>) -> Polygon<Validated> {
+        let shell = LinearRing::try_from(shell).unwrap();
+        let holes = holes
+            .into_iter()
+            .map(|hole| LinearRing::try_from(hole).unwrap())
+            .collect();
+        Polygon::try_new(shell, holes).unwrap()
+    }
+
+    fn square(x_min: f64, y_min: f64, x_max: f64, y_max: f64) -> Vec<(f64, f64)> {
+        vec![
+            (x_min, y_min),
+            (x_min, y_max),
+            (x_max, y_max),
+            (x_max, y_min),
+            (x_min, y_min),
+        ]
+    }
+
+    fn ring_coords(ring: &LinearRing<Raw>) -> Vec<(f64, f64)> {
+        ring.coords().iter().map(|c| (c.x, c.y)).collect()
+    }
+
+    #[test]
+    fn test_disjoint_and_contained() {
+        let polygon = make_polygon(square(0., 0., 4., 4.), vec![square(1., 1., 2., 2.)]);
+        let rect = Rectangle::new((5., 5.).into(), (6., 6.).into());
+        assert!(clip_polygon(rect, &polygon).is_empty());
+
+        let rect = Rectangle::new((-1., -1.).into(), (5., 5.).into());
+        let output = clip_polygon(rect, &polygon);
+        assert_eq!(output.len(), 1);
+        assert_eq!(ring_coords(output[0].shell()), square(0., 0., 4., 4.));
+        assert_eq!(output[0].holes().len(), 1);
+    }
+
+    #[test]
+    fn test_rectangle_inside_polygon() {
+        let polygon = make_polygon(square(0., 0., 4., 4.), vec![square(1., 1., 2., 2.)]);
+        // Contains the hole
+        let rect = Rectangle::new((0.5, 0.5).into(), (3., 3.).into());
+        let output = clip_polygon(rect, &polygon);
+        assert_eq!(output.len(), 1);
+        assert_eq!(
+            ring_coords(output[0].shell()),
+            vec![(0.5, 0.5), (3., 0.5), (3., 3.), (0.5, 3.), (0.5, 0.5)]
+        );
+        assert_eq!(output[0].holes().len(), 1);
+        assert_eq!(signed_area(output[0].holes()[0].coords()), -1.);
+
+        // Misses the hole
+        let rect = Rectangle::new((2.5, 2.5).into(), (3., 3.).into());
+        let output = clip_polygon(rect, &polygon);
+        assert_eq!(output.len(), 1);
+        assert!(output[0].holes().is_empty());
+
+        // Inside the hole
+        let rect = Rectangle::new((1.25, 1.25).into(), (1.75, 1.75).into());
+        assert!(clip_polygon(rect, &polygon).is_empty());
+    }
+
+    #[test]
+    fn test_clipped_area() {
+        let polygon = make_polygon(square(0., 0., 4., 2.), Vec::new());
+        // The left half
+        let rect = Rectangle::new((-1., -1.).into(), (2., 3.).into());
+        assert_eq!(clipped_area(rect, &polygon), polygon.area() / 2.);
+        // The bottom half, with the rectangle's lower-left corner inside
+        let rect = Rectangle::new((0.5, 0.5).into(), (5., 3.).into());
+        assert_eq!(clipped_area(rect, &polygon), 3.5 * 1.5);
+
+        // A triangle, cut in half by area
+        let triangle = make_polygon(vec![(0., 0.), (4., 0.), (0., 4.), (0., 0.)], Vec::new());
+        let rect = Rectangle::new((0., 0.).into(), (4., 4. - 8_f64.sqrt()).into());
+        assert!((clipped_area(rect, &triangle) - 4.).abs() < 1e-12);
+
+        // With a hole, matching the area of the clipped polygons
+        let polygon = make_polygon(square(0., 0., 4., 4.), vec![square(1., 1., 3., 3.)]);
+        for &(x_min, y_min, x_max, y_max) in &[
+            (2., -1., 5., 5.),
+            (2., 0.5, 3.5, 3.5),
+            (0.5, 0.5, 3., 3.),
+            (1.5, 1.5, 2.5, 2.5),
+            (3.5, 3.5, 5., 5.),
+            (-1., -1., 5., 5.),
+            (5., 5., 6., 6.),
+        ] {
+            let rect = Rectangle::new((x_min, y_min).into(), (x_max, y_max).into());
+            let expected: f64 = clip_polygon(rect, &polygon)
+                .into_iter()
+                .map(|piece| piece.area())
+                .sum();
+            assert_eq!(clipped_area(rect, &polygon), expected);
+        }
+    }
+
+    #[test]
+    fn test_clip_crossing_rings() {
+        let polygon = make_polygon(square(0., 0., 4., 4.), vec![square(1., 1., 3., 3.)]);
+        // Cuts the shell and the hole on one side
+        let rect = Rectangle::new((2., -1.).into(), (5., 5.).into());
+        let output = clip_polygon(rect, &polygon);
+        assert_eq!(output.len(), 1);
+        assert!(output[0].holes().is_empty());
+        assert_eq!(signed_area(output[0].shell().coords()), 8. - 2.);
+
+        // Cuts the hole only
+        let rect = Rectangle::new((2., 0.5).into(), (3.5, 3.5).into());
+        let output = clip_polygon(rect, &polygon);
+        assert_eq!(output.len(), 1);
+        assert_eq!(signed_area(output[0].shell().coords()), 1.5 * 3. - 1. * 2.);
+    }
+}
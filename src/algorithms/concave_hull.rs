@@ -0,0 +1,198 @@
+use super::convex_hull::convex_hull;
+use crate::utils::{intersect_segments, orientation, point_segment_distance};
+use crate::{Coordinate, Flatbush, HasEnvelope, Rectangle};
+use std::cmp::Ordering;
+
+const CONCAVE_HULL_DEGREE: usize = 16;
+
+/// A concave hull of coords, eg to outline a point cloud more tightly than
+/// its convex hull.
+///
+/// This starts from the convex hull and digs into it: an edge from a to b is
+/// replaced by a to p to b, for the interior point p nearest the edge, when
+/// the edge is more than alpha times longer than the distance from p to the
+/// closer of a and b.  A dig is skipped if it would leave another point
+/// outside, or make the boundary cross itself.  Smaller alpha (eg 1 to 3)
+/// digs deeper; as alpha goes to infinity nothing is dug, and the result is
+/// the convex hull.
+///
+/// The hull is returned as a closed, counter-clockwise ring, with every
+/// point on its boundary as a vertex, so unlike convex_hull it may have
+/// collinear vertices.  As for convex_hull, if the coords have fewer than 3
+/// non-collinear points, the distinct extreme points are returned instead.
+/// This is O(n^3) in the worst case: each of up to n edges may try up to n
+/// candidates, each checked against the whole hull.
+pub fn concave_hull(coords: &[Coordinate], alpha: f64) -> Vec<Coordinate> {
+    let convex = convex_hull(coords);
+    if convex.len() < 4 {
+        return convex;
+    }
+
+    // The same points, in the same order, that convex_hull used.
+    let mut points: Vec<Coordinate> = coords.iter().copied().filter(|c| c.is_finite()).collect();
+    points.sort_by(lexicographic);
+    points.dedup();
+    let envelopes: Vec<Rectangle> = points.iter().map(|p| p.envelope()).collect();
+    let index = Flatbush::new(CONCAVE_HULL_DEGREE, &envelopes);
+
+    let mut on_hull = vec![false; points.len()];
+    let mut hull: Vec<usize> = Vec::with_capacity(points.len());
+    for edge in convex.windows(2) {
+        let a = find(&points, edge[0]);
+        let b = find(&points, edge[1]);
+        // convex_hull drops the points along its edges, so add them back.
+        let mut along: Vec<usize> = index
+            .query_rect(Rectangle::new(points[a], points[b]))
+            .into_iter()
+            .filter(|&q| q != a && q != b && orientation(points[a], points[b], points[q]) == 0.)
+            .collect();
+        along.sort_by(|&p, &q| {
+            let from_a = |i: usize| points[a].distance(points[i]);
+            from_a(p).total_cmp(&from_a(q))
+        });
+        hull.push(a);
+        hull.extend(along);
+    }
+    for &i in &hull {
+        on_hull[i] = true;
+    }
+
+    // Digging an edge inserts a new edge after it, which is checked next.
+    let mut position = 0;
+    while position < hull.len() {
+        let a = hull[position];
+        let b = hull[(position + 1) % hull.len()];
+        match find_dig(&points, &index, &on_hull, &hull, a, b, alpha) {
+            Some(p) => {
+                hull.insert(position + 1, p);
+                on_hull[p] = true;
+            }
+            None => position += 1,
+        }
+    }
+
+    let mut ring: Vec<Coordinate> = hull.iter().map(|&i| points[i]).collect();
+    ring.push(ring[0]);
+    ring
+}
+
+fn lexicographic(a: &Coordinate, b: &Coordinate) -> Ordering {
+    a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+}
+
+fn find(points: &[Coordinate], coord: Coordinate) -> usize {
+    points
+        .binary_search_by(|p| lexicographic(p, &coord))
+        .expect("Hull coordinates are among the points")
+}
+
+/// The interior point to dig the edge from a to b to, if any.
+fn find_dig(
+    points: &[Coordinate],
+    index: &Flatbush,
+    on_hull: &[bool],
+    hull: &[usize],
+    a: usize,
+    b: usize,
+    alpha: f64,
+) -> Option<usize> {
+    let (start, end) = (points[a], points[b]);
+    let max_distance = start.distance(end) / alpha;
+    let mut candidates: Vec<(usize, f64)> = index
+        .query_rect_within(Rectangle::new(start, end), max_distance)
+        .into_iter()
+        .filter(|&p| !on_hull[p] && orientation(start, end, points[p]) > 0.)
+        .filter(|&p| points[p].distance(start).min(points[p].distance(end)) < max_distance)
+        .map(|p| (p, point_segment_distance(points[p], start, end)))
+        .collect();
+    candidates.sort_by(|x, y| x.1.total_cmp(&y.1).then(x.0.cmp(&y.0)));
+    candidates
+        .into_iter()
+        .map(|(p, _)| p)
+        .find(|&p| can_dig(points, index, hull, a, b, p))
+}
+
+/// Whether no other point is in or on the triangle abp, and the new edges
+/// ap and pb don't meet the rest of the hull.
+fn can_dig(
+    points: &[Coordinate],
+    index: &Flatbush,
+    hull: &[usize],
+    a: usize,
+    b: usize,
+    p: usize,
+) -> bool {
+    let (start, end, apex) = (points[a], points[b], points[p]);
+    let triangle = Rectangle::of(&[start, end, apex]);
+    let is_empty = index.query_rect(triangle).into_iter().all(|q| {
+        let point = points[q];
+        q == a
+            || q == b
+            || q == p
+            || orientation(start, end, point) < 0.
+            || orientation(end, apex, point) < 0.
+            || orientation(apex, start, point) < 0.
+    });
+    if !is_empty {
+        return false;
+    }
+    (0..hull.len()).all(|i| {
+        let (c, d) = (hull[i], hull[(i + 1) % hull.len()]);
+        if c == a || c == b || d == a || d == b {
+            return true;
+        }
+        let (c, d) = (points[c], points[d]);
+        intersect_segments(start, apex, c, d).is_none()
+            && intersect_segments(apex, end, c, d).is_none()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::point_in_polygon::{point_in_loop, ContainRelation};
+    use crate::utils::signed_area;
+    use crate::LinearRing;
+    use std::convert::TryFrom;
+
+    /// The points of a 7x7 grid, without a notch from the top middle.
+    fn u_shape() -> Vec<Coordinate> {
+        (0..7)
+            .flat_map(|x| (0..7).map(move |y| Coordinate::new(x as f64, y as f64)))
+            .filter(|c| !(c.x >= 2. && c.x <= 4. && c.y >= 2.))
+            .collect()
+    }
+
+    #[test]
+    fn test_concave_hull() {
+        let points = u_shape();
+        let hull = concave_hull(&points, 2.);
+        let ring = LinearRing::try_from(hull.clone()).unwrap();
+        assert!(signed_area(&hull) > 0.);
+        for &point in &points {
+            assert_ne!(point_in_loop(point, &ring), ContainRelation::Exterior);
+        }
+        // The notch, but not the arms, is dug out.
+        assert_eq!(
+            point_in_loop(Coordinate::new(3., 4.), &ring),
+            ContainRelation::Exterior
+        );
+        // The convex hull has area 36, and the U itself 16.
+        assert!(signed_area(&hull) < 17.);
+    }
+
+    #[test]
+    fn test_large_alpha() {
+        let points = u_shape();
+        let hull = concave_hull(&points, f64::INFINITY);
+        assert_eq!(signed_area(&hull), signed_area(&convex_hull(&points)));
+        // The corners, 5 more points along each side but the top, and 2
+        // along the top.
+        assert_eq!(hull.len(), 4 + 3 * 5 + 2 + 1);
+        assert_eq!(hull.first(), hull.last());
+
+        let collinear = Coordinate::vec_from(&[(0., 0.), (1., 1.), (2., 2.)]);
+        assert_eq!(concave_hull(&collinear, 1.), convex_hull(&collinear));
+        assert!(concave_hull(&[], 1.).is_empty());
+    }
+}
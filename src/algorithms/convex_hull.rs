@@ -0,0 +1,284 @@
+use crate::utils::orientation;
+use crate::{Coordinate, Rectangle};
+
+/// The convex hull of coords, via Andrew's monotone chain.
+///
+/// The hull is returned as a closed, counter-clockwise ring without
+/// collinear points.  If the coords have fewer than 3 non-collinear points,
+/// the distinct extreme points are returned instead (not closed).
+pub fn convex_hull(coords: &[Coordinate]) -> Vec<Coordinate> {
+    let mut points: Vec<Coordinate> = coords.iter().copied().filter(|c| c.is_finite()).collect();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut hull: Vec<Coordinate> = Vec::with_capacity(points.len() + 1);
+    add_chain(&mut hull, points.iter());
+    add_chain(&mut hull, points.iter().rev());
+
+    if hull.len() < 3 {
+        // All points are collinear
+        return vec![points[0], points[points.len() - 1]];
+    }
+    hull.push(hull[0]);
+    hull
+}
+
+/// Push the lower (or upper, if reversed) hull of sorted points, without its
+/// last point, which begins the next chain.
+fn add_chain<'a>(hull: &mut Vec<Coordinate>, points: impl Iterator<Item = &'a Coordinate>) {
+    let start = hull.len();
+    for &point in points {
+        while hull.len() >= start + 2
+            && orientation(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.
+        {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+    hull.pop();
+}
+
+/// The minimum-area rectangle enclosing coords, at any orientation,
+/// returned as its four corners in counter-clockwise order.
+///
+/// This uses rotating calipers over the convex hull: the minimum rectangle
+/// has a side collinear with some hull edge.  For collinear inputs, the
+/// rectangle is degenerate; for empty inputs the corners are NaN.
+pub fn oriented_bounding_box(coords: &[Coordinate]) -> [Coordinate; 4] {
+    let hull = convex_hull(coords);
+    match hull.len() {
+        0 => return [Coordinate::new(f64::NAN, f64::NAN); 4],
+        1 => return [hull[0]; 4],
+        2 => return [hull[0], hull[1], hull[1], hull[0]],
+        _ => (),
+    }
+    let points = &hull[..hull.len() - 1];
+    let n = points.len();
+    let next = |i: usize| (i + 1) % n;
+
+    let mut best_area = f64::INFINITY;
+    let mut best = [points[0]; 4];
+    // Indices of the points furthest along, furthest from, and furthest back
+    // from each edge.
+    let mut right = 0;
+    let mut top = 0;
+    let mut left = 0;
+    for i in 0..n {
+        let origin = points[i];
+        let edge = points[next(i)] - origin;
+        let u = edge * (1. / edge.dot(edge).sqrt());
+        let v = Coordinate::new(-u.y, u.x);
+        let along = |c: Coordinate| (c - origin).dot(u);
+        let across = |c: Coordinate| (c - origin).dot(v);
+
+        if i == 0 {
+            right = (0..n)
+                .max_by(|&a, &b| along(points[a]).total_cmp(&along(points[b])))
+                .unwrap();
+            top = (0..n)
+                .max_by(|&a, &b| across(points[a]).total_cmp(&across(points[b])))
+                .unwrap();
+            left = (0..n)
+                .min_by(|&a, &b| along(points[a]).total_cmp(&along(points[b])))
+                .unwrap();
+        } else {
+            while along(points[next(right)]) > along(points[right]) {
+                right = next(right);
+            }
+            while across(points[next(top)]) > across(points[top]) {
+                top = next(top);
+            }
+            while along(points[next(left)]) < along(points[left]) {
+                left = next(left);
+            }
+        }
+
+        let min_u = along(points[left]);
+        let max_u = along(points[right]);
+        let height = across(points[top]);
+        let area = (max_u - min_u) * height;
+        if area < best_area {
+            best_area = area;
+            best = [
+                origin + u * min_u,
+                origin + u * max_u,
+                origin + u * max_u + v * height,
+                origin + u * min_u + v * height,
+            ];
+        }
+    }
+    best
+}
+
+/// The smallest rectangle enclosing coords with its sides at the given
+/// angle (in radians, counter-clockwise from the x axis), returned as its
+/// four corners in counter-clockwise order.  This suits a known preferred
+/// orientation, eg a road's bearing.  For empty inputs the corners are NaN.
+pub fn bounding_box_at_angle(coords: &[Coordinate], angle_rad: f64) -> [Coordinate; 4] {
+    let rotated: Vec<Coordinate> = coords.iter().map(|&c| rotate(c, -angle_rad)).collect();
+    let rect = Rectangle::of(&rotated);
+    [
+        Coordinate::new(rect.x_min, rect.y_min),
+        Coordinate::new(rect.x_max, rect.y_min),
+        Coordinate::new(rect.x_max, rect.y_max),
+        Coordinate::new(rect.x_min, rect.y_max),
+    ]
+    .map(|corner| rotate(corner, angle_rad))
+}
+
+/// The farthest pair of coords, as their indices and distance, eg for
+/// measuring elongation.
+///
+/// This uses rotating calipers over the convex hull: the farthest pair are
+/// hull vertices on parallel lines of support.  A single point is its own
+/// farthest pair, at distance 0; for empty inputs the distance is NaN.
+pub fn diameter(coords: &[Coordinate]) -> (usize, usize, f64) {
+    let hull = convex_hull(coords);
+    let (a, b) = match hull.len() {
+        0 => return (0, 0, f64::NAN),
+        1 => (hull[0], hull[0]),
+        2 => (hull[0], hull[1]),
+        _ => {
+            let points = &hull[..hull.len() - 1];
+            let n = points.len();
+            let next = |i: usize| (i + 1) % n;
+            let mut best = (points[0], points[0]);
+            let mut best_distance = 0.;
+            // The point furthest from each edge
+            let mut far = 1;
+            for i in 0..n {
+                let (start, end) = (points[i], points[next(i)]);
+                while orientation(start, end, points[next(far)])
+                    > orientation(start, end, points[far])
+                {
+                    far = next(far);
+                }
+                for &vertex in &[start, end] {
+                    let distance = vertex.distance(points[far]);
+                    if distance > best_distance {
+                        best_distance = distance;
+                        best = (vertex, points[far]);
+                    }
+                }
+            }
+            best
+        }
+    };
+    // The hull's points are copied from coords.
+    let index_of = |point: Coordinate| coords.iter().position(|&c| c == point).unwrap();
+    (index_of(a), index_of(b), a.distance(b))
+}
+
+/// Rotate coord counter-clockwise about the origin.
+fn rotate(coord: Coordinate, angle_rad: f64) -> Coordinate {
+    let (sin, cos) = angle_rad.sin_cos();
+    Coordinate::new(coord.x * cos - coord.y * sin, coord.x * sin + coord.y * cos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::signed_area;
+
+    #[test]
+    fn test_convex_hull() {
+        let coords = Coordinate::vec_from(&[
+            (0., 0.),
+            (2., 0.),
+            (1., 1.),
+            (2., 2.),
+            (1., 0.),
+            (0., 2.),
+            (0.5, 1.5),
+        ]);
+        assert_eq!(
+            convex_hull(&coords),
+            Coordinate::vec_from(&[(0., 0.), (2., 0.), (2., 2.), (0., 2.), (0., 0.)])
+        );
+        let line = Coordinate::vec_from(&[(0., 0.), (2., 2.), (1., 1.)]);
+        assert_eq!(
+            convex_hull(&line),
+            Coordinate::vec_from(&[(0., 0.), (2., 2.)])
+        );
+        assert_eq!(convex_hull(&[]), vec![]);
+    }
+
+    #[test]
+    fn test_oriented_bounding_box() {
+        // A 4x1 rectangle rotated by 30 degrees, with some interior points.
+        let (sin, cos) = (30f64).to_radians().sin_cos();
+        let rotate = |x: f64, y: f64| Coordinate::new(x * cos - y * sin, x * sin + y * cos);
+        let mut coords = vec![
+            rotate(0., 0.),
+            rotate(4., 0.),
+            rotate(4., 1.),
+            rotate(0., 1.),
+        ];
+        coords.extend((1..8).map(|i| rotate(i as f64 / 2., 0.5)));
+        coords.push(rotate(2., 0.));
+
+        let corners = oriented_bounding_box(&coords);
+        let mut ring = corners.to_vec();
+        ring.push(corners[0]);
+        assert!((signed_area(&ring) - 4.).abs() < 1e-12);
+        for corner in &[
+            rotate(0., 0.),
+            rotate(4., 0.),
+            rotate(4., 1.),
+            rotate(0., 1.),
+        ] {
+            assert!(corners.iter().any(|c| c.distance(*corner) < 1e-12));
+        }
+    }
+
+    #[test]
+    fn test_diameter() {
+        let coords = Coordinate::vec_from(&[
+            (1., 1.),
+            (4., 0.),
+            (2., 2.),
+            (0., 3.),
+            (-1., -1.),
+            (3., 2.5),
+            (5., 4.),
+        ]);
+        let (a, b, distance) = diameter(&coords);
+        assert_eq!((a.min(b), a.max(b)), (4, 6));
+        assert_eq!(distance, 61_f64.sqrt());
+
+        assert_eq!(diameter(&coords[..1]), (0, 0, 0.));
+        let (a, b, distance) = diameter(&coords[..2]);
+        assert_eq!((a.min(b), a.max(b), distance), (0, 1, 10_f64.sqrt()));
+        let collinear = Coordinate::vec_from(&[(1., 1.), (0., 0.), (3., 3.), (2., 2.)]);
+        let (a, b, _) = diameter(&collinear);
+        assert_eq!((a.min(b), a.max(b)), (1, 2));
+        assert!(diameter(&[]).2.is_nan());
+    }
+
+    #[test]
+    fn test_bounding_box_at_angle() {
+        let diamond = Coordinate::vec_from(&[(1., 0.), (0., 1.), (-1., 0.), (0., -1.), (0., 0.)]);
+        let corners = bounding_box_at_angle(&diamond, 45f64.to_radians());
+        let mut ring = corners.to_vec();
+        ring.push(corners[0]);
+        assert!((signed_area(&ring) - 2.).abs() < 1e-12);
+        for corner in &diamond[..4] {
+            assert!(corners.iter().any(|c| c.distance(*corner) < 1e-12));
+        }
+
+        // At angle 0, this is the axis-aligned envelope.
+        assert_eq!(
+            bounding_box_at_angle(&diamond, 0.),
+            [
+                Coordinate::new(-1., -1.),
+                Coordinate::new(1., -1.),
+                Coordinate::new(1., 1.),
+                Coordinate::new(-1., 1.),
+            ]
+        );
+        assert!(bounding_box_at_angle(&[], 1.)[0].x.is_nan());
+    }
+}
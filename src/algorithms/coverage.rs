@@ -0,0 +1,138 @@
+use super::point_in_polygon::{point_in_polygon, ContainRelation};
+use crate::errors::CoverageError;
+use crate::geometry_state::{HasRTree, Validated};
+use crate::utils::intersect_segments;
+use crate::{Coordinate, Flatbush, HasEnvelope, Polygon, Rectangle};
+
+const COVERAGE_DEGREE: usize = 16;
+
+/// Check that no two polygons overlap in area, eg for administrative
+/// boundaries.  Polygons may share edges or vertices, and one may fill
+/// another's hole.
+///
+/// Candidate pairs come from an index of the polygon envelopes.  Returns the
+/// overlapping pair with the lowest indices, if any.
+pub fn validate_coverage(polygons: &[Polygon<Validated>]) -> Result<(), CoverageError> {
+    let envelopes: Vec<Rectangle> = polygons.iter().map(|p| p.envelope()).collect();
+    let index = Flatbush::new(COVERAGE_DEGREE, &envelopes);
+    let mut candidates = index.query_self_intersections();
+    candidates.sort_unstable();
+    for (first, second) in candidates {
+        if interiors_overlap(&polygons[first], &polygons[second]) {
+            return Err(CoverageError::Overlap { first, second });
+        }
+    }
+    Ok(())
+}
+
+fn interiors_overlap(a: &Polygon<Validated>, b: &Polygon<Validated>) -> bool {
+    if a.shell().coords().is_empty() || b.shell().coords().is_empty() {
+        return false;
+    }
+    // If no boundary enters the other's interior, the polygons are disjoint
+    // or the same, which the interior points distinguish.
+    boundary_enters(a, b)
+        || boundary_enters(b, a)
+        || point_in_polygon(a.representative_point(), b) == ContainRelation::Interior
+}
+
+/// Whether any part of a's boundary is in b's interior.  Each segment of a is
+/// split where it meets b's boundary, and the midpoints of the pieces tested.
+fn boundary_enters(a: &Polygon<Validated>, b: &Polygon<Validated>) -> bool {
+    let rings_a = std::iter::once(a.shell()).chain(a.holes());
+    for ring in rings_a {
+        for (start, end) in ring.segments() {
+            let direction = end - start;
+            let position = |c: Coordinate| (c - start).dot(direction) / direction.dot(direction);
+            let mut splits = vec![0., 1.];
+            for other in std::iter::once(b.shell()).chain(b.holes()) {
+                let coords = other.coords();
+                for i in other.rtree().query_rect(Rectangle::new(start, end)) {
+                    if let Some((isxn_start, isxn_end)) =
+                        intersect_segments(start, end, coords[i], coords[i + 1])
+                    {
+                        splits.push(position(isxn_start));
+                        splits.push(position(isxn_end));
+                    }
+                }
+            }
+            splits.sort_by(|x, y| x.total_cmp(y));
+            let inside = splits.windows(2).any(|w| {
+                w[0] < w[1]
+                    && point_in_polygon(start.lerp(end, (w[0] + w[1]) / 2.), b)
+                        == ContainRelation::Interior
+            });
+            if inside {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LinearRing;
+    use std::convert::TryFrom;
+
+    fn rect_polygon(x_min: f64, y_min: f64, x_max: f64, y_max: f64) -> Polygon<Validated> {
+        let shell = LinearRing::try_from(vec![
+            (x_min, y_min),
+            (x_max, y_min),
+            (x_max, y_max),
+            (x_min, y_max),
+            (x_min, y_min),
+        ])
+        .unwrap();
+        Polygon::try_new(shell, Vec::new()).unwrap()
+    }
+
+    #[test]
+    fn test_valid_coverage() {
+        // A 2x2 grid of squares, sharing edges and the center vertex.
+        let mut polygons = vec![
+            rect_polygon(0., 0., 1., 1.),
+            rect_polygon(1., 0., 2., 1.),
+            rect_polygon(0., 1., 1., 2.),
+            rect_polygon(1., 1., 2., 2.),
+        ];
+        assert_eq!(validate_coverage(&polygons), Ok(()));
+
+        // A frame, with a square filling its hole.
+        let shell = LinearRing::try_from(vec![(3., 0.), (6., 0.), (6., 3.), (3., 3.), (3., 0.)]);
+        let hole = LinearRing::try_from(vec![(4., 1.), (5., 1.), (5., 2.), (4., 2.), (4., 1.)]);
+        polygons.push(Polygon::try_new(shell.unwrap(), vec![hole.unwrap()]).unwrap());
+        polygons.push(rect_polygon(4., 1., 5., 2.));
+        assert_eq!(validate_coverage(&polygons), Ok(()));
+        assert_eq!(validate_coverage(&[]), Ok(()));
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let polygons = vec![
+            rect_polygon(0., 0., 1., 1.),
+            rect_polygon(5., 5., 6., 6.),
+            rect_polygon(0.5, 0.5, 1.5, 1.5),
+        ];
+        assert_eq!(
+            validate_coverage(&polygons),
+            Err(CoverageError::Overlap {
+                first: 0,
+                second: 2
+            })
+        );
+
+        // Containment, and an identical copy
+        let inner = vec![rect_polygon(0., 0., 4., 4.), rect_polygon(1., 1., 2., 2.)];
+        assert!(validate_coverage(&inner).is_err());
+        let copies = vec![rect_polygon(0., 0., 1., 1.), rect_polygon(0., 0., 1., 1.)];
+        assert!(validate_coverage(&copies).is_err());
+        // A diamond through the square's edge midpoints crosses only at
+        // vertices.
+        let shell =
+            LinearRing::try_from(vec![(0.5, 0.), (1., 0.5), (0.5, 1.), (0., 0.5), (0.5, 0.)]);
+        let diamond = Polygon::try_new(shell.unwrap(), Vec::new()).unwrap();
+        assert!(validate_coverage(&[rect_polygon(0., 0., 1., 1.), diamond]).is_err());
+    }
+}
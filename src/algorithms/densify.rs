@@ -0,0 +1,38 @@
+use crate::Coordinate;
+
+/// Insert n evenly spaced points into every segment of coords, eg to give
+/// each segment a fixed vertex budget when rendering.  The original
+/// coordinates are kept, so a path of k segments gets k * n more.
+pub fn densify_n(coords: &[Coordinate], n: usize) -> Vec<Coordinate> {
+    let mut densified = Vec::with_capacity(coords.len() + coords.len().saturating_sub(1) * n);
+    for segment in coords.windows(2) {
+        densified.push(segment[0]);
+        densified
+            .extend((1..=n).map(|step| segment[0].lerp(segment[1], step as f64 / (n + 1) as f64)));
+    }
+    densified.extend(coords.last());
+    densified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_densify_n() {
+        let segment = Coordinate::vec_from(&[(0., 0.), (3., 6.)]);
+        assert_eq!(
+            densify_n(&segment, 2),
+            Coordinate::vec_from(&[(0., 0.), (1., 2.), (2., 4.), (3., 6.)])
+        );
+        assert_eq!(densify_n(&segment, 0), segment);
+
+        let path = Coordinate::vec_from(&[(0., 0.), (2., 0.), (2., 4.)]);
+        assert_eq!(
+            densify_n(&path, 1),
+            Coordinate::vec_from(&[(0., 0.), (1., 0.), (2., 0.), (2., 2.), (2., 4.)])
+        );
+        assert!(densify_n(&[], 3).is_empty());
+        assert_eq!(densify_n(&path[..1], 3), &path[..1]);
+    }
+}
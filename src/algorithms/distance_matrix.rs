@@ -0,0 +1,78 @@
+use crate::{Coordinate, Flatbush, HasEnvelope, Rectangle};
+
+const KNN_DEGREE: usize = 16;
+
+/// The pairwise distances of points, as a flat lower-triangular matrix: the
+/// distance between points i and j, for j < i, is at index i*(i-1)/2 + j.
+///
+/// This is O(n^2) in time and space; see knn_graph for large point sets.
+pub fn distance_matrix(points: &[Coordinate]) -> Vec<f64> {
+    let n = points.len();
+    let mut distances = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+    for (i, &point) in points.iter().enumerate() {
+        distances.extend(points[..i].iter().map(|&other| point.distance(other)));
+    }
+    distances
+}
+
+/// For each point, the indices of its (up to) k nearest other points,
+/// closest first.  Ties are in no particular order.
+pub fn knn_graph(points: &[Coordinate], k: usize) -> Vec<Vec<usize>> {
+    let envelopes: Vec<Rectangle> = points.iter().map(|p| p.envelope()).collect();
+    let index = Flatbush::new(KNN_DEGREE, &envelopes);
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &point)| {
+            // The point itself is among the nearest, so ask for one more.
+            let mut neighbors = index.query_nearest(point, k + 1);
+            neighbors.retain(|&j| j != i);
+            neighbors.truncate(k);
+            neighbors
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_matrix() {
+        assert!(distance_matrix(&[]).is_empty());
+        assert!(distance_matrix(&[Coordinate::new(1., 1.)]).is_empty());
+
+        let points = Coordinate::vec_from(&[(0., 0.), (3., 0.), (3., 4.)]);
+        assert_eq!(distance_matrix(&points), vec![3., 5., 4.]);
+    }
+
+    #[test]
+    fn test_knn_graph() {
+        let points: Vec<Coordinate> = (0..9)
+            .map(|i| Coordinate::new((i % 3) as f64, (i / 3) as f64))
+            .collect();
+        let graph = knn_graph(&points, 4);
+        assert_eq!(graph.len(), 9);
+
+        let mut center = graph[4].clone();
+        center.sort_unstable();
+        assert_eq!(center, vec![1, 3, 5, 7]);
+
+        let matrix = distance_matrix(&points);
+        let distance = |i: usize, j: usize| {
+            let (i, j) = if i > j { (i, j) } else { (j, i) };
+            matrix[i * (i - 1) / 2 + j]
+        };
+        for (i, neighbors) in graph.iter().enumerate() {
+            assert_eq!(neighbors.len(), 4);
+            assert!(!neighbors.contains(&i));
+            let found: Vec<f64> = neighbors.iter().map(|&j| distance(i, j)).collect();
+            let mut expected: Vec<f64> =
+                (0..9).filter(|&j| j != i).map(|j| distance(i, j)).collect();
+            expected.sort_by(|a, b| a.total_cmp(b));
+            assert_eq!(found, expected[..4]);
+        }
+
+        assert_eq!(knn_graph(&points[..2], 5), vec![vec![1], vec![0]]);
+    }
+}
@@ -0,0 +1,190 @@
+use super::point_in_polygon::{point_in_loop, ContainRelation};
+use crate::geometry_state::{HasRTree, Raw, Validated};
+use crate::utils::{orientation, signed_area};
+use crate::{Coordinate, HasEnvelope, LineString, LinearRing, Polygon};
+
+/// Intersect polygon a with the convex polygon b.
+///
+/// Each ring of a is clipped against the edges of b by Sutherland-Hodgman,
+/// so the rings keep their orientation.  NB: b must be convex (see
+/// Polygon::is_convex); otherwise the result is unspecified.  If the
+/// intersection has several pieces, they are returned as one polygon whose
+/// pieces are joined along b's boundary, so validate the result to detect
+/// this.
+pub fn intersection(a: &Polygon<Validated>, b: &Polygon<Validated>) -> Vec<Polygon<Raw>> {
+    debug_assert!(b.is_convex() || b.shell().coords().is_empty());
+    if !a.envelope().intersects(b.envelope()) {
+        return Vec::new();
+    }
+
+    // If the shells don't cross, one is inside the other, or they are
+    // disjoint.
+    let shell_a = a.shell();
+    let shell_b = b.shell();
+    if shell_a
+        .rtree()
+        .query_other_intersections(shell_b.rtree())
+        .is_empty()
+    {
+        if point_in_loop(shell_a.coords()[0], shell_b) != ContainRelation::Exterior {
+            return vec![a.to_raw()];
+        }
+        if point_in_loop(shell_b.coords()[0], shell_a) == ContainRelation::Exterior {
+            return Vec::new();
+        }
+        // b is inside a's shell, but may be inside one of its holes.
+        let in_hole = a.holes().iter().any(|hole| {
+            hole.rtree()
+                .query_other_intersections(shell_b.rtree())
+                .is_empty()
+                && point_in_loop(shell_b.coords()[0], hole) == ContainRelation::Interior
+        });
+        if in_hole {
+            return Vec::new();
+        }
+    }
+
+    let mut clip_ring = shell_b.coords().clone();
+    if signed_area(&clip_ring) < 0. {
+        clip_ring.reverse();
+    }
+    let shell = match clip_ring_coords(shell_a.coords(), &clip_ring) {
+        Some(shell) => shell,
+        None => return Vec::new(),
+    };
+    let holes = a
+        .holes()
+        .iter()
+        .filter_map(|hole| clip_ring_coords(hole.coords(), &clip_ring))
+        .collect();
+    vec![Polygon::<Raw>::new(shell, holes)]
+}
+
+/// Clip the closed ring of coords to the counter-clockwise convex
+/// clip_ring, or None if nothing of it remains.
+fn clip_ring_coords(coords: &[Coordinate], clip_ring: &[Coordinate]) -> Option<LinearRing<Raw>> {
+    // Work with open loops, dropping the closing coordinates.
+    let mut output: Vec<Coordinate> = coords[..coords.len() - 1].to_vec();
+    for edge in clip_ring.windows(2) {
+        let (start, end) = (edge[0], edge[1]);
+        let input = std::mem::take(&mut output);
+        for (i, &current) in input.iter().enumerate() {
+            let previous = input[(i + input.len() - 1) % input.len()];
+            let current_inside = orientation(start, end, current) >= 0.;
+            let previous_inside = orientation(start, end, previous) >= 0.;
+            if current_inside != previous_inside {
+                output.push(line_crossing(previous, current, start, end));
+            }
+            if current_inside {
+                output.push(current);
+            }
+        }
+        if output.is_empty() {
+            return None;
+        }
+    }
+
+    output.dedup();
+    if output.first() == output.last() {
+        output.pop();
+    }
+    if output.len() < 3 {
+        return None;
+    }
+    output.push(output[0]);
+    LineString::new(output).into_ring().ok()
+}
+
+/// Where the segment from a to b crosses the line through start and end.
+fn line_crossing(a: Coordinate, b: Coordinate, start: Coordinate, end: Coordinate) -> Coordinate {
+    let side_a = orientation(start, end, a);
+    let side_b = orientation(start, end, b);
+    a.lerp(b, side_a / (side_a - side_b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn make_polygon(shell: Vec<(f64, f64)>, holes: Vec<Vec<(f64, f64)>>) -> Polygon<Validated> {
+        let shell = LinearRing::try_from(shell).unwrap();
+        let holes = holes
+            .into_iter()
+            .map(|hole| LinearRing::try_from(hole).unwrap())
+            .collect();
+        Polygon::try_new(shell, holes).unwrap()
+    }
+
+    fn validated_area(polygon: Polygon<Raw>) -> f64 {
+        polygon.prepare().validate().unwrap().area()
+    }
+
+    #[test]
+    fn test_overlapping_quads() {
+        let a = make_polygon(
+            vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)],
+            vec![],
+        );
+        // A diamond centered on a's top-right corner, clockwise.
+        let b = make_polygon(
+            vec![(4., 2.), (2., 4.), (4., 6.), (6., 4.), (4., 2.)],
+            vec![],
+        );
+        let output = intersection(&a, &b);
+        assert_eq!(output.len(), 1);
+        let expected = LinearRing::try_from(vec![(4., 2.), (4., 4.), (2., 4.), (4., 2.)]).unwrap();
+        assert!(output[0].shell().equals_rotation(&expected.to_raw()));
+        assert_eq!(validated_area(output[0].clone()), 2.);
+
+        let output = intersection(&b, &a);
+        assert_eq!(output.len(), 1);
+        assert_eq!(validated_area(output[0].clone()), 2.);
+    }
+
+    #[test]
+    fn test_holes() {
+        let a = make_polygon(
+            vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)],
+            vec![vec![(1., 1.), (1., 3.), (3., 3.), (3., 1.), (1., 1.)]],
+        );
+        // Covers the left half of a, and the left half of its hole.
+        let b = make_polygon(
+            vec![(-1., -1.), (2., -1.), (2., 5.), (-1., 5.), (-1., -1.)],
+            vec![],
+        );
+        let output = intersection(&a, &b);
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].holes().len(), 1);
+        assert_eq!(output[0].area(), 8. - 2.);
+    }
+
+    #[test]
+    fn test_disjoint_and_contained() {
+        let a = make_polygon(
+            vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)],
+            vec![],
+        );
+        let inside = make_polygon(vec![(1., 1.), (2., 1.), (2., 2.), (1., 1.)], vec![]);
+        let outside = make_polygon(vec![(5., 5.), (6., 5.), (6., 6.), (5., 5.)], vec![]);
+        assert_eq!(intersection(&inside, &a), vec![inside.to_raw()]);
+        assert_eq!(intersection(&a, &inside).len(), 1);
+        assert_eq!(intersection(&a, &inside)[0].area(), 0.5);
+        assert!(intersection(&a, &outside).is_empty());
+        // Touching at a corner leaves nothing.
+        let corner = make_polygon(vec![(4., 4.), (5., 4.), (5., 5.), (4., 4.)], vec![]);
+        assert!(intersection(&a, &corner).is_empty());
+
+        let holed = make_polygon(
+            vec![(0., 0.), (10., 0.), (10., 10.), (0., 10.), (0., 0.)],
+            vec![vec![(2., 2.), (2., 8.), (8., 8.), (8., 2.), (2., 2.)]],
+        );
+        let in_hole = make_polygon(vec![(4., 4.), (6., 4.), (5., 6.), (4., 4.)], vec![]);
+        assert!(intersection(&holed, &in_hole).is_empty());
+        // Inside the shell, but not the hole
+        let beside_hole = make_polygon(vec![(0.5, 0.5), (1.5, 0.5), (1., 1.5), (0.5, 0.5)], vec![]);
+        let pieces = intersection(&holed, &beside_hole);
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(validated_area(pieces[0].clone()), beside_hole.area());
+    }
+}
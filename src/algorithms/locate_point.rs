@@ -0,0 +1,62 @@
+use super::point_in_polygon::{point_in_polygon, ContainRelation};
+use crate::geometry_state::Validated;
+use crate::{Coordinate, Flatbush, HasEnvelope, Polygon};
+
+/// Find the smallest polygon containing point (in its interior or on its
+/// boundary), eg to find the innermost of nested regions.  The index must be
+/// built from the polygons' envelopes, in order.
+///
+/// Equal areas are broken by the lower index.  Returns None if no polygon
+/// contains the point.
+pub fn locate_point(
+    point: Coordinate,
+    polygons: &[Polygon<Validated>],
+    index: &Flatbush,
+) -> Option<usize> {
+    let mut candidates = index.query_rect(point.envelope());
+    candidates.sort_unstable();
+    candidates
+        .into_iter()
+        .filter(|&i| point_in_polygon(point, &polygons[i]) != ContainRelation::Exterior)
+        .map(|i| (i, polygons[i].area()))
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LinearRing, Rectangle};
+    use std::convert::TryFrom;
+
+    fn square(min: f64, max: f64) -> Polygon<Validated> {
+        let shell = LinearRing::try_from(vec![
+            (min, min),
+            (max, min),
+            (max, max),
+            (min, max),
+            (min, min),
+        ])
+        .unwrap();
+        Polygon::try_new(shell, Vec::new()).unwrap()
+    }
+
+    #[test]
+    fn test_locate_point() {
+        // Nested squares, with the innermost not first.
+        let polygons = vec![
+            square(0., 10.),
+            square(4., 6.),
+            square(2., 8.),
+            square(20., 30.),
+        ];
+        let envelopes: Vec<Rectangle> = polygons.iter().map(|p| p.envelope()).collect();
+        let index = Flatbush::new(16, &envelopes);
+
+        assert_eq!(locate_point((5., 5.).into(), &polygons, &index), Some(1));
+        assert_eq!(locate_point((3., 3.).into(), &polygons, &index), Some(2));
+        assert_eq!(locate_point((1., 5.).into(), &polygons, &index), Some(0));
+        assert_eq!(locate_point((25., 25.).into(), &polygons, &index), Some(3));
+        assert_eq!(locate_point((15., 15.).into(), &polygons, &index), None);
+    }
+}
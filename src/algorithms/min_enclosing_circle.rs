@@ -0,0 +1,133 @@
+use crate::Coordinate;
+
+const DEFAULT_SEED: u64 = 0x5eed;
+
+// Relative slack for points on the circle, to absorb rounding.
+const EPSILON: f64 = 1e-12;
+
+/// The smallest circle containing coords, as (center, radius), or None if
+/// coords is empty.
+///
+/// This is Welzl's algorithm, which is expected O(n) for coords in random
+/// order.  The coords are shuffled with a fixed seed, so the result is
+/// deterministic; see min_enclosing_circle_seeded to choose the seed.
+pub fn min_enclosing_circle(coords: &[Coordinate]) -> Option<(Coordinate, f64)> {
+    min_enclosing_circle_seeded(coords, DEFAULT_SEED)
+}
+
+/// Like min_enclosing_circle, but shuffle the coords with the given seed.
+/// The same seed always gives the same result.
+pub fn min_enclosing_circle_seeded(coords: &[Coordinate], seed: u64) -> Option<(Coordinate, f64)> {
+    let mut points = coords.to_vec();
+    shuffle(&mut points, seed);
+
+    let mut circle = (*points.first()?, 0.);
+    for i in 1..points.len() {
+        if contains(circle, points[i]) {
+            continue;
+        }
+        // points[i] is on the boundary of the circle of points[..=i].
+        circle = (points[i], 0.);
+        for j in 0..i {
+            if contains(circle, points[j]) {
+                continue;
+            }
+            circle = circle_of_two(points[i], points[j]);
+            for k in 0..j {
+                if !contains(circle, points[k]) {
+                    circle = circle_of_three(points[i], points[j], points[k]);
+                }
+            }
+        }
+    }
+    Some(circle)
+}
+
+fn contains((center, radius): (Coordinate, f64), point: Coordinate) -> bool {
+    center.distance(point) <= radius * (1. + EPSILON) + EPSILON
+}
+
+fn circle_of_two(a: Coordinate, b: Coordinate) -> (Coordinate, f64) {
+    (a.lerp(b, 0.5), a.distance(b) / 2.)
+}
+
+/// The circumcircle of abc, or the circle of its furthest pair if they are
+/// collinear.
+fn circle_of_three(a: Coordinate, b: Coordinate, c: Coordinate) -> (Coordinate, f64) {
+    let ab = b - a;
+    let ac = c - a;
+    let det = 2. * ab.cross(ac);
+    if det == 0. {
+        let pairs = [(a, b), (a, c), (b, c)];
+        let &(start, end) = pairs
+            .iter()
+            .max_by(|p, q| p.0.distance(p.1).total_cmp(&q.0.distance(q.1)))
+            .unwrap();
+        return circle_of_two(start, end);
+    }
+    let ab_2 = ab.dot(ab);
+    let ac_2 = ac.dot(ac);
+    let offset = Coordinate::new(
+        (ac.y * ab_2 - ab.y * ac_2) / det,
+        (ab.x * ac_2 - ac.x * ab_2) / det,
+    );
+    (a + offset, offset.dot(offset).sqrt())
+}
+
+/// Fisher-Yates shuffle, driven by splitmix64.
+fn shuffle(points: &mut [Coordinate], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    for i in (1..points.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        points.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_enclosing_circle() {
+        assert_eq!(min_enclosing_circle(&[]), None);
+        let point = Coordinate::new(1., 2.);
+        assert_eq!(min_enclosing_circle(&[point]), Some((point, 0.)));
+
+        // The corners of a square, and its interior
+        let coords = Coordinate::vec_from(&[(0., 0.), (2., 0.), (1., 1.), (2., 2.), (0., 2.)]);
+        let (center, radius) = min_enclosing_circle(&coords).unwrap();
+        assert_eq!(center, Coordinate::new(1., 1.));
+        assert!((radius - 2_f64.sqrt()).abs() < 1e-12);
+
+        // An obtuse triangle is enclosed by the circle on its longest side.
+        let coords = Coordinate::vec_from(&[(0., 0.), (4., 0.), (2., 1.)]);
+        assert_eq!(
+            min_enclosing_circle(&coords),
+            Some((Coordinate::new(2., 0.), 2.))
+        );
+    }
+
+    #[test]
+    fn test_seeded() {
+        let coords: Vec<Coordinate> = (0..50)
+            .map(|i| {
+                let i = i as f64;
+                Coordinate::new((i * 7.3) % 11., (i * 3.1) % 5.)
+            })
+            .collect();
+        let circle = min_enclosing_circle_seeded(&coords, 42).unwrap();
+        assert_eq!(min_enclosing_circle_seeded(&coords, 42), Some(circle));
+        assert!(coords.iter().all(|&c| contains(circle, c)));
+
+        let other = min_enclosing_circle_seeded(&coords, 7).unwrap();
+        assert!(circle.0.distance(other.0) < 1e-9);
+        assert!((circle.1 - other.1).abs() < 1e-9);
+    }
+}
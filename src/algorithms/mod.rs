@@ -1,8 +1,51 @@
 mod clip;
+mod clip_polygon;
+mod concave_hull;
+mod convex_hull;
+mod coverage;
+mod densify;
+mod distance_matrix;
+mod intersection;
+mod locate_point;
+mod min_enclosing_circle;
 mod min_heap;
-mod point_in_polygon;
+mod nms;
+mod overlap;
+pub(crate) mod point_in_polygon;
 mod polygon_validation;
+mod polylabel;
+mod simplify;
+mod smooth;
+mod snap;
+mod spikes;
+mod triangulate;
 
-pub use clip::clip_path;
-pub use point_in_polygon::{point_in_loop, point_in_polygon};
-pub use polygon_validation::validate_polygon;
+pub use clip::{
+    clip_envelope, clip_multi_path, clip_path, clip_path_raw, clip_path_typed,
+    clip_path_with_indices, ClippedPiece, SectionSegments,
+};
+pub use clip_polygon::{clip_polygon, clipped_area};
+pub use concave_hull::concave_hull;
+pub use convex_hull::{bounding_box_at_angle, convex_hull, diameter, oriented_bounding_box};
+pub use coverage::validate_coverage;
+pub use densify::densify_n;
+pub use distance_matrix::{distance_matrix, knn_graph};
+pub use intersection::intersection;
+pub use locate_point::locate_point;
+pub use min_enclosing_circle::{min_enclosing_circle, min_enclosing_circle_seeded};
+pub use nms::nms;
+pub use overlap::overlap_length;
+pub use point_in_polygon::{
+    point_in_convex_loop, point_in_loop, point_in_loop_with_rule, point_in_loop_with_stack,
+    point_in_polygon, point_in_polygon_with_boundary, ray_crossings, FillRule,
+};
+pub use polygon_validation::{
+    validate_polygon, validate_polygon_report, validate_polygon_with_tolerance, ValidationIssue,
+    ValidationReport,
+};
+pub use polylabel::polylabel;
+pub use simplify::simplify_to_count;
+pub use smooth::{chaikin_smooth, chaikin_smooth_ring};
+pub use snap::snap_to_grid;
+pub use spikes::find_spikes;
+pub use triangulate::triangulate;
@@ -0,0 +1,62 @@
+use crate::{Flatbush, Rectangle};
+
+/// Non-maximum suppression: greedily keep the highest-scoring box, and drop
+/// the remaining boxes whose IoU with it exceeds iou_threshold.
+///
+/// Returns the indices of the kept boxes, by decreasing score.  Equal scores
+/// are broken by the lower index.  Panics if boxes and scores have
+/// different lengths.
+pub fn nms(boxes: &[Rectangle], scores: &[f64], iou_threshold: f64) -> Vec<usize> {
+    assert_eq!(
+        boxes.len(),
+        scores.len(),
+        "boxes and scores must have the same length"
+    );
+    let mut order: Vec<usize> = (0..boxes.len()).collect();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+
+    let index = Flatbush::new(16, boxes);
+    let mut suppressed = vec![false; boxes.len()];
+    let mut kept = Vec::new();
+    for i in order {
+        if suppressed[i] {
+            continue;
+        }
+        kept.push(i);
+        for candidate in index.query_rect(boxes[i]) {
+            if candidate != i && boxes[i].iou(boxes[candidate]) > iou_threshold {
+                suppressed[candidate] = true;
+            }
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_box(x_min: f64, y_min: f64, x_max: f64, y_max: f64) -> Rectangle {
+        Rectangle::new((x_min, y_min).into(), (x_max, y_max).into())
+    }
+
+    #[test]
+    fn test_suppress_overlapping() {
+        let boxes = vec![
+            make_box(0., 0., 2., 2.),
+            make_box(0.2, 0., 2.2, 2.),
+            make_box(1.9, 0., 3.9, 2.),
+        ];
+        // The middle box overlaps the first a lot, and the last a little
+        assert_eq!(nms(&boxes, &[0.9, 0.8, 0.7], 0.5), vec![0, 2]);
+        // If the middle box is the best, it still only suppresses the first
+        assert_eq!(nms(&boxes, &[0.8, 0.9, 0.7], 0.5), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_ties_and_empty() {
+        assert!(nms(&[], &[], 0.5).is_empty());
+        let boxes = vec![make_box(0., 0., 1., 1.), make_box(0., 0., 1., 1.)];
+        assert_eq!(nms(&boxes, &[0.5, 0.5], 0.5), vec![0]);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::geometry_state::{HasRTree, Prepared};
+use crate::utils::intersect_segments;
+use crate::LineString;
+
+/// The total length along which paths a and b coincide, eg to find
+/// duplicate road segments.
+///
+/// This sums the collinear overlaps of each pair of segments; segments that
+/// only cross or touch contribute nothing.  If a path doubles back over
+/// itself, an overlap is counted once per segment covering it.
+pub fn overlap_length(a: &LineString<Prepared>, b: &LineString<Prepared>) -> f64 {
+    let coords_a = a.coords();
+    let coords_b = b.coords();
+    a.rtree()
+        .query_other_intersections(b.rtree())
+        .into_iter()
+        .filter_map(|(index_a, index_b)| {
+            intersect_segments(
+                coords_a[index_a],
+                coords_a[index_a + 1],
+                coords_b[index_b],
+                coords_b[index_b + 1],
+            )
+        })
+        .map(|(start, end)| start.distance(end))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coordinate;
+
+    fn path(coords: &[(f64, f64)]) -> LineString<Prepared> {
+        LineString::new(Coordinate::vec_from(coords)).prepare()
+    }
+
+    #[test]
+    fn test_overlap_length() {
+        let a = path(&[(0., 0.), (4., 0.)]);
+        let b = path(&[(3., 0.), (1., 0.)]);
+        assert_eq!(overlap_length(&a, &b), 2.);
+        assert_eq!(overlap_length(&b, &a), 2.);
+
+        // Overlapping across several segments, then crossing
+        let b = path(&[(-1., 0.), (1., 0.), (3., 0.), (3., 1.), (2., -1.)]);
+        assert_eq!(overlap_length(&a, &b), 3.);
+
+        // Only crossing or touching
+        let b = path(&[(2., -1.), (2., 1.), (4., 0.), (5., 0.)]);
+        assert_eq!(overlap_length(&a, &b), 0.);
+        assert_eq!(overlap_length(&a, &path(&[])), 0.);
+    }
+}
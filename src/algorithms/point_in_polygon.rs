@@ -1,6 +1,10 @@
 use crate::geometry_state::{HasRTree, Validated};
-use crate::utils::{winding_number, WindingPosition};
-use crate::{Coordinate, HasEnvelope, LinearRing, Polygon, Rectangle};
+use crate::utils::{orientation, point_on_segment, winding_number, WindingPosition};
+use crate::{Coordinate, HasEnvelope, LinearRing, Polygon, Rectangle, SegRTree};
+
+// Above about this size, the rtree traversal beats scanning every edge of
+// a convex ring (see the point_in_convex_loop benchmark).
+const CONVEX_FAST_PATH_MAX_COORDS: usize = 33;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ContainRelation {
@@ -9,11 +13,31 @@ pub enum ContainRelation {
     Interior,
 }
 
+/// How the winding number of a loop around a point decides whether the point
+/// is inside.  The rules differ only for loops that overlap themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside if the loop winds around the point at all.
+    NonZero,
+    /// Inside if the loop crosses a ray from the point an odd number of
+    /// times, regardless of direction.
+    EvenOdd,
+}
+
+impl FillRule {
+    fn is_inside(self, winding_number: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding_number != 0,
+            FillRule::EvenOdd => winding_number % 2 != 0,
+        }
+    }
+}
+
 pub fn point_in_polygon(point: Coordinate, polygon: &Polygon<Validated>) -> ContainRelation {
     let shell_relation = point_in_loop(point, polygon.shell());
     if shell_relation == ContainRelation::Interior {
-        for hole in polygon.holes() {
-            match point_in_loop(point, hole) {
+        for hole_index in polygon.candidate_holes(point) {
+            match point_in_loop(point, &polygon.holes()[hole_index]) {
                 ContainRelation::Interior => return ContainRelation::Exterior,
                 ContainRelation::Boundary => return ContainRelation::Boundary,
                 ContainRelation::Exterior => (),
@@ -23,17 +47,101 @@ pub fn point_in_polygon(point: Coordinate, polygon: &Polygon<Validated>) -> Cont
     shell_relation
 }
 
+/// Whether the point is in the polygon, counting its boundary as inside if
+/// boundary_is_inside, else as outside.
+pub fn point_in_polygon_with_boundary(
+    point: Coordinate,
+    polygon: &Polygon<Validated>,
+    boundary_is_inside: bool,
+) -> bool {
+    match point_in_polygon(point, polygon) {
+        ContainRelation::Interior => true,
+        ContainRelation::Boundary => boundary_is_inside,
+        ContainRelation::Exterior => false,
+    }
+}
+
 pub fn point_in_loop(point: Coordinate, path: &LinearRing<Validated>) -> ContainRelation {
-    if !path.envelope().contains(point) {
+    if path.is_convex() && path.coords().len() <= CONVEX_FAST_PATH_MAX_COORDS {
+        return point_in_convex_loop(point, path);
+    }
+    point_in_coords_loop(point, path.coords(), path.rtree())
+}
+
+/// Like point_in_loop, for a convex ring: the point is in the interior if
+/// it is strictly on the inner side of every edge.  This is a linear scan,
+/// with no rtree traversal.  If the ring is not convex, the result is
+/// unspecified.
+pub fn point_in_convex_loop(point: Coordinate, ring: &LinearRing<Validated>) -> ContainRelation {
+    let sign = ring.signed_area().signum();
+    // On the line through an edge, but off the edge itself
+    let mut beyond_edge = false;
+    for (start, end) in ring.segments() {
+        let side = orientation(start, end, point) * sign;
+        if side < 0. {
+            return ContainRelation::Exterior;
+        } else if side == 0. {
+            if point_on_segment(point, start, end) {
+                return ContainRelation::Boundary;
+            }
+            beyond_edge = true;
+        }
+    }
+    if beyond_edge || ring.coords().is_empty() {
+        ContainRelation::Exterior
+    } else {
+        ContainRelation::Interior
+    }
+}
+
+/// Like point_in_loop, but decide the interior with the given fill rule.
+/// The ring need not be validated, so it may overlap itself.
+pub fn point_in_loop_with_rule<S: HasRTree>(
+    point: Coordinate,
+    ring: &LinearRing<S>,
+    rule: FillRule,
+) -> ContainRelation {
+    let mut stack = Vec::with_capacity(ring.rtree().height() * ring.rtree().degree());
+    point_in_coords_loop_with_stack(point, ring.coords(), ring.rtree(), &mut stack, rule)
+}
+
+/// Like point_in_loop, but use a caller-owned stack for the traversal, to
+/// avoid allocating on each call when probing many points.  The stack is
+/// cleared first.
+pub fn point_in_loop_with_stack(
+    point: Coordinate,
+    path: &LinearRing<Validated>,
+    stack: &mut Vec<(usize, usize)>,
+) -> ContainRelation {
+    point_in_coords_loop_with_stack(point, path.coords(), path.rtree(), stack, FillRule::NonZero)
+}
+
+/// Find the relation of point to the loop defined by coords, whose segments
+/// are indexed by rtree.  The caller must ensure coords form a closed loop.
+pub(crate) fn point_in_coords_loop(
+    point: Coordinate,
+    coords: &[Coordinate],
+    rtree: &SegRTree,
+) -> ContainRelation {
+    let mut stack = Vec::with_capacity(rtree.height() * rtree.degree());
+    point_in_coords_loop_with_stack(point, coords, rtree, &mut stack, FillRule::NonZero)
+}
+
+fn point_in_coords_loop_with_stack(
+    point: Coordinate,
+    coords: &[Coordinate],
+    rtree: &SegRTree,
+    stack: &mut Vec<(usize, usize)>,
+    rule: FillRule,
+) -> ContainRelation {
+    stack.clear();
+    if !rtree.envelope().contains(point) {
         return ContainRelation::Exterior;
     }
-    let coords = path.coords();
-    let rtree = path.rtree();
 
     let mut wn: i32 = 0;
 
     // Stack entries: (level, offset)
-    let mut stack = Vec::with_capacity(rtree.height() * rtree.degree());
     if check_point_rect(point, rtree.get_rectangle(rtree.height(), 0)) {
         stack.push((rtree.height(), 0));
     }
@@ -58,21 +166,114 @@ pub fn point_in_loop(point: Coordinate, path: &LinearRing<Validated>) -> Contain
             }
         } else {
             let child_level = level - 1;
-            let first_child_offset = rtree.degree() * offset;
-            for child_offset in first_child_offset..(first_child_offset + rtree.degree()) {
+            for child_offset in rtree.child_offsets(level, offset) {
                 if check_point_rect(point, rtree.get_rectangle(child_level, child_offset)) {
                     stack.push((child_level, child_offset));
                 }
             }
         }
     }
-    if wn != 0 {
+    if rule.is_inside(wn) {
         ContainRelation::Interior
     } else {
         ContainRelation::Exterior
     }
 }
 
+/// The net winding number of the loop formed by coords (whose segments are
+/// indexed by rtree) around point, found by casting a ray from point in the
+/// direction dir.
+///
+/// For points not on the loop, every nonzero dir gives the same result.  For
+/// points on the loop, the result is unspecified; use point_in_loop to detect
+/// the boundary.
+#[allow(clippy::float_cmp)]
+pub fn ray_crossings(
+    point: Coordinate,
+    rtree: &SegRTree,
+    coords: &[Coordinate],
+    dir: Coordinate,
+) -> i32 {
+    if rtree.is_empty() || (dir.x == 0. && dir.y == 0.) {
+        return 0;
+    }
+
+    // Rotate (and scale) coords so that point is the origin and dir is +x,
+    // then count crossings of the +x ray.
+    let origin = Coordinate::new(0., 0.);
+    let crossing = |start: Coordinate, end: Coordinate| {
+        let start = start - point;
+        let end = end - point;
+        let start = Coordinate::new(start.dot(dir), dir.cross(start));
+        let end = Coordinate::new(end.dot(dir), dir.cross(end));
+        match winding_number(origin, start, end) {
+            WindingPosition::Left => 1,
+            WindingPosition::Right => -1,
+            WindingPosition::On | WindingPosition::Off => 0,
+        }
+    };
+
+    let mut wn: i32 = 0;
+    let mut stack = Vec::with_capacity(rtree.height() * rtree.degree());
+    if check_ray_rect(point, dir, rtree.envelope()) {
+        stack.push(rtree.root());
+    }
+    while let Some((level, offset)) = stack.pop() {
+        let rect = rtree.get_rectangle(level, offset);
+        if check_rect_ahead(point, dir, rect) {
+            // The whole section is ahead of point, so it crosses the ray
+            // the same as the segment joining its ends.
+            let (low, high) = rtree.get_low_high(level, offset);
+            wn += crossing(coords[low], coords[high]);
+            continue;
+        }
+        if level == 0 {
+            wn += crossing(coords[offset], coords[offset + 1]);
+        } else {
+            let child_level = level - 1;
+            for child_offset in rtree.child_offsets(level, offset) {
+                if check_ray_rect(point, dir, rtree.get_rectangle(child_level, child_offset)) {
+                    stack.push((child_level, child_offset));
+                }
+            }
+        }
+    }
+    wn
+}
+
+// Check if the ray from point in direction dir intersects the rectangle
+fn check_ray_rect(point: Coordinate, dir: Coordinate, rect: Rectangle) -> bool {
+    if rect.is_empty() {
+        return false;
+    }
+    let mut t_min: f64 = 0.;
+    let mut t_max = f64::INFINITY;
+    for &(start, delta, low, high) in &[
+        (point.x, dir.x, rect.x_min, rect.x_max),
+        (point.y, dir.y, rect.y_min, rect.y_max),
+    ] {
+        if delta == 0. {
+            if start < low || start > high {
+                return false;
+            }
+        } else {
+            let t0 = (low - start) / delta;
+            let t1 = (high - start) / delta;
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+        }
+    }
+    t_min <= t_max
+}
+
+// Check if the rectangle is strictly ahead of point, in direction dir
+fn check_rect_ahead(point: Coordinate, dir: Coordinate, rect: Rectangle) -> bool {
+    // The corner of the rectangle furthest back along dir
+    let x = if dir.x > 0. { rect.x_min } else { rect.x_max };
+    let y = if dir.y > 0. { rect.y_min } else { rect.y_max };
+    (Coordinate::new(x, y) - point).dot(dir) > 0.
+}
+
 // Check if a point is in the rectangle, or to its left
 fn check_point_rect(point: Coordinate, rect: Rectangle) -> bool {
     point.x <= rect.x_max && point.y >= rect.y_min && point.y <= rect.y_max
@@ -81,6 +282,7 @@ fn check_point_rect(point: Coordinate, rect: Rectangle) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::LineString;
     use std::convert::TryFrom;
 
     #[test]
@@ -108,6 +310,135 @@ mod tests {
             ContainRelation::Exterior
         );
     }
+
+    #[test]
+    fn check_containment_with_stack() {
+        let loop_a =
+            LinearRing::try_from(vec![(0., 0.), (0., 2.), (2., 2.), (2., 0.), (0., 0.)]).unwrap();
+        let mut stack = vec![(7, 7)];
+        for &(x, y) in &[(1., 1.), (0., 1.), (3., 1.), (1.5, 0.5), (-1., -1.)] {
+            let point = Coordinate::new(x, y);
+            assert_eq!(
+                point_in_loop_with_stack(point, &loop_a, &mut stack),
+                point_in_loop(point, &loop_a)
+            );
+        }
+    }
+
+    #[test]
+    fn check_convex_loop() {
+        // A hexagon, clockwise, with a collinear vertex at (2, 0)
+        let hexagon = LinearRing::try_from(vec![
+            (1., 0.),
+            (0., 1.),
+            (1., 2.),
+            (3., 2.),
+            (4., 1.),
+            (3., 0.),
+            (2., 0.),
+            (1., 0.),
+        ])
+        .unwrap();
+        assert!(hexagon.is_convex());
+        for i in 0..=50 {
+            for j in 0..=30 {
+                let point = Coordinate::new(i as f64 * 0.1 - 0.5, j as f64 * 0.1 - 0.5);
+                let expected = point_in_coords_loop(point, hexagon.coords(), hexagon.rtree());
+                assert_eq!(point_in_convex_loop(point, &hexagon), expected);
+                assert_eq!(point_in_loop(point, &hexagon), expected);
+            }
+        }
+        // On the lines through the edges, beyond them
+        assert_eq!(
+            point_in_convex_loop((5., 0.).into(), &hexagon),
+            ContainRelation::Exterior
+        );
+        assert_eq!(
+            point_in_convex_loop((2., 0.).into(), &hexagon),
+            ContainRelation::Boundary
+        );
+
+        let notched = LinearRing::try_from(vec![
+            (0., 0.),
+            (2., 0.),
+            (1., 1.),
+            (2., 2.),
+            (0., 2.),
+            (0., 0.),
+        ])
+        .unwrap();
+        assert!(!notched.is_convex());
+        assert!(!LinearRing::empty().is_convex());
+    }
+
+    #[test]
+    fn check_fill_rule() {
+        // A pentagram, whose center is wound around twice.
+        let star = LineString::new(Coordinate::vec_from(&[
+            (0., 10.),
+            (5.878, -8.09),
+            (-9.511, 3.09),
+            (9.511, 3.09),
+            (-5.878, -8.09),
+            (0., 10.),
+        ]))
+        .into_ring()
+        .unwrap()
+        .prepare();
+        let center = Coordinate::new(0., 0.);
+        assert_eq!(
+            point_in_loop_with_rule(center, &star, FillRule::NonZero),
+            ContainRelation::Interior
+        );
+        assert_eq!(
+            point_in_loop_with_rule(center, &star, FillRule::EvenOdd),
+            ContainRelation::Exterior
+        );
+
+        for &rule in &[FillRule::NonZero, FillRule::EvenOdd] {
+            let tip = Coordinate::new(0., 7.);
+            assert_eq!(
+                point_in_loop_with_rule(tip, &star, rule),
+                ContainRelation::Interior
+            );
+            let outside = Coordinate::new(5., 8.);
+            assert_eq!(
+                point_in_loop_with_rule(outside, &star, rule),
+                ContainRelation::Exterior
+            );
+        }
+    }
+
+    #[test]
+    fn check_ray_crossings() {
+        // A comb with 4 teeth, to have many segments and concavities.
+        let mut coords = vec![(0., 0.)];
+        for i in 0..4 {
+            let x = 2. * i as f64;
+            coords.extend_from_slice(&[(x, 5.), (x + 1., 5.), (x + 1., 1.), (x + 2., 1.)]);
+        }
+        coords.extend_from_slice(&[(8., 0.), (0., 0.)]);
+        let ring = LinearRing::try_from(coords).unwrap();
+
+        let dirs: Vec<Coordinate> =
+            Coordinate::vec_from(&[(1., 0.), (0., 1.), (-1., 0.), (-1., -1.), (0.3, -2.)]);
+        for &(x, y) in &[
+            (0.5, 0.5),
+            (0.5, 3.),
+            (1.5, 3.),
+            (6.5, 4.),
+            (7.9, 0.2),
+            (9., 0.5),
+        ] {
+            let point = Coordinate::new(x, y);
+            let inside = point_in_loop(point, &ring) == ContainRelation::Interior;
+            for &dir in &dirs {
+                let wn = ray_crossings(point, ring.rtree(), ring.coords(), dir);
+                assert_eq!(wn != 0, inside, "point {} dir {}", point, dir);
+            }
+        }
+    }
+
     #[test]
     fn check_containment_polygon() {
         let shell =
@@ -143,4 +474,24 @@ mod tests {
             ContainRelation::Exterior
         );
     }
+
+    #[test]
+    fn check_containment_with_boundary() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (0., 4.), (4., 4.), (4., 0.), (0., 0.)]).unwrap();
+        let hole =
+            LinearRing::try_from(vec![(1., 1.), (1., 2.), (2., 2.), (2., 1.), (1., 1.)]).unwrap();
+        let polygon = Polygon::try_new(shell, vec![hole]).unwrap();
+        for &boundary_is_inside in &[true, false] {
+            let check = |x: f64, y: f64| {
+                point_in_polygon_with_boundary((x, y).into(), &polygon, boundary_is_inside)
+            };
+            assert!(check(3., 3.));
+            assert!(!check(1.5, 1.5));
+            assert!(!check(5., 5.));
+            // On the shell, and on the hole
+            assert_eq!(check(0., 2.), boundary_is_inside);
+            assert_eq!(check(2., 1.5), boundary_is_inside);
+        }
+    }
 }
@@ -3,7 +3,7 @@ use crate::errors::ValidationError;
 use crate::errors::ValidationError::*;
 use crate::geometry_state::{HasRTree, Validated};
 use crate::utils::intersect_segments;
-use crate::{Coordinate, HasEnvelope, LinearRing};
+use crate::{Coordinate, HasEnvelope, LinearRing, Rectangle};
 use std::collections::{HashMap, HashSet};
 
 type Intersections = HashSet<(usize, usize)>;
@@ -13,21 +13,117 @@ pub fn validate_polygon(
     shell: &LinearRing<Validated>,
     holes: &[LinearRing<Validated>],
 ) -> Result<(), ValidationError> {
+    validate_polygon_with_tolerance(shell, holes, 0.)
+}
+
+/// Like validate_polygon, but two rings' intersection points within
+/// tolerance of each other count as a single touch.  This absorbs
+/// floating-point noise, which can split one touch into nearby points.
+/// Holes' envelopes may also stick out of the shell's by tolerance.
+pub fn validate_polygon_with_tolerance(
+    shell: &LinearRing<Validated>,
+    holes: &[LinearRing<Validated>],
+    tolerance: f64,
+) -> Result<(), ValidationError> {
+    let mut result = Ok(());
+    check_polygon(shell, holes, tolerance, |issue| {
+        result = Err(issue.error);
+        false
+    });
+    result
+}
+
+/// An issue found by validate_polygon_report, with its size if it has one:
+///
+/// - OverlappingSegments: the length of the overlap.
+/// - MultipleIntersections: the farthest the rings' intersection points are
+///   from the first, so a tolerance of at least this merges them.
+/// - HoleNotValid, if the hole's envelope sticks out of the shell's: how far
+///   it sticks out.
+#[derive(Debug, PartialEq)]
+pub struct ValidationIssue {
+    pub error: ValidationError,
+    pub magnitude: Option<f64>,
+}
+
+/// All the issues validate_polygon would find, rather than just the first.
+#[derive(Debug, PartialEq)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// The smallest tolerance for which validate_polygon_with_tolerance
+    /// accepts the rings, or None if some issue is not from floating-point
+    /// noise, eg to decide between snapping and rejecting the data.
+    pub fn required_tolerance(&self) -> Option<f64> {
+        self.issues
+            .iter()
+            .try_fold(0_f64, |tolerance, issue| match issue.error {
+                MultipleIntersections | HoleNotValid => Some(tolerance.max(issue.magnitude?)),
+                _ => None,
+            })
+    }
+}
+
+/// Like validate_polygon, but collect every issue found, with its
+/// magnitude, eg to see whether a tolerance would help.  Rings that touch
+/// several times are treated as touching once when checking that the
+/// interior is connected, as they would be with enough tolerance.
+pub fn validate_polygon_report(
+    shell: &LinearRing<Validated>,
+    holes: &[LinearRing<Validated>],
+) -> ValidationReport {
+    let mut issues = Vec::new();
+    check_polygon(shell, holes, 0., |issue| {
+        issues.push(issue);
+        true
+    });
+    ValidationReport { issues }
+}
+
+/// Check the polygon, passing each issue found to the sink, and stopping
+/// if it returns false.
+fn check_polygon<F>(
+    shell: &LinearRing<Validated>,
+    holes: &[LinearRing<Validated>],
+    tolerance: f64,
+    mut sink: F,
+) where
+    F: FnMut(ValidationIssue) -> bool,
+{
+    let mut report = |error, magnitude| sink(ValidationIssue { error, magnitude });
     let mut intersections: Intersections = Intersections::new();
     for (i, hole) in holes.iter().enumerate() {
-        if shell.envelope() == hole.envelope() || !shell.envelope().contains(hole.envelope()) {
-            return Err(HoleNotValid);
+        // A hole may share its envelope with the shell: it then touches the
+        // shell, which the intersection checks below handle.
+        if !shell.envelope().contains_eps(hole.envelope(), tolerance) {
+            let excess = envelope_excess(shell.envelope(), hole.envelope());
+            if !report(HoleNotValid, Some(excess)) {
+                return;
+            }
         }
 
-        let intersection = find_intersecting_point(hole, shell)?;
+        let intersection = find_intersecting_point(hole, shell);
+        if let Some(issue) = intersection.check(tolerance) {
+            if !report(issue.error, issue.magnitude) {
+                return;
+            }
+        }
+        let intersection = intersection.first;
         if intersection.is_some() {
             intersections.insert((0, i + 1));
         }
 
         if point_in_loop(find_nonequal_point(hole.coords(), intersection), shell)
             == ContainRelation::Exterior
+            && !report(HoleNotValid, None)
         {
-            return Err(HoleNotValid);
+            return;
         }
 
         // Check existing holes for intersections.
@@ -37,38 +133,97 @@ pub fn validate_polygon(
             if !hole.envelope().intersects(other_hole.envelope()) {
                 continue;
             }
-            let intersection = find_intersecting_point(hole, other_hole)?;
+            // Identical holes overlap everywhere; report them specifically.
+            if hole.equals_rotation(other_hole) {
+                let error = DuplicateHole {
+                    first: j,
+                    second: i,
+                };
+                if !report(error, None) {
+                    return;
+                }
+                continue;
+            }
+            let intersection = find_intersecting_point(hole, other_hole);
+            if let Some(issue) = intersection.check(tolerance) {
+                if !report(issue.error, issue.magnitude) {
+                    return;
+                }
+            }
+            let intersection = intersection.first;
             if intersection.is_some() {
                 intersections.insert((i + 1, j + 1));
             }
             // Check that each hole is not in the other
             if point_in_loop(find_nonequal_point(hole.coords(), intersection), other_hole)
                 == ContainRelation::Interior
+                && !report(HoleNotValid, None)
             {
-                return Err(HoleNotValid);
+                return;
             }
             if point_in_loop(find_nonequal_point(other_hole.coords(), intersection), hole)
                 == ContainRelation::Interior
+                && !report(HoleNotValid, None)
             {
-                return Err(HoleNotValid);
+                return;
             }
         }
     }
 
     if has_cycle(&intersections) {
-        Err(InteriorDisconnected)
-    } else {
-        Ok(())
+        report(InteriorDisconnected, None);
     }
 }
 
-/// Find 0 or 1 intersecting points.  If there are 2 or more points, the
-/// intersection is invalid, and return a ValidationError.
+/// How far the inner rectangle sticks out of the outer, or 0 if it doesn't.
+fn envelope_excess(outer: Rectangle, inner: Rectangle) -> f64 {
+    (outer.x_min - inner.x_min)
+        .max(inner.x_max - outer.x_max)
+        .max(outer.y_min - inner.y_min)
+        .max(inner.y_max - outer.y_max)
+        .max(0.)
+}
+
+/// Where two rings meet: the first intersection point, how far the others
+/// are from it, and the first overlap of their segments.
+struct RingIntersection {
+    first: Option<Coordinate>,
+    spread: f64,
+    overlap: Option<ValidationError>,
+}
+
+impl RingIntersection {
+    /// The issue with the intersection, if any.  Overlapping segments take
+    /// precedence: a shared edge is reported as such, even if the rings also
+    /// meet at other points.  Points within tolerance of the first are
+    /// considered equal to it.
+    fn check(&self, tolerance: f64) -> Option<ValidationIssue> {
+        match &self.overlap {
+            Some(overlap) => Some(ValidationIssue {
+                error: overlap.clone(),
+                magnitude: overlap
+                    .overlap_segment()
+                    .map(|(start, end)| start.distance(end)),
+            }),
+            _ if self.spread > tolerance => Some(ValidationIssue {
+                error: MultipleIntersections,
+                magnitude: Some(self.spread),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Find how the rings intersect; at most 1 point is valid.
 fn find_intersecting_point(
     ring_a: &LinearRing<Validated>,
     ring_b: &LinearRing<Validated>,
-) -> Result<Option<Coordinate>, ValidationError> {
-    let mut final_intersection = None;
+) -> RingIntersection {
+    let mut intersection = RingIntersection {
+        first: None,
+        spread: 0.,
+        overlap: None,
+    };
     for (index_a, index_b) in ring_a.rtree().query_other_intersections(ring_b.rtree()) {
         let start_a = ring_a.coords()[index_a];
         let end_a = ring_a.coords()[index_a + 1];
@@ -81,20 +236,22 @@ fn find_intersecting_point(
         }
         let (isxn_start, isxn_end) = seg_intersection.unwrap();
         if isxn_start != isxn_end {
-            return Err(OverlappingSegments {
-                first_index: index_a,
-                second_index: index_b,
-                start: isxn_start,
-                end: isxn_end,
-            });
+            if intersection.overlap.is_none() {
+                intersection.overlap = Some(OverlappingSegments {
+                    first_index: index_a,
+                    second_index: index_b,
+                    start: isxn_start,
+                    end: isxn_end,
+                });
+            }
+            continue;
         }
-        match final_intersection {
-            None => final_intersection = Some(isxn_start),
-            Some(c) if c == isxn_start => (),
-            _ => return Err(MultipleIntersections),
+        match intersection.first {
+            None => intersection.first = Some(isxn_start),
+            Some(c) => intersection.spread = intersection.spread.max(c.distance(isxn_start)),
         }
     }
-    Ok(final_intersection)
+    intersection
 }
 
 /// Find a point in coords that is not the needle.  We are only using this for
@@ -148,6 +305,147 @@ fn has_cycle(intersections: &Intersections) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::convert::TryFrom;
+
+    fn make_ring(coords: Vec<(f64, f64)>) -> LinearRing<Validated> {
+        LinearRing::try_from(coords).unwrap()
+    }
+
+    #[test]
+    fn test_hole_sharing_shell_edge() {
+        let shell = make_ring(vec![(0., 0.), (0., 10.), (10., 10.), (10., 0.), (0., 0.)]);
+        let hole = make_ring(vec![(0., 2.), (0., 5.), (3., 5.), (3., 2.), (0., 2.)]);
+        assert_eq!(
+            validate_polygon(&shell, &[hole]),
+            Err(OverlappingSegments {
+                first_index: 0,
+                second_index: 0,
+                start: (0., 2.).into(),
+                end: (0., 5.).into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_hole_with_shell_envelope() {
+        let shell = make_ring(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]);
+        // Touches every side, so the interior is disconnected.
+        let hole = make_ring(vec![(2., 0.), (0., 2.), (2., 4.), (4., 2.), (2., 0.)]);
+        assert_eq!(hole.envelope(), shell.envelope());
+        assert!(shell
+            .envelope()
+            .contains_strict(Rectangle::new((1., 1.).into(), (3., 3.).into())));
+        assert!(!shell.envelope().contains_strict(hole.envelope()));
+        assert_eq!(
+            validate_polygon(&shell, &[hole]),
+            Err(MultipleIntersections)
+        );
+    }
+
+    #[test]
+    fn test_hole_in_shell_notch() {
+        // An L-shape, with a hole in its notch reaching the shell's envelope.
+        let shell = make_ring(vec![
+            (0., 0.),
+            (4., 0.),
+            (4., 2.),
+            (2., 2.),
+            (2., 4.),
+            (0., 4.),
+            (0., 0.),
+        ]);
+        let hole = make_ring(vec![(3., 3.), (4., 3.), (4., 4.), (3., 4.), (3., 3.)]);
+        assert!(shell.envelope().contains(hole.envelope()));
+        assert!(!shell.envelope().contains_strict(hole.envelope()));
+        assert_eq!(validate_polygon(&shell, &[hole]), Err(HoleNotValid));
+
+        // Reaching the envelope at a touch of the shell is fine.
+        let hole = make_ring(vec![(0., 2.), (1., 1.), (1., 3.), (0., 2.)]);
+        assert!(!shell.envelope().contains_strict(hole.envelope()));
+        assert_eq!(validate_polygon(&shell, &[hole]), Ok(()));
+    }
+
+    #[test]
+    fn test_duplicate_hole() {
+        let shell = make_ring(vec![(0., 0.), (6., 0.), (6., 6.), (0., 6.), (0., 0.)]);
+        let holes = [
+            make_ring(vec![(1., 1.), (2., 1.), (2., 2.), (1., 1.)]),
+            make_ring(vec![(4., 4.), (5., 4.), (5., 5.), (4., 4.)]),
+            // The first hole, starting from another vertex
+            make_ring(vec![(2., 1.), (2., 2.), (1., 1.), (2., 1.)]),
+        ];
+        assert_eq!(
+            validate_polygon(&shell, &holes),
+            Err(DuplicateHole {
+                first: 0,
+                second: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let shell = make_ring(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]);
+        // The apex pokes just above the other hole's base, crossing it twice
+        // about 1e-12 apart.
+        let hole_a = make_ring(vec![(1., 1.), (3., 1.), (2., 2. + 1e-12), (1., 1.)]);
+        let hole_b = make_ring(vec![(1., 2.), (3., 2.), (2., 3.), (1., 2.)]);
+        let holes = [hole_a, hole_b];
+        assert_eq!(validate_polygon(&shell, &holes), Err(MultipleIntersections));
+        assert_eq!(
+            validate_polygon_with_tolerance(&shell, &holes, 1e-9),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_validate_polygon_report() {
+        let shell = make_ring(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]);
+        let hole_a = make_ring(vec![(1., 1.), (3., 1.), (2., 2. + 1e-12), (1., 1.)]);
+        let hole_b = make_ring(vec![(1., 2.), (3., 2.), (2., 3.), (1., 2.)]);
+        let holes = [hole_a, hole_b];
+        let report = validate_polygon_report(&shell, &holes);
+        assert!(!report.is_valid());
+        assert_eq!(report.issues.len(), 1);
+        let issue = &report.issues[0];
+        assert_eq!(issue.error, MultipleIntersections);
+        // The crossings are 2e-12 apart, up to rounding.
+        let magnitude = issue.magnitude.unwrap();
+        assert!(magnitude > 1e-12 && magnitude < 3e-12);
+        let tolerance = report.required_tolerance().unwrap();
+        assert_eq!(tolerance, magnitude);
+        assert_eq!(
+            validate_polygon_with_tolerance(&shell, &holes, tolerance),
+            Ok(())
+        );
+        assert_eq!(
+            validate_polygon_with_tolerance(&shell, &holes, tolerance / 2.),
+            Err(MultipleIntersections)
+        );
+
+        // A shared edge is not noise, and is reported with the other issues.
+        let holes = [
+            make_ring(vec![(0., 1.), (0., 3.), (2., 3.), (0., 1.)]),
+            make_ring(vec![(3., 3.), (4.5, 3.), (3., 2.), (3., 3.)]),
+        ];
+        let report = validate_polygon_report(&shell, &holes);
+        assert_eq!(report.issues.len(), 3);
+        assert_eq!(
+            report.issues[0].error,
+            validate_polygon(&shell, &holes).unwrap_err()
+        );
+        assert_eq!(report.issues[0].magnitude, Some(2.));
+        // The second hole sticks out of the shell, crossing it twice.
+        assert_eq!(report.issues[1].error, HoleNotValid);
+        assert_eq!(report.issues[1].magnitude, Some(0.5));
+        assert_eq!(report.issues[2].error, MultipleIntersections);
+        assert!((report.issues[2].magnitude.unwrap() - 1. / 3.).abs() < 1e-12);
+        assert_eq!(report.required_tolerance(), None);
+
+        let report = validate_polygon_report(&shell, &[]);
+        assert!(report.is_valid());
+        assert_eq!(report.required_tolerance(), Some(0.));
+    }
 
     #[test]
     fn test_no_cycle() {
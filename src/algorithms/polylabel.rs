@@ -0,0 +1,147 @@
+use crate::geometry_state::Validated;
+use crate::{Coordinate, HasEnvelope, Polygon};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// The pole of inaccessibility of the polygon: the interior point farthest
+/// from its boundary, eg to place a label.
+///
+/// This is Mapbox's polylabel: the envelope is covered with square cells,
+/// which are split while they might hold a point more than precision
+/// farther from the boundary than the best found so far.  So the result is
+/// within precision of the farthest distance.  An empty polygon gives NaN
+/// components.  Panics if precision is not positive and finite.
+pub fn polylabel(polygon: &Polygon<Validated>, precision: f64) -> Coordinate {
+    assert!(
+        precision > 0. && precision.is_finite(),
+        "Precision must be positive and finite, got {}",
+        precision
+    );
+    let envelope = polygon.envelope();
+    if envelope.is_empty() {
+        return Coordinate::new(f64::NAN, f64::NAN);
+    }
+    let width = envelope.x_max - envelope.x_min;
+    let height = envelope.y_max - envelope.y_min;
+    let cell_size = width.min(height);
+    if cell_size == 0. {
+        return Coordinate::new(envelope.x_min, envelope.y_min);
+    }
+
+    let half_size = cell_size / 2.;
+    let mut cells = BinaryHeap::new();
+    let mut x = envelope.x_min;
+    while x < envelope.x_max {
+        let mut y = envelope.y_min;
+        while y < envelope.y_max {
+            cells.push(Cell::new(
+                polygon,
+                Coordinate::new(x + half_size, y + half_size),
+                half_size,
+            ));
+            y += cell_size;
+        }
+        x += cell_size;
+    }
+
+    let mut best = Cell::new(polygon, envelope.center(), 0.);
+    while let Some(cell) = cells.pop() {
+        if cell.distance > best.distance {
+            best = cell;
+        }
+        // Cells are popped by their potential, so none left can do better.
+        if cell.max_distance - best.distance <= precision {
+            break;
+        }
+        let quarter_size = cell.half_size / 2.;
+        for &(dx, dy) in &[(-1., -1.), (1., -1.), (-1., 1.), (1., 1.)] {
+            let center = cell.center + Coordinate::new(dx, dy) * quarter_size;
+            cells.push(Cell::new(polygon, center, quarter_size));
+        }
+    }
+    best.center
+}
+
+/// A square cell, ordered by the farthest any point in it could be from
+/// the boundary.
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+    center: Coordinate,
+    half_size: f64,
+    // Distance from the center to the boundary, negative outside.
+    distance: f64,
+    max_distance: f64,
+}
+
+impl Cell {
+    fn new(polygon: &Polygon<Validated>, center: Coordinate, half_size: f64) -> Self {
+        let distance = -polygon.signed_distance(center);
+        Cell {
+            center,
+            half_size,
+            distance,
+            max_distance: distance + half_size * std::f64::consts::SQRT_2,
+        }
+    }
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Cell {}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.max_distance.total_cmp(&other.max_distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::point_in_polygon::{point_in_polygon, ContainRelation};
+    use crate::LinearRing;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_polylabel() {
+        // A C-shape, whose bottom arm is thickest.
+        let shell = LinearRing::try_from(vec![
+            (0., 0.),
+            (10., 0.),
+            (10., 4.),
+            (2., 4.),
+            (2., 8.),
+            (10., 8.),
+            (10., 10.),
+            (0., 10.),
+            (0., 0.),
+        ])
+        .unwrap();
+        let polygon = Polygon::try_new(shell, Vec::new()).unwrap();
+        let label = polylabel(&polygon, 0.01);
+        assert_eq!(point_in_polygon(label, &polygon), ContainRelation::Interior);
+        assert!(-polygon.signed_distance(label) >= 2. - 0.01);
+        assert!((label.y - 2.).abs() <= 0.01);
+        // The envelope center is in the gap of the C.
+        assert_eq!(
+            point_in_polygon(polygon.envelope().center(), &polygon),
+            ContainRelation::Exterior
+        );
+
+        let square =
+            LinearRing::try_from(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]).unwrap();
+        let polygon = Polygon::try_new(square, Vec::new()).unwrap();
+        assert_eq!(polylabel(&polygon, 0.1), Coordinate::new(2., 2.));
+        assert!(polylabel(&Polygon::empty(), 0.1).x.is_nan());
+    }
+}
@@ -0,0 +1,133 @@
+use super::min_heap::MinHeap;
+use crate::utils::orientation;
+use crate::Coordinate;
+use std::cmp::Ordering;
+
+/// Simplify the path to target coordinates, by Visvalingam-Whyatt.
+///
+/// This repeatedly removes the vertex whose triangle with its neighbors has
+/// the least area, so the most significant vertices are kept.  This gives a
+/// vertex budget rather than a tolerance, eg for rendering.  The endpoints
+/// are always kept, so the result has at least 2 coordinates (if the path
+/// does).
+pub fn simplify_to_count(coords: &[Coordinate], target: usize) -> Vec<Coordinate> {
+    let n = coords.len();
+    if n <= target.max(2) {
+        return coords.to_vec();
+    }
+    // A linked list of the remaining vertices
+    let mut prev: Vec<usize> = (0..n).map(|i| i.wrapping_sub(1)).collect();
+    let mut next: Vec<usize> = (1..=n).collect();
+    let mut areas = vec![f64::INFINITY; n];
+    let area = |prev: usize, i: usize, next: usize| {
+        orientation(coords[prev], coords[i], coords[next]).abs() / 2.
+    };
+
+    let mut heap = MinHeap::new();
+    for (i, vertex_area) in areas.iter_mut().enumerate().take(n - 1).skip(1) {
+        *vertex_area = area(i - 1, i, i + 1);
+        heap.push(VertexArea {
+            area: *vertex_area,
+            index: i,
+        });
+    }
+
+    let mut remaining = n;
+    let mut last_area = 0_f64;
+    while remaining > target.max(2) {
+        let VertexArea {
+            area: popped,
+            index,
+        } = match heap.pop() {
+            Some(vertex) => vertex,
+            None => break,
+        };
+        // Skip entries for removed vertices, or superseded by a new area.
+        if popped.to_bits() != areas[index].to_bits() {
+            continue;
+        }
+        areas[index] = f64::NAN;
+        remaining -= 1;
+        // Removing a vertex never makes its neighbors less significant.
+        last_area = last_area.max(popped);
+
+        let (before, after) = (prev[index], next[index]);
+        next[before] = after;
+        prev[after] = before;
+        for &neighbor in &[before, after] {
+            if neighbor == 0 || neighbor == n - 1 {
+                continue;
+            }
+            areas[neighbor] = area(prev[neighbor], neighbor, next[neighbor]).max(last_area);
+            heap.push(VertexArea {
+                area: areas[neighbor],
+                index: neighbor,
+            });
+        }
+    }
+
+    (0..n)
+        .filter(|&i| !areas[i].is_nan())
+        .map(|i| coords[i])
+        .collect()
+}
+
+/// A vertex ordered by its effective area, preferring the lower index on
+/// ties.
+#[derive(Debug, Clone, Copy)]
+struct VertexArea {
+    area: f64,
+    index: usize,
+}
+
+impl PartialEq for VertexArea {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for VertexArea {}
+
+impl PartialOrd for VertexArea {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for VertexArea {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.area
+            .total_cmp(&other.area)
+            .then_with(|| self.index.cmp(&other.index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simplify_to_count() {
+        // A zigzag with small wobbles along its 2 big teeth
+        let coords = Coordinate::vec_from(&[
+            (0., 0.),
+            (1., 0.1),
+            (2., 5.),
+            (3., 4.9),
+            (4., 0.),
+            (4.5, 1.5),
+            (6., 6.),
+            (7., 5.8),
+            (8., 0.1),
+            (9., 0.),
+        ]);
+        assert_eq!(
+            simplify_to_count(&coords, 5),
+            Coordinate::vec_from(&[(0., 0.), (2., 5.), (4., 0.), (6., 6.), (9., 0.)])
+        );
+        assert_eq!(simplify_to_count(&coords, 10), coords);
+        assert_eq!(simplify_to_count(&coords, 20), coords);
+        assert_eq!(simplify_to_count(&coords, 0), vec![coords[0], coords[9]]);
+        assert!(simplify_to_count(&[], 3).is_empty());
+    }
+}
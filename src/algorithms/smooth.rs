@@ -0,0 +1,94 @@
+use crate::Coordinate;
+
+/// Round off the corners of the path by Chaikin's corner cutting, eg for
+/// rendering coarse paths.
+///
+/// Each iteration replaces every segment by the points 1/4 and 3/4 of the
+/// way along it, so the interior vertices are cut off.  The endpoints are
+/// kept.  Paths with fewer than 3 coordinates have no corners, and are
+/// unchanged.
+pub fn chaikin_smooth(coords: &[Coordinate], iterations: usize) -> Vec<Coordinate> {
+    let mut smoothed = coords.to_vec();
+    if coords.len() < 3 {
+        return smoothed;
+    }
+    for _ in 0..iterations {
+        let mut next = Vec::with_capacity(2 * smoothed.len());
+        next.push(smoothed[0]);
+        next.extend(cut_corners(&smoothed));
+        next.extend(smoothed.last());
+        smoothed = next;
+    }
+    smoothed
+}
+
+/// Like chaikin_smooth, but for a closed ring, whose first (and last)
+/// coordinate is also a corner to cut.  The result is closed.  Coordinates
+/// that are not a closed ring of at least 4 coordinates are unchanged.
+pub fn chaikin_smooth_ring(coords: &[Coordinate], iterations: usize) -> Vec<Coordinate> {
+    let mut smoothed = coords.to_vec();
+    if coords.len() < 4 || coords.first() != coords.last() {
+        return smoothed;
+    }
+    for _ in 0..iterations {
+        let mut next: Vec<Coordinate> = cut_corners(&smoothed).collect();
+        next.push(next[0]);
+        smoothed = next;
+    }
+    smoothed
+}
+
+fn cut_corners(coords: &[Coordinate]) -> impl Iterator<Item = Coordinate> + '_ {
+    coords.windows(2).flat_map(|segment| {
+        let (start, end) = (segment[0], segment[1]);
+        std::iter::once(start.lerp(end, 0.25)).chain(std::iter::once(start.lerp(end, 0.75)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chaikin_smooth() {
+        let path = Coordinate::vec_from(&[(0., 0.), (4., 0.), (4., 4.)]);
+        assert_eq!(
+            chaikin_smooth(&path, 1),
+            Coordinate::vec_from(&[(0., 0.), (1., 0.), (3., 0.), (4., 1.), (4., 3.), (4., 4.)])
+        );
+        let smoothed = chaikin_smooth(&path, 3);
+        // Each iteration doubles the number of coordinates.
+        assert_eq!(smoothed.len(), 24);
+        assert_eq!(smoothed.first(), path.first());
+        assert_eq!(smoothed.last(), path.last());
+        assert!(!smoothed.contains(&Coordinate::new(4., 0.)));
+
+        assert_eq!(chaikin_smooth(&path, 0), path);
+        assert_eq!(chaikin_smooth(&path[..2], 2), &path[..2]);
+    }
+
+    #[test]
+    fn test_chaikin_smooth_ring() {
+        let ring = Coordinate::vec_from(&[(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]);
+        assert_eq!(
+            chaikin_smooth_ring(&ring, 1),
+            Coordinate::vec_from(&[
+                (1., 0.),
+                (3., 0.),
+                (4., 1.),
+                (4., 3.),
+                (3., 4.),
+                (1., 4.),
+                (0., 3.),
+                (0., 1.),
+                (1., 0.),
+            ])
+        );
+        let smoothed = chaikin_smooth_ring(&ring, 2);
+        assert_eq!(smoothed.len(), 17);
+        assert_eq!(smoothed.first(), smoothed.last());
+
+        let path = &ring[..4];
+        assert_eq!(chaikin_smooth_ring(path, 1), path);
+    }
+}
@@ -0,0 +1,38 @@
+use crate::Coordinate;
+
+/// Round each coordinate to the nearest multiple of cell, dropping
+/// consecutive coordinates that snap to the same point.  This makes
+/// near-coincident vertices exactly equal, eg before overlay.
+///
+/// NB: Snapping can collapse short segments, or make a valid path
+/// self-intersect, so the result should be re-validated.  Panics if cell
+/// is not positive and finite.
+pub fn snap_to_grid(coords: &[Coordinate], cell: f64) -> Vec<Coordinate> {
+    assert!(
+        cell > 0. && cell.is_finite(),
+        "Cell size must be positive and finite, got {}",
+        cell
+    );
+    let snap = |value: f64| (value / cell).round() * cell;
+    let mut snapped: Vec<Coordinate> = coords
+        .iter()
+        .map(|c| Coordinate::new(snap(c.x), snap(c.y)))
+        .collect();
+    snapped.dedup();
+    snapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap_to_grid() {
+        let coords = Coordinate::vec_from(&[(0.01, 0.99), (0.2, 1.1), (1.3, 0.6), (0.01, 0.99)]);
+        assert_eq!(
+            snap_to_grid(&coords, 0.5),
+            Coordinate::vec_from(&[(0., 1.), (1.5, 0.5), (0., 1.)])
+        );
+        assert!(snap_to_grid(&[], 0.5).is_empty());
+    }
+}
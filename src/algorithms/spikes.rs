@@ -0,0 +1,83 @@
+use crate::Coordinate;
+
+/// The indices of the spikes in coords: vertices where the path turns back
+/// to within angle_eps (in radians) of the way it came, eg where it goes
+/// out and straight back along the same line.  These should be cleaned
+/// before validation.
+///
+/// If coords is closed, the first vertex is checked with the wrap-around
+/// neighbors, and the closing coordinate is not reported separately.
+/// Vertices next to a zero-length segment are skipped.
+pub fn find_spikes(coords: &[Coordinate], angle_eps: f64) -> Vec<usize> {
+    let is_closed = coords.len() > 3 && coords.first() == coords.last();
+    // The vertices without the closing coordinate, if closed
+    let vertices = if is_closed {
+        &coords[..coords.len() - 1]
+    } else {
+        coords
+    };
+    let n = vertices.len();
+    let unit = |from: Coordinate, to: Coordinate| {
+        let edge = to - from;
+        let length = edge.dot(edge).sqrt();
+        if length > 0. {
+            Some(edge * (1. / length))
+        } else {
+            None
+        }
+    };
+    // Reversing within angle_eps is a turn whose cosine is near -1.
+    let threshold = -angle_eps.cos();
+
+    let candidates = if is_closed {
+        0..n
+    } else {
+        1..n.saturating_sub(1)
+    };
+    candidates
+        .filter(|&i| {
+            let previous = vertices[(i + n - 1) % n];
+            let next = vertices[(i + 1) % n];
+            match (unit(previous, vertices[i]), unit(vertices[i], next)) {
+                (Some(incoming), Some(outgoing)) => incoming.dot(outgoing) <= threshold,
+                _ => false,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_spikes() {
+        // A square with a spike out of its right side, at index 3
+        let ring = Coordinate::vec_from(&[
+            (0., 0.),
+            (4., 0.),
+            (4., 2.),
+            (8., 2.1),
+            (4., 2.2),
+            (4., 4.),
+            (0., 4.),
+            (0., 0.),
+        ]);
+        // It turns back by about 0.05 radians.
+        assert_eq!(find_spikes(&ring, 0.1), vec![3]);
+        assert!(find_spikes(&ring, 0.01).is_empty());
+
+        // A spike back along the same line, then the same ring started at
+        // the spike, so that it is found with the wrap-around neighbors.
+        let ring = Coordinate::vec_from(&[(0., 0.), (2., 0.), (1., 0.), (1., 1.), (0., 0.)]);
+        assert_eq!(find_spikes(&ring, 1e-9), vec![1]);
+        let ring = Coordinate::vec_from(&[(2., 0.), (1., 0.), (1., 1.), (0., 0.), (2., 0.)]);
+        assert_eq!(find_spikes(&ring, 1e-9), vec![0]);
+
+        // In an open path, the ends are never spikes.
+        let path = Coordinate::vec_from(&[(0., 0.), (2., 0.), (1., 0.)]);
+        assert_eq!(find_spikes(&path, 1e-9), vec![1]);
+        assert!(find_spikes(&path[..2], 1e-9).is_empty());
+        assert!(find_spikes(&[], 1e-9).is_empty());
+    }
+}
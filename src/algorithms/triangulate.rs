@@ -0,0 +1,222 @@
+use crate::geometry_state::Validated;
+use crate::utils::{intersect_segments, orientation, signed_area};
+use crate::{Coordinate, LinearRing, Polygon};
+
+/// Triangulate the polygon by ear clipping, eg for rendering its fill.
+///
+/// Holes are first bridged into the shell, making a single loop.  The
+/// triangles are counter-clockwise, and tile the interior of the polygon.
+/// This is O(n^2) in the number of vertices.
+pub fn triangulate(polygon: &Polygon<Validated>) -> Vec<[Coordinate; 3]> {
+    let mut outer = ring_vertices(polygon.shell(), true);
+    let mut holes: Vec<Vec<Coordinate>> = polygon
+        .holes()
+        .iter()
+        .map(|hole| ring_vertices(hole, false))
+        .collect();
+    // Bridging holes from the right first keeps the bridges short.
+    holes.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+    while !holes.is_empty() {
+        let hole = holes.remove(0);
+        bridge_hole(&mut outer, hole, &holes);
+    }
+    clip_ears(outer)
+}
+
+/// The ring's coordinates without the closing coordinate, wound
+/// counter-clockwise if ccw, else clockwise.
+fn ring_vertices(ring: &LinearRing<Validated>, ccw: bool) -> Vec<Coordinate> {
+    let coords = ring.coords();
+    if coords.is_empty() {
+        return Vec::new();
+    }
+    let mut vertices = coords[..coords.len() - 1].to_vec();
+    if (signed_area(coords) > 0.) != ccw {
+        vertices.reverse();
+    }
+    vertices
+}
+
+fn max_x(coords: &[Coordinate]) -> f64 {
+    coords.iter().map(|c| c.x).fold(f64::NEG_INFINITY, f64::max)
+}
+
+/// Splice the (clockwise) hole into the (counter-clockwise) outer loop, via
+/// a bridge from the hole's rightmost vertex to the nearest outer vertex it
+/// can see.  The bridge is traversed in both directions.
+fn bridge_hole(
+    outer: &mut Vec<Coordinate>,
+    hole: Vec<Coordinate>,
+    other_holes: &[Vec<Coordinate>],
+) {
+    let hole_start = (0..hole.len())
+        .max_by(|&a, &b| hole[a].x.total_cmp(&hole[b].x))
+        .unwrap();
+    let from = hole[hole_start];
+
+    let mut candidates: Vec<usize> = (0..outer.len()).collect();
+    candidates.sort_by(|&a, &b| from.distance(outer[a]).total_cmp(&from.distance(outer[b])));
+    let bridge_end = candidates
+        .into_iter()
+        .find(|&i| {
+            let to = outer[i];
+            std::iter::once(&outer[..])
+                .chain(std::iter::once(&hole[..]))
+                .chain(other_holes.iter().map(|h| &h[..]))
+                .all(|ring| !blocks_bridge(ring, from, to))
+        })
+        .unwrap_or(0);
+
+    let mut spliced = Vec::with_capacity(outer.len() + hole.len() + 2);
+    spliced.extend_from_slice(&outer[..=bridge_end]);
+    spliced.extend(hole[hole_start..].iter().chain(&hole[..=hole_start]));
+    spliced.extend_from_slice(&outer[bridge_end..]);
+    *outer = spliced;
+}
+
+/// Whether any edge of the ring meets the segment from a to b, other than
+/// at a or b.
+fn blocks_bridge(ring: &[Coordinate], a: Coordinate, b: Coordinate) -> bool {
+    (0..ring.len()).any(|i| {
+        let start = ring[i];
+        let end = ring[(i + 1) % ring.len()];
+        match intersect_segments(start, end, a, b) {
+            None => false,
+            Some((isxn_start, isxn_end)) => {
+                isxn_start != isxn_end || (isxn_start != a && isxn_start != b)
+            }
+        }
+    })
+}
+
+/// Triangulate a counter-clockwise loop, which may touch itself at bridge
+/// vertices.
+fn clip_ears(mut vertices: Vec<Coordinate>) -> Vec<[Coordinate; 3]> {
+    let mut triangles = Vec::with_capacity(vertices.len().saturating_sub(2));
+    while vertices.len() > 3 {
+        let n = vertices.len();
+        let ear = (0..n).find(|&i| is_ear(&vertices, i)).or_else(|| {
+            // Numerical noise can leave no ear; drop a flat or reflex vertex
+            // instead, without emitting its triangle.
+            (0..n).find(|&i| {
+                orientation(
+                    vertices[(i + n - 1) % n],
+                    vertices[i],
+                    vertices[(i + 1) % n],
+                ) <= 0.
+            })
+        });
+        let i = match ear {
+            Some(i) => i,
+            None => break,
+        };
+        let triangle = [
+            vertices[(i + n - 1) % n],
+            vertices[i],
+            vertices[(i + 1) % n],
+        ];
+        if orientation(triangle[0], triangle[1], triangle[2]) > 0. {
+            triangles.push(triangle);
+        }
+        vertices.remove(i);
+    }
+    if vertices.len() == 3 && orientation(vertices[0], vertices[1], vertices[2]) > 0. {
+        triangles.push([vertices[0], vertices[1], vertices[2]]);
+    }
+    triangles
+}
+
+/// Whether the vertex at i is convex, with no other vertex in or on the
+/// triangle it makes with its neighbors.
+fn is_ear(vertices: &[Coordinate], i: usize) -> bool {
+    let n = vertices.len();
+    let a = vertices[(i + n - 1) % n];
+    let b = vertices[i];
+    let c = vertices[(i + 1) % n];
+    if orientation(a, b, c) <= 0. {
+        return false;
+    }
+    vertices.iter().all(|&p| {
+        p == a
+            || p == b
+            || p == c
+            || orientation(a, b, p) < 0.
+            || orientation(b, c, p) < 0.
+            || orientation(c, a, p) < 0.
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::point_in_polygon::{point_in_polygon, ContainRelation};
+    use std::convert::TryFrom;
+
+    fn triangles_area(triangles: &[[Coordinate; 3]]) -> f64 {
+        triangles
+            .iter()
+            .map(|t| orientation(t[0], t[1], t[2]) / 2.)
+            .sum()
+    }
+
+    fn assert_tiles(polygon: &Polygon<Validated>, num_triangles: usize) {
+        let triangles = triangulate(polygon);
+        assert_eq!(triangles.len(), num_triangles);
+        assert_eq!(triangles_area(&triangles), polygon.area());
+        for t in &triangles {
+            assert!(orientation(t[0], t[1], t[2]) > 0.);
+            let centroid = (t[0] + t[1] + t[2]) * (1. / 3.);
+            assert_eq!(
+                point_in_polygon(centroid, polygon),
+                ContainRelation::Interior
+            );
+        }
+    }
+
+    #[test]
+    fn test_convex_quad() {
+        // Clockwise, to check it is reoriented.
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (0., 2.), (3., 3.), (4., 0.), (0., 0.)]).unwrap();
+        assert_tiles(&Polygon::try_new(shell, Vec::new()).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_concave() {
+        let shell = LinearRing::try_from(vec![
+            (0., 0.),
+            (4., 0.),
+            (4., 4.),
+            (2., 1.),
+            (0., 4.),
+            (0., 0.),
+        ])
+        .unwrap();
+        assert_tiles(&Polygon::try_new(shell, Vec::new()).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_square_with_hole() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (10., 0.), (10., 10.), (0., 10.), (0., 0.)])
+                .unwrap();
+        let hole =
+            LinearRing::try_from(vec![(4., 4.), (6., 4.), (6., 6.), (4., 6.), (4., 4.)]).unwrap();
+        let polygon = Polygon::try_new(shell, vec![hole]).unwrap();
+        // 4 + 4 vertices, and the 2 bridge ends repeated
+        assert_tiles(&polygon, 8);
+    }
+
+    #[test]
+    fn test_two_holes() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (10., 0.), (10., 10.), (0., 10.), (0., 0.)])
+                .unwrap();
+        let holes = vec![
+            LinearRing::try_from(vec![(1., 1.), (3., 1.), (3., 3.), (1., 3.), (1., 1.)]).unwrap(),
+            LinearRing::try_from(vec![(6., 6.), (8., 6.), (7., 8.), (6., 6.)]).unwrap(),
+        ];
+        let polygon = Polygon::try_new(shell, holes).unwrap();
+        assert_tiles(&polygon, 4 + 4 + 3 + 2 * 2 - 2);
+    }
+}
@@ -37,6 +37,41 @@ impl Coordinate {
         self.x * rhs.x + self.y * rhs.y
     }
 
+    /// Euclidean distance between self and other
+    pub fn distance(&self, other: Coordinate) -> f64 {
+        (*self - other).dot(*self - other).sqrt()
+    }
+
+    /// The point a fraction t of the way from self to other.  t is not
+    /// clamped, so values outside [0, 1] extrapolate along the line.
+    pub fn lerp(&self, other: Coordinate, t: f64) -> Coordinate {
+        *self + (other - *self) * t
+    }
+
+    /// The planar angle of the direction from self to other, in radians in
+    /// [-pi, pi].  East (+x) is 0, and angles increase counter-clockwise, so
+    /// north (+y) is pi/2.  The bearing to self is 0.
+    pub fn bearing_to(&self, other: Coordinate) -> f64 {
+        let delta = other - *self;
+        delta.y.atan2(delta.x)
+    }
+
+    /// Round each component to the given number of decimal places, eg for
+    /// stable output.  Halves round away from zero, so negative values round
+    /// symmetrically.  Components too large to round are unchanged.
+    pub fn round_to(&self, decimals: u32) -> Coordinate {
+        let scale = 10_f64.powi(decimals as i32);
+        let round = |value: f64| {
+            let scaled = value * scale;
+            if scaled.is_finite() {
+                scaled.round() / scale
+            } else {
+                value
+            }
+        };
+        Coordinate::new(round(self.x), round(self.y))
+    }
+
     pub fn is_finite(&self) -> bool {
         self.x.is_finite() && self.y.is_finite()
     }
@@ -45,7 +80,7 @@ impl Coordinate {
     where
         C: Into<Coordinate> + Copy,
     {
-        cs.into_iter().map(|&c| c.into()).collect()
+        cs.iter().map(|&c| c.into()).collect()
     }
 }
 
@@ -105,6 +140,27 @@ mod tests {
         assert_eq!(0.2 * p, expected);
     }
 
+    #[test]
+    fn test_lerp() {
+        let a = Coordinate::new(1., 2.);
+        let b = Coordinate::new(3., 6.);
+        assert_eq!(a.lerp(b, 0.), a);
+        assert_eq!(a.lerp(b, 1.), b);
+        assert_eq!(a.lerp(b, 0.5), Coordinate::new(2., 4.));
+        assert_eq!(a.lerp(b, 2.), Coordinate::new(5., 10.));
+    }
+
+    #[test]
+    fn test_bearing_to() {
+        use std::f64::consts::{FRAC_PI_2, PI};
+        let origin = Coordinate::new(1., 1.);
+        assert_eq!(origin.bearing_to(Coordinate::new(3., 1.)), 0.);
+        assert_eq!(origin.bearing_to(Coordinate::new(1., 3.)), FRAC_PI_2);
+        assert_eq!(origin.bearing_to(Coordinate::new(-1., 1.)), PI);
+        assert_eq!(origin.bearing_to(Coordinate::new(1., -1.)), -FRAC_PI_2);
+        assert_eq!(origin.bearing_to(origin), 0.);
+    }
+
     #[allow(clippy::float_cmp)]
     #[test]
     fn test_cross() {
@@ -117,4 +173,17 @@ mod tests {
         assert_eq!(p2.cross(p1), -1.);
         assert_eq!((3. * p1).cross(5. * p2), 3. * 5.);
     }
+
+    #[test]
+    fn test_round_to() {
+        let c = Coordinate::new(1.23456, -2.34567);
+        assert_eq!(c.round_to(2), Coordinate::new(1.23, -2.35));
+        assert_eq!(c.round_to(0), Coordinate::new(1., -2.));
+        assert_eq!(
+            Coordinate::new(-0.125, 0.125).round_to(2),
+            Coordinate::new(-0.13, 0.13)
+        );
+        let big = Coordinate::new(1e300, f64::MAX);
+        assert_eq!(big.round_to(10), big);
+    }
 }
@@ -1,7 +1,8 @@
-use crate::Coordinate;
+use crate::utils::lexicographic_order;
+use crate::{Coordinate, LineStringEditor};
 use thiserror::Error;
 
-#[derive(Error, Debug, PartialEq)]
+#[derive(Error, Debug, Clone, PartialEq)]
 pub enum ValidationError {
     #[error("Coordinate has a non-finite component")]
     NonFiniteCoordinate,
@@ -12,6 +13,9 @@ pub enum ValidationError {
     #[error("Degenenerate Segment {index} at {position:?}")]
     DegenerateSegment { index: usize, position: Coordinate },
 
+    #[error("Coordinate {index} at {position:?} is out of bounds")]
+    OutOfBounds { index: usize, position: Coordinate },
+
     #[error("Overlapping segments {first_index} {second_index} between {start:?} and {end:?}")]
     OverlappingSegments {
         first_index: usize,
@@ -20,6 +24,9 @@ pub enum ValidationError {
         end: Coordinate,
     },
 
+    #[error("Path touches itself at coordinate {index} at {position:?}")]
+    SelfTouch { index: usize, position: Coordinate },
+
     #[error("Self-intersection for segments {first_index} {second_index} at {position:?}")]
     SelfIntersection {
         first_index: usize,
@@ -33,6 +40,9 @@ pub enum ValidationError {
     #[error("Hole is not contained in the shell or intersects too many times.")]
     HoleNotValid,
 
+    #[error("Holes {first} and {second} are the same ring.")]
+    DuplicateHole { first: usize, second: usize },
+
     #[error("Polygon rings have >1 intersection.")]
     MultipleIntersections,
 
@@ -44,4 +54,81 @@ pub enum ValidationError {
 
     #[error("Rings must have their first and last coordinate equal.")]
     NotClosed,
+
+    #[error("Expected a {expected} geometry.")]
+    WrongGeometryType { expected: &'static str },
+}
+
+impl ValidationError {
+    /// The shared part of the segments of an OverlappingSegments error, as
+    /// (start, end) with start lexicographically smaller (by x, then y), eg
+    /// to highlight it.  None for other errors.
+    pub fn overlap_segment(&self) -> Option<(Coordinate, Coordinate)> {
+        match self {
+            ValidationError::OverlappingSegments { start, end, .. } => {
+                Some(lexicographic_order(*start, *end))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A failed LineStringEditor::commit, with the editor so the edit can be
+/// fixed and committed again.
+#[derive(Error, Debug, Clone)]
+#[error("Edited path is invalid: {error}")]
+pub struct CommitError {
+    pub editor: LineStringEditor,
+    pub error: ValidationError,
+}
+
+impl From<CommitError> for ValidationError {
+    fn from(err: CommitError) -> Self {
+        err.error
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContainmentError {
+    #[error("Path is not closed: first and last coordinates are not equal.")]
+    NotClosed,
+
+    #[error("Path has {num_coords} coordinates but the rtree has {num_segments} segments.")]
+    MismatchedCoordinates {
+        num_coords: usize,
+        num_segments: usize,
+    },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CoverageError {
+    #[error("Polygons {first} and {second} overlap.")]
+    Overlap { first: usize, second: usize },
+}
+
+#[derive(Error, Debug)]
+pub enum WktError {
+    #[error("Failed to read WKT: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse WKT: {0}")]
+    Parse(String),
+
+    #[error("Expected 1 geometry, found {0}")]
+    NotSingleGeometry(usize),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DecodeError {
+    #[error("Encoded rtree ends early.")]
+    Truncated,
+
+    #[error("Encoded rtree has {0} trailing bytes.")]
+    TrailingBytes(usize),
+
+    #[error("Encoded rtree has an invalid layout: {0}")]
+    InvalidLayout(&'static str),
+
+    #[error("Encoded rtree has {found} rectangles, but its layout needs {expected}.")]
+    MismatchedTreeSize { expected: usize, found: usize },
 }
@@ -7,10 +7,13 @@
 use super::hilbert::Hilbert;
 use crate::utils::calculate_level_indices;
 use crate::{Coordinate, HasEnvelope, Rectangle};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::ops::Range;
 
 pub const FLATBUSH_DEFAULT_DEGREE: usize = 16;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct Flatbush {
     pub degree: usize,
@@ -26,6 +29,12 @@ impl HasEnvelope for Flatbush {
     }
 }
 
+impl Default for Flatbush {
+    fn default() -> Self {
+        Flatbush::new_empty()
+    }
+}
+
 #[allow(dead_code)]
 impl Flatbush {
     pub fn new_empty() -> Flatbush {
@@ -38,14 +47,20 @@ impl Flatbush {
     }
 
     pub fn new(degree: usize, items: &[Rectangle]) -> Flatbush {
-        let total_envelope = Rectangle::of(items);
-        let hilbert_square: Hilbert;
+        Flatbush::new_with_envelope(degree, items, Rectangle::of(items))
+    }
+
+    /// Like new, with the already-known envelope of the items.
+    fn new_with_envelope(
+        degree: usize,
+        items: &[Rectangle],
+        total_envelope: Rectangle,
+    ) -> Flatbush {
         if total_envelope.is_empty() {
             // The list of items are empty, or all items are empty.
             return Flatbush::new_unsorted(degree, items);
-        } else {
-            hilbert_square = Hilbert::new(total_envelope);
         }
+        let hilbert_square = Hilbert::new(total_envelope);
 
         let mut entries: Vec<(u32, usize, Rectangle)> = items
             .iter()
@@ -79,18 +94,15 @@ impl Flatbush {
         let mut tree: Vec<Rectangle> = Vec::with_capacity(tree_size);
         tree.extend(entries.iter().map(|(_i, e)| e));
 
-        tree.extend(vec![Rectangle::new_empty(); level_indices[1] - tree.len()]);
-
+        // A single item is its own root, with no further levels.
         for level in 1..level_indices.len() {
             let level_index = level_indices[level];
             tree.extend(vec![Rectangle::new_empty(); level_index - tree.len()]);
             assert_eq!(tree.len(), level_index);
 
             let level_items = &tree[level_indices[level - 1]..level_indices[level]];
-            let next_items: Vec<Rectangle> = level_items
-                .chunks(degree)
-                .map(|items| Rectangle::of(items))
-                .collect();
+            let next_items: Vec<Rectangle> =
+                level_items.chunks(degree).map(Rectangle::of).collect();
             tree.extend(next_items);
         }
 
@@ -112,37 +124,158 @@ impl Flatbush {
         self.tree[self.level_indices[level] + offset]
     }
 
+    /// The offsets of the children of the node at (level, offset), which
+    /// stop at the end of the child level if it is not full.
+    fn child_offsets(&self, level: usize, offset: usize) -> Range<usize> {
+        let first_child_offset = self.degree * offset;
+        let level_size = self.level_indices[level] - self.level_indices[level - 1];
+        first_child_offset..level_size.min(first_child_offset + self.degree)
+    }
+
     /**
-     * Find geometries that might intersect the query_rect.
+     * Find geometries whose envelopes satisfy the predicate.
      *
-     * This only checks bounding-box intersection, so the candidates must be
-     * checked by the caller.
+     * The predicate is applied to nodes as well as items, so it must be true
+     * of a node if it is true of any of the node's descendants (eg, "the
+     * rectangle intersects the query").  Empty rectangles are skipped.
      */
-    pub fn query_rect(&self, query: Rectangle) -> Vec<usize> {
+    pub fn query<P: Fn(Rectangle) -> bool>(&self, predicate: P) -> Vec<usize> {
         let mut results = Vec::new();
+        self.visit_leaves(predicate, |offset| results.push(self.node_indices[offset]));
+        results
+    }
+
+    /// Call visit with the offset of each leaf satisfying the predicate, as
+    /// for query.
+    fn visit_leaves<P, V>(&self, predicate: P, mut visit: V)
+    where
+        P: Fn(Rectangle) -> bool,
+        V: FnMut(usize),
+    {
         let mut stack: Vec<(usize, usize)> = vec![(self.height(), 0)];
 
         // The todo_list will keep a LIFO stack of nodes to be processed.
-        // The invariant is that everything in todo_list (envelope) intersects
-        // query_rect, and is level > 0 (leaves are yielded).
+        // The invariant is that everything in todo_list (envelope) satisfies
+        // the predicate, and is level > 0 (leaves are yielded).
         while let Some((level, offset)) = stack.pop() {
             let rect = self.get_rectangle(level, offset);
-            if !query.intersects(rect) {
+            if rect.is_empty() || !predicate(rect) {
                 continue;
             }
             if level == 0 {
-                results.push(self.node_indices[offset]);
+                visit(offset);
             } else {
                 let child_level = level - 1;
-                let first_child_offset = self.degree * offset;
-                let last_child_offset = first_child_offset + self.degree;
-                for child_offset in first_child_offset..last_child_offset {
+                for child_offset in self.child_offsets(level, offset) {
                     stack.push((child_level, child_offset));
                 }
             }
         }
+    }
 
-        results
+    /**
+     * Find geometries that might intersect the query_rect.
+     *
+     * This only checks bounding-box intersection, so the candidates must be
+     * checked by the caller.
+     */
+    pub fn query_rect(&self, query: Rectangle) -> Vec<usize> {
+        self.query(|rect| query.intersects(rect))
+    }
+
+    /**
+     * Find geometries whose envelopes are within `distance` of the query
+     * rectangle, including those intersecting it.
+     *
+     * As with query_rect, the candidates must be checked by the caller.
+     */
+    pub fn query_rect_within(&self, query: Rectangle, distance: f64) -> Vec<usize> {
+        let buffered = query.buffer(distance);
+        self.query(|rect| buffered.intersects(rect) && query.distance(rect) <= distance)
+    }
+
+    /**
+     * Find the (up to) k items intersecting the query with the largest
+     * envelope areas, largest first, eg for level-of-detail rendering.
+     * Equal areas are broken by the lower index.
+     *
+     * This keeps a heap of the best k, rather than sorting all the matches.
+     */
+    pub fn query_rect_top_k_by_area(&self, query: Rectangle, k: usize) -> Vec<usize> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut heap: BinaryHeap<Reverse<ItemArea>> = BinaryHeap::with_capacity(k + 1);
+        self.visit_leaves(
+            |rect| query.intersects(rect),
+            |offset| {
+                heap.push(Reverse(ItemArea {
+                    area: self.get_rectangle(0, offset).area(),
+                    index: self.node_indices[offset],
+                }));
+                if heap.len() > k {
+                    heap.pop();
+                }
+            },
+        );
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(item)| item.index)
+            .collect()
+    }
+
+    /**
+     * Find any one geometry that might intersect the query, stopping at the
+     * first found.  This is the first in traversal order, not the nearest.
+     */
+    pub fn query_first(&self, query: Rectangle) -> Option<usize> {
+        let mut stack: Vec<(usize, usize)> = vec![(self.height(), 0)];
+        while let Some((level, offset)) = stack.pop() {
+            if !query.intersects(self.get_rectangle(level, offset)) {
+                continue;
+            }
+            if level == 0 {
+                return Some(self.node_indices[offset]);
+            }
+            let child_level = level - 1;
+            for child_offset in self.child_offsets(level, offset) {
+                stack.push((child_level, child_offset));
+            }
+        }
+        None
+    }
+
+    /**
+     * Iterate over the (original index, envelope) of the items in leaf
+     * order, which is Hilbert order for trees built with `new`.  Neighboring
+     * items are close together, which makes for cache-friendly batches.
+     * Empty items are skipped, as in queries.
+     */
+    pub fn leaves_hilbert_order(&self) -> impl Iterator<Item = (usize, Rectangle)> + '_ {
+        self.node_indices
+            .iter()
+            .zip(&self.tree)
+            .map(|(&index, &rect)| (index, rect))
+            .filter(|(_, rect)| !rect.is_empty())
+    }
+
+    /**
+     * The rectangles of every node, level by level from the leaves up, eg
+     * to draw the tree.  The leaves are in leaf order (see
+     * `leaves_hilbert_order`), and levels may be padded with empty
+     * rectangles.
+     */
+    pub fn tree_rectangles(&self) -> &[Rectangle] {
+        &self.tree
+    }
+
+    /**
+     * The start of each level in `tree_rectangles`, from the leaves (at 0)
+     * up to the root, which is the last rectangle.  The node at `offset`
+     * in level `i` is `tree_rectangles()[level_bounds()[i] + offset]`.
+     */
+    pub fn level_bounds(&self) -> &[usize] {
+        &self.level_indices
     }
 
     /**
@@ -156,6 +289,67 @@ impl Flatbush {
         self.query_rect(Rectangle::new(position - delta, position + delta))
     }
 
+    /**
+     * Find items whose center is within `distance` of `position`, along with
+     * that distance, sorted by distance.
+     *
+     * The centers are the exact positions of the items (eg, the points of a
+     * MultiPoint), indexed the same as the items this tree was built from.
+     */
+    pub fn within_distance(
+        &self,
+        position: Coordinate,
+        distance: f64,
+        centers: &[Coordinate],
+    ) -> Vec<(usize, f64)> {
+        let mut results: Vec<(usize, f64)> = self
+            .query_within(position, distance)
+            .into_iter()
+            .map(|i| (i, centers[i].distance(position)))
+            .filter(|&(_, d)| d <= distance)
+            .collect();
+        results.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        results
+    }
+
+    /**
+     * Find the (up to) k items nearest to `position`, ordered by distance.
+     *
+     * Distance is measured to each item's envelope, so this is exact for
+     * point items, but only a candidate ordering for others.
+     */
+    pub fn query_nearest(&self, position: Coordinate, k: usize) -> Vec<usize> {
+        let mut results = Vec::with_capacity(k);
+        if k == 0 || self.envelope().is_empty() {
+            return results;
+        }
+
+        // Visit nodes closest-first; children are never closer than their
+        // parents, so leaves are popped in order of distance.
+        let mut heap = BinaryHeap::new();
+        heap.push(NodeDistance::new(0., self.height(), 0));
+        while let Some(NodeDistance { level, offset, .. }) = heap.pop() {
+            if level == 0 {
+                results.push(self.node_indices[offset]);
+                if results.len() == k {
+                    break;
+                }
+                continue;
+            }
+            let child_level = level - 1;
+            for child_offset in self.child_offsets(level, offset) {
+                let rect = self.get_rectangle(child_level, child_offset);
+                if rect.is_empty() {
+                    continue;
+                }
+                let distance = rect.distance_to_coord(position);
+                heap.push(NodeDistance::new(distance, child_level, child_offset));
+            }
+        }
+
+        results
+    }
+
     /**
      * Find all distinct elements of the Rtree that might intersect each other.
      *
@@ -192,16 +386,13 @@ impl Flatbush {
                 }
             } else if level_a == level_b {
                 let child_level = level_a - 1;
-                let first_child_offset = self.degree * offset_a;
-                for child_offset in first_child_offset..(first_child_offset + self.degree) {
+                for child_offset in self.child_offsets(level_a, offset_a) {
                     stack.push((child_level, child_offset, level_b, offset_b));
                 }
             } else {
                 assert_eq!(level_a + 1, level_b);
                 let child_level = level_b - 1;
-                let first_child_offset = self.degree * offset_b;
-                let last_child_offset = first_child_offset + self.degree;
-                for child_offset in first_child_offset..last_child_offset {
+                for child_offset in self.child_offsets(level_b, offset_b) {
                     stack.push((level_a, offset_a, child_level, child_offset));
                 }
             }
@@ -211,6 +402,108 @@ impl Flatbush {
     }
 }
 
+/// A heap entry for nearest-neighbor searches, ordered so that the closest
+/// node is the greatest (for a max-heap).
+struct NodeDistance {
+    distance: f64,
+    level: usize,
+    offset: usize,
+}
+
+impl NodeDistance {
+    fn new(distance: f64, level: usize, offset: usize) -> Self {
+        NodeDistance {
+            distance,
+            level,
+            offset,
+        }
+    }
+}
+
+impl PartialEq for NodeDistance {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for NodeDistance {}
+
+impl PartialOrd for NodeDistance {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NodeDistance {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Break ties by offset, so equidistant items come out in tree order.
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| other.level.cmp(&self.level))
+            .then_with(|| other.offset.cmp(&self.offset))
+    }
+}
+
+/// An item ordered by its area, preferring the lower index on ties.
+#[derive(Debug)]
+struct ItemArea {
+    area: f64,
+    index: usize,
+}
+
+impl PartialEq for ItemArea {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ItemArea {}
+
+impl PartialOrd for ItemArea {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ItemArea {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.area
+            .total_cmp(&other.area)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// Collect items one at a time, tracking their envelope, to build a Flatbush
+/// without a separate pass over the items to find it.
+#[derive(Debug, Clone)]
+pub struct FlatbushAccumulator {
+    degree: usize,
+    items: Vec<Rectangle>,
+    envelope: Rectangle,
+}
+
+impl FlatbushAccumulator {
+    pub fn new(degree: usize) -> Self {
+        FlatbushAccumulator {
+            degree,
+            items: Vec::new(),
+            envelope: Rectangle::new_empty(),
+        }
+    }
+
+    pub fn push(&mut self, rect: Rectangle) {
+        self.envelope.expand(rect);
+        self.items.push(rect);
+    }
+
+    /// Build the tree, as with Flatbush::new.  Items are indexed in the
+    /// order they were pushed.
+    pub fn finish(self) -> Flatbush {
+        Flatbush::new_with_envelope(self.degree, &self.items, self.envelope)
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct FlatbushNode {
     // Level in tree, 0 is leaf, max is root.
@@ -225,6 +518,7 @@ pub struct FlatbushNode {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::get_envelopes;
 
     #[test]
     fn test_empty_tree() {
@@ -270,119 +564,6 @@ mod tests {
         );
     }
 
-    fn get_envelopes() -> Vec<Rectangle> {
-        #[rustfmt::skip]
-        let rects: Vec<f64> = vec![
-             8, 62, 11, 66,
-            57, 17, 57, 19,
-            76, 26, 79, 29,
-            36, 56, 38, 56,
-            92, 77, 96, 80,
-            87, 70, 90, 74,
-            43, 41, 47, 43,
-             0, 58,  2, 62,
-            76, 86, 80, 89,
-            27, 13, 27, 15,
-            71, 63, 75, 67,
-            25,  2, 27,  2,
-            87,  6, 88,  6,
-            22, 90, 23, 93,
-            22, 89, 22, 93,
-            57, 11, 61, 13,
-            61, 55, 63, 56,
-            17, 85, 21, 87,
-            33, 43, 37, 43,
-             6,  1,  7,  3,
-            80, 87, 80, 87,
-            23, 50, 26, 52,
-            58, 89, 58, 89,
-            12, 30, 15, 34,
-            32, 58, 36, 61,
-            41, 84, 44, 87,
-            44, 18, 44, 19,
-            13, 63, 15, 67,
-            52, 70, 54, 74,
-            57, 59, 58, 59,
-            17, 90, 20, 92,
-            48, 53, 52, 56,
-             2, 68, 92, 72,
-            26, 52, 30, 52,
-            56, 23, 57, 26,
-            88, 48, 88, 48,
-            66, 13, 67, 15,
-             7, 82,  8, 86,
-            46, 68, 50, 68,
-            37, 33, 38, 36,
-             6, 15,  8, 18,
-            85, 36, 89, 38,
-            82, 45, 84, 48,
-            12,  2, 16,  3,
-            26, 15, 26, 16,
-            55, 23, 59, 26,
-            76, 37, 79, 39,
-            86, 74, 90, 77,
-            16, 75, 18, 78,
-            44, 18, 45, 21,
-            52, 67, 54, 71,
-            59, 78, 62, 78,
-            24,  5, 24,  8,
-            64, 80, 64, 83,
-            66, 55, 70, 55,
-             0, 17,  2, 19,
-            15, 71, 18, 74,
-            87, 57, 87, 59,
-             6, 34,  7, 37,
-            34, 30, 37, 32,
-            51, 19, 53, 19,
-            72, 51, 73, 55,
-            29, 45, 30, 45,
-            94, 94, 96, 95,
-             7, 22, 11, 24,
-            86, 45, 87, 48,
-            33, 62, 34, 65,
-            18, 10, 21, 14,
-            64, 66, 67, 67,
-            64, 25, 65, 28,
-            27,  4, 31,  6,
-            84,  4, 85,  5,
-            48, 80, 50, 81,
-             1, 61,  3, 61,
-            71, 89, 74, 92,
-            40, 42, 43, 43,
-            27, 64, 28, 66,
-            46, 26, 50, 26,
-            53, 83, 57, 87,
-            14, 75, 15, 79,
-            31, 45, 34, 45,
-            89, 84, 92, 88,
-            84, 51, 85, 53,
-            67, 87, 67, 89,
-            39, 26, 43, 27,
-            47, 61, 47, 63,
-            23, 49, 25, 53,
-            12,  3, 14,  5,
-            16, 50, 19, 53,
-            63, 80, 64, 84,
-            22, 63, 22, 64,
-            26, 66, 29, 66,
-             2, 15,  3, 15,
-            74, 77, 77, 79,
-            64, 11, 68, 11,
-            38,  4, 39,  8,
-            83, 73, 87, 77,
-            85, 52, 89, 56,
-            74, 60, 76, 63,
-            62, 66, 65, 67,
-        ]
-        .into_iter()
-        .map(|v| v as f64)
-        .collect();
-        rects
-            .chunks(4)
-            .map(|r| Rectangle::new((r[0], r[1]).into(), (r[2], r[3]).into()))
-            .collect()
-    }
-
     #[test]
     fn test_intersection_candidates_unsorted() {
         let envelopes = get_envelopes();
@@ -407,6 +588,150 @@ mod tests {
         assert_eq!(rtree_results, brute_results);
     }
 
+    #[test]
+    fn test_single_item() {
+        let item = Rectangle::new((1., 1.).into(), (2., 2.).into());
+        for f in &[
+            Flatbush::new(16, &[item]),
+            Flatbush::new_unsorted(16, &[item]),
+        ] {
+            assert_eq!(f.height(), 0);
+            assert_eq!(f.envelope(), item);
+            let hit = Rectangle::new((0., 0.).into(), (1.5, 1.5).into());
+            assert_eq!(f.query_rect(hit), vec![0]);
+            let miss = Rectangle::new((3., 3.).into(), (4., 4.).into());
+            assert!(f.query_rect(miss).is_empty());
+            assert_eq!(f.query_nearest((5., 5.).into(), 1), vec![0]);
+        }
+    }
+
+    #[test]
+    fn test_leaves_hilbert_order() {
+        let envelopes = get_envelopes();
+        let f = Flatbush::new(16, &envelopes);
+        let leaves: Vec<(usize, Rectangle)> = f.leaves_hilbert_order().collect();
+        let mut indices: Vec<usize> = leaves.iter().map(|&(i, _)| i).collect();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..envelopes.len()).collect::<Vec<usize>>());
+        assert!(leaves.iter().all(|&(i, rect)| rect == envelopes[i]));
+
+        let hilbert = Hilbert::new(f.envelope());
+        let hilbert_indices: Vec<u32> = leaves
+            .iter()
+            .map(|(_, rect)| hilbert.hilbert(rect.center()))
+            .collect();
+        assert!(hilbert_indices.windows(2).all(|w| w[0] <= w[1]));
+
+        let unsorted = Flatbush::new_unsorted(16, &envelopes);
+        assert!(unsorted
+            .leaves_hilbert_order()
+            .map(|(i, _)| i)
+            .eq(0..envelopes.len()));
+        assert_eq!(Flatbush::new_empty().leaves_hilbert_order().count(), 0);
+    }
+
+    #[test]
+    fn test_tree_rectangles() {
+        let envelopes = get_envelopes();
+        let f = Flatbush::new(16, &envelopes);
+        let rects = f.tree_rectangles();
+        let bounds = f.level_bounds();
+        assert_eq!(bounds[0], 0);
+        assert_eq!(rects[bounds[bounds.len() - 1]], Rectangle::of(&envelopes));
+        assert_eq!(rects[rects.len() - 1], f.envelope());
+        // Each node contains its children.
+        for level in 1..bounds.len() {
+            for offset in 0..bounds[level] - bounds[level - 1] {
+                let child_rect = rects[bounds[level - 1] + offset];
+                let parent_rect = rects[bounds[level] + offset / f.degree];
+                assert!(child_rect.is_empty() || parent_rect.contains(child_rect));
+            }
+        }
+    }
+
+    #[test]
+    fn test_query_rect_within() {
+        let envelopes = get_envelopes();
+        let f = Flatbush::new(16, &envelopes);
+        let query = Rectangle::new((40., 40.).into(), (60., 60.).into());
+        for &distance in &[0., 1., 5., 20.] {
+            let expected: Vec<usize> = (0..envelopes.len())
+                .filter(|&i| query.distance(envelopes[i]) <= distance)
+                .collect();
+            let mut results = f.query_rect_within(query, distance);
+            results.sort_unstable();
+            assert_eq!(results, expected);
+        }
+
+        // Just outside the query, but within the distance.
+        let items = [
+            Rectangle::new((0., 0.).into(), (1., 1.).into()),
+            Rectangle::new((3., 3.).into(), (4., 4.).into()),
+        ];
+        let f = Flatbush::new(16, &items);
+        let query = Rectangle::new((1.5, 0.).into(), (2., 1.).into());
+        assert!(f.query_rect(query).is_empty());
+        assert_eq!(f.query_rect_within(query, 0.5), vec![0]);
+        // The corner of the second item is sqrt(5) away.
+        assert_eq!(f.query_rect_within(query, 2.2).len(), 1);
+        assert_eq!(f.query_rect_within(query, 2.3).len(), 2);
+    }
+
+    #[test]
+    fn test_accumulator() {
+        let envelopes = get_envelopes();
+        let mut accumulator = FlatbushAccumulator::new(8);
+        for &rect in &envelopes {
+            accumulator.push(rect);
+        }
+        let accumulated = accumulator.finish();
+        let expected = Flatbush::new(8, &envelopes);
+        assert_eq!(accumulated.tree, expected.tree);
+        assert_eq!(accumulated.node_indices, expected.node_indices);
+
+        let empty = FlatbushAccumulator::new(8).finish();
+        assert!(empty.envelope().is_empty());
+    }
+
+    #[test]
+    fn test_query_rect_top_k_by_area() {
+        // Squares of side 1..=6 along a diagonal, in shuffled order.
+        let sides = [3., 6., 1., 5., 2., 4.];
+        let items: Vec<Rectangle> = sides
+            .iter()
+            .map(|&side| Rectangle::new((side * 10., 0.).into(), (side * 11., side).into()))
+            .collect();
+        let f = Flatbush::new(2, &items);
+        let everything = f.envelope();
+        assert_eq!(f.query_rect_top_k_by_area(everything, 2), vec![1, 3]);
+        assert_eq!(
+            f.query_rect_top_k_by_area(everything, 10),
+            vec![1, 3, 5, 0, 4, 2]
+        );
+        // Only the sides 1..=3 are in the query.
+        let query = Rectangle::new((0., 0.).into(), (35., 1.).into());
+        assert_eq!(f.query_rect_top_k_by_area(query, 2), vec![0, 4]);
+        assert!(f.query_rect_top_k_by_area(query, 0).is_empty());
+    }
+
+    #[test]
+    fn test_default() {
+        let f = Flatbush::default();
+        assert!(f.envelope().is_empty());
+        let query_rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        assert!(f.query_rect(query_rect).is_empty());
+    }
+
+    #[test]
+    fn test_clone() {
+        let envelopes = get_envelopes();
+        let f = Flatbush::new(16, &envelopes);
+        let g = f.clone();
+        let query_rect = Rectangle::new((40., 40.).into(), (60., 60.).into());
+        assert_eq!(g.query_rect(query_rect), f.query_rect(query_rect));
+        assert_eq!(g.query_self_intersections(), f.query_self_intersections());
+    }
+
     #[test]
     fn test_self_intersection_unsorted() {
         let envelopes: Vec<Rectangle> = get_envelopes();
@@ -429,6 +754,70 @@ mod tests {
         assert_eq!(rtree_results, brute_results);
     }
 
+    #[test]
+    fn test_query_first() {
+        let envelopes = get_envelopes();
+        let f = Flatbush::new(16, &envelopes);
+        let hit = Rectangle::new((40., 40.).into(), (60., 60.).into());
+        let index = f.query_first(hit).unwrap();
+        assert!(f.query_rect(hit).contains(&index));
+        let miss = Rectangle::new((200., 200.).into(), (300., 300.).into());
+        assert_eq!(f.query_first(miss), None);
+        assert_eq!(Flatbush::new_empty().query_first(hit), None);
+    }
+
+    #[test]
+    fn test_query_predicate() {
+        let envelopes = get_envelopes();
+        let f = Flatbush::new(16, &envelopes);
+        // Items whose center is left of x = 30; a node qualifies if it
+        // reaches left of the line.
+        let mut results = f.query(|rect| rect.x_min < 30.);
+        results.retain(|&i| (envelopes[i].x_min + envelopes[i].x_max) / 2. < 30.);
+        results.sort_unstable();
+        let expected: Vec<usize> = (0..envelopes.len())
+            .filter(|&i| (envelopes[i].x_min + envelopes[i].x_max) / 2. < 30.)
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(results, expected);
+        assert!(Flatbush::new_empty().query(|_| true).is_empty());
+    }
+
+    #[test]
+    fn test_within_distance() {
+        let centers: Vec<Coordinate> = (0..25)
+            .map(|i| Coordinate::new((i % 5) as f64, (i / 5) as f64))
+            .collect();
+        let envelopes: Vec<Rectangle> = centers.iter().map(|c| c.envelope()).collect();
+        let f = Flatbush::new(4, &envelopes);
+
+        let results = f.within_distance((2., 2.).into(), 1.2, &centers);
+        let indices: Vec<usize> = results.iter().map(|&(i, _)| i).collect();
+        assert_eq!(indices, vec![12, 7, 11, 13, 17]);
+        assert_eq!(results[0].1, 0.);
+        assert!(results[1..].iter().all(|&(_, d)| d == 1.));
+    }
+
+    #[test]
+    fn test_nearest() {
+        let envelopes = get_envelopes();
+        let f = Flatbush::new(16, &envelopes);
+        let position = Coordinate::new(50., 50.);
+
+        let mut distances: Vec<(f64, usize)> = envelopes
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.distance_to_coord(position), i))
+            .collect();
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let results = f.query_nearest(position, 5);
+        assert_eq!(results.len(), 5);
+        for (&index, &(distance, _)) in results.iter().zip(&distances) {
+            assert_eq!(envelopes[index].distance_to_coord(position), distance);
+        }
+        assert_eq!(Flatbush::new_empty().query_nearest(position, 5), vec![]);
+    }
+
     // #[test]
     // fn test_rtree_intersection_unsorted() {
     //     let mut envelopes1 = get_envelopes();
@@ -1,16 +1,48 @@
 use crate::{Coordinate, Rectangle};
 
+pub const HILBERT_MAX_BITS: u32 = 16;
+
+/**
+ * The hilbert index of position within env, as used to sort Flatbush items.
+ *
+ * For computing many indices, construct a Hilbert once and reuse it.
+ */
+pub fn hilbert_index(env: Rectangle, position: Coordinate) -> u32 {
+    Hilbert::new(env).hilbert(position)
+}
+
 pub struct Hilbert {
     env: Rectangle,
     x_min: f64,
     y_min: f64,
     x_scale: f64,
     y_scale: f64,
+    shift: u32,
 }
 
 impl Hilbert {
-    #[allow(clippy::float_cmp)]
+    /// Hilbert curve at the default (and maximum) resolution of 16 bits.
     pub fn new(env: Rectangle) -> Self {
+        Hilbert::with_bits(env, HILBERT_MAX_BITS)
+    }
+
+    /**
+     * Hilbert curve with 2^bits cells along each axis.
+     *
+     * Bits must be between 1 and 16; other values are clamped to that range.
+     * Lower resolutions give coarser orderings that are consistent with the
+     * full resolution: they only differ by making nearby positions equal.
+     */
+    #[allow(clippy::float_cmp)]
+    pub fn with_bits(env: Rectangle, bits: u32) -> Self {
+        debug_assert!(
+            (1..=HILBERT_MAX_BITS).contains(&bits),
+            "Hilbert bits must be between 1 and {}, got {}",
+            HILBERT_MAX_BITS,
+            bits
+        );
+        let bits = bits.clamp(1, HILBERT_MAX_BITS);
+        let shift = HILBERT_MAX_BITS - bits;
         if env.is_empty() {
             return Hilbert {
                 env,
@@ -18,13 +50,14 @@ impl Hilbert {
                 y_scale: 0.,
                 x_min: 0.,
                 y_min: 0.,
+                shift,
             };
         }
         let x_scale;
         let y_scale;
         let x_min;
         let y_min;
-        let hilbert_max = ((1 << 16) - 1) as f64;
+        let hilbert_max = ((1 << HILBERT_MAX_BITS) - 1) as f64;
         if env.x_min == env.x_max {
             x_scale = 0.;
             x_min = 0.;
@@ -45,6 +78,7 @@ impl Hilbert {
             y_min,
             x_scale,
             y_scale,
+            shift,
         }
     }
 
@@ -57,7 +91,7 @@ impl Hilbert {
     pub fn safe_hilbert(&self, position: Option<Coordinate>) -> u32 {
         match position {
             Some(p) if self.env.contains(p) => self.hilbert(p),
-            _ => u32::max_value(),
+            _ => u32::MAX,
         }
     }
 
@@ -70,7 +104,9 @@ impl Hilbert {
     pub fn hilbert(&self, position: Coordinate) -> u32 {
         let x = self.x_scale * (position.x - self.x_min);
         let y = self.y_scale * (position.y - self.y_min);
-        Self::hilbert_normalized(x.floor() as u32, y.floor() as u32)
+        // Drop the low bits, so every coarse cell is a block of fine cells.
+        let mask = u32::MAX << self.shift;
+        Self::hilbert_normalized(x.floor() as u32 & mask, y.floor() as u32 & mask)
     }
 
     /**
@@ -156,13 +192,23 @@ mod tests {
         assert_eq!(result, 805_306_368);
     }
 
+    #[test]
+    fn hilbert_index_matches() {
+        let total_rect = Rectangle::new((1., 2.).into(), (2., 8.).into());
+        let h = Hilbert::new(total_rect);
+        for &(x, y) in &[(1., 2.), (1.25, 5.), (1.9, 2.1), (2., 8.)] {
+            let position = Coordinate::new(x, y);
+            assert_eq!(hilbert_index(total_rect, position), h.hilbert(position));
+        }
+    }
+
     #[test]
     fn hilbert_from_none_position() {
         let total_rect = Rectangle::new((1., 2.).into(), (2., 3.).into());
         let position = None;
         let h = Hilbert::new(total_rect);
         let result = h.safe_hilbert(position);
-        assert_eq!(result, u32::max_value());
+        assert_eq!(result, u32::MAX);
     }
 
     #[test]
@@ -171,7 +217,7 @@ mod tests {
         let position = Some(Coordinate::new(4., 4.));
         let h = Hilbert::new(total_rect);
         let result = h.safe_hilbert(position);
-        assert_eq!(result, u32::max_value());
+        assert_eq!(result, u32::MAX);
     }
 
     #[test]
@@ -197,4 +243,22 @@ mod tests {
         assert!(hi2 < hi3);
         assert!(hi3 < hi4);
     }
+
+    #[test]
+    fn hilbert_with_fewer_bits() {
+        let total_rect = Rectangle::new((0., 0.).into(), (100., 100.).into());
+        let fine = Hilbert::new(total_rect);
+        let coarse = Hilbert::with_bits(total_rect, 2);
+        let mut positions: Vec<Coordinate> = (0..100)
+            .map(|i| Coordinate::new((i * 37 % 100) as f64, (i * 61 % 100) as f64))
+            .collect();
+        positions.sort_by_key(|&p| fine.hilbert(p));
+
+        let indices: Vec<u32> = positions.iter().map(|&p| coarse.hilbert(p)).collect();
+        assert!(indices.windows(2).all(|w| w[0] <= w[1]));
+        let mut distinct = indices.clone();
+        distinct.dedup();
+        assert!(distinct.len() <= 16);
+        assert!(distinct.len() > 1);
+    }
 }
@@ -1,3 +1,4 @@
 mod flatbush_impl;
 mod hilbert;
-pub use flatbush_impl::Flatbush;
+pub use flatbush_impl::{Flatbush, FlatbushAccumulator, FlatbushNode};
+pub use hilbert::{hilbert_index, Hilbert};
@@ -1,4 +1,9 @@
-use crate::Coordinate;
+use crate::errors::{ValidationError, WktError};
+use crate::geometry_state::Validated;
+use crate::{Coordinate, LineString, LinearRing};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::io::BufRead;
 use wkt::types;
 use wkt::types::Coord;
 
@@ -19,6 +24,28 @@ pub enum Geometry {
     MultiPolygon(Vec<Polygon>),
 }
 
+/// Iterates the parts of a geometry: the components of a multi-geometry, or
+/// the geometry itself if it is singular.  Empty geometries have no parts.
+impl IntoIterator for Geometry {
+    type Item = Geometry;
+    type IntoIter = std::vec::IntoIter<Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let parts = match self {
+            Geometry::Empty => Vec::new(),
+            Geometry::MultiPoint(points) => points.into_iter().map(Geometry::Point).collect(),
+            Geometry::MultiLineString(paths) => {
+                paths.into_iter().map(Geometry::LineString).collect()
+            }
+            Geometry::MultiPolygon(polygons) => {
+                polygons.into_iter().map(Geometry::Polygon).collect()
+            }
+            geom => vec![geom],
+        };
+        parts.into_iter()
+    }
+}
+
 impl From<Coord<f64>> for Coordinate {
     fn from(coord: Coord<f64>) -> Self {
         Coordinate {
@@ -40,13 +67,176 @@ fn linestring_to_positions(linestring: types::LineString<f64>) -> Vec<Coordinate
     coords_to_positions(linestring.0)
 }
 
+/// Parse the geometries of a WKT string, separated by whitespace.
+///
+/// NB: Only 2D coordinates are supported: the wkt parser rejects
+/// coordinates with z or m values, eg `POINT Z (1 2 3)`.
 #[allow(dead_code)]
 pub fn parse_wkt(wkt_str: &str) -> Result<Vec<Geometry>, &str> {
-    let wkt_geoms = wkt::Wkt::from_str(wkt_str)?;
-    let geoms = wkt_geoms.items.into_iter().map(from_wkt_geometry).collect();
+    let mut geoms = Vec::new();
+    for geom_str in split_geometries(wkt_str) {
+        let wkt_geoms = wkt::Wkt::from_str(geom_str)?;
+        geoms.extend(wkt_geoms.items.into_iter().map(from_wkt_geometry));
+    }
     Ok(geoms)
 }
 
+/// Split WKT text into the text of each geometry, since the wkt parser
+/// stops after the first.  A geometry ends at the parenthesis closing its
+/// first one, or at the word EMPTY.
+fn split_geometries(text: &str) -> Vec<&str> {
+    let mut geom_strs = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        let end = geometry_end(rest);
+        geom_strs.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    geom_strs
+}
+
+fn geometry_end(text: &str) -> usize {
+    let mut depth = 0_usize;
+    let mut word_start = None;
+    for (index, c) in text.char_indices() {
+        if depth == 0 && c.is_ascii_alphabetic() {
+            word_start.get_or_insert(index);
+            continue;
+        }
+        if let Some(start) = word_start.take() {
+            if text[start..index].eq_ignore_ascii_case("EMPTY") {
+                return index;
+            }
+        }
+        match c {
+            '(' => depth += 1,
+            // Unbalanced parentheses are left for the parser to reject.
+            ')' if depth <= 1 => return index + 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+impl TryFrom<Geometry> for LineString<Validated> {
+    type Error = ValidationError;
+
+    fn try_from(geom: Geometry) -> Result<Self, Self::Error> {
+        match geom {
+            Geometry::LineString(coords) => LineString::try_from(coords),
+            _ => Err(ValidationError::WrongGeometryType {
+                expected: "LineString",
+            }),
+        }
+    }
+}
+
+impl TryFrom<Geometry> for crate::Polygon<Validated> {
+    type Error = ValidationError;
+
+    fn try_from(geom: Geometry) -> Result<Self, Self::Error> {
+        match geom {
+            Geometry::Polygon(Polygon { shell, holes }) => {
+                let shell = LinearRing::try_from(shell)?;
+                let holes = holes
+                    .into_iter()
+                    .map(LinearRing::try_from)
+                    .collect::<Result<Vec<_>, _>>()?;
+                crate::Polygon::try_new(shell, holes)
+            }
+            _ => Err(ValidationError::WrongGeometryType {
+                expected: "Polygon",
+            }),
+        }
+    }
+}
+
+/// Lazily parse WKT from a reader, one record at a time.
+///
+/// Records are separated by blank lines, and each may hold one or more
+/// geometries.  Only the current record is held in memory.  After a parse
+/// error, parsing continues with the next record; after an IO error, the
+/// iterator ends.
+pub fn parse_wkt_reader<R: BufRead>(
+    mut reader: R,
+) -> impl Iterator<Item = Result<Geometry, WktError>> {
+    let mut pending: VecDeque<Geometry> = VecDeque::new();
+    let mut finished = false;
+    std::iter::from_fn(move || loop {
+        if let Some(geom) = pending.pop_front() {
+            return Some(Ok(geom));
+        }
+        if finished {
+            return None;
+        }
+        let record = match read_record(&mut reader) {
+            Ok(Some(record)) => record,
+            Ok(None) => {
+                finished = true;
+                continue;
+            }
+            Err(err) => {
+                finished = true;
+                return Some(Err(err.into()));
+            }
+        };
+        match parse_wkt(&record) {
+            Ok(geoms) => pending.extend(geoms),
+            Err(err) => return Some(Err(WktError::Parse(err.to_owned()))),
+        }
+    })
+}
+
+/// Parse WKT text of records separated by blank lines, returning the
+/// geometries of each record.  Extra blank lines, including trailing ones,
+/// are ignored rather than read as empty records.
+///
+/// This is the format of the benchmark test cases.  Unlike
+/// parse_wkt_reader, this stops at the first record that fails to parse.
+pub fn parse_wkt_collection(text: &str) -> Result<Vec<Vec<Geometry>>, WktError> {
+    let mut reader = text.as_bytes();
+    let mut records = Vec::new();
+    while let Some(record) = read_record(&mut reader)? {
+        records.push(parse_wkt(&record).map_err(|err| WktError::Parse(err.to_owned()))?);
+    }
+    Ok(records)
+}
+
+/// Read lines up to the next blank line, skipping leading blank lines.
+/// Returns None at the end of the input.
+fn read_record<R: BufRead>(reader: &mut R) -> std::io::Result<Option<String>> {
+    let mut record = String::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        if line.trim().is_empty() {
+            if record.is_empty() {
+                continue;
+            }
+            break;
+        }
+        record.push_str(&line);
+    }
+    if record.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(record))
+    }
+}
+
+/// Parse a WKT string holding exactly one geometry.
+pub fn parse_one(wkt_str: &str) -> Result<Geometry, WktError> {
+    let mut geoms = parse_wkt(wkt_str).map_err(|err| WktError::Parse(err.to_owned()))?;
+    if geoms.len() != 1 {
+        return Err(WktError::NotSingleGeometry(geoms.len()));
+    }
+    Ok(geoms.remove(0))
+}
+
 fn from_wkt_geometry(geom: wkt::Geometry<f64>) -> Geometry {
     match geom {
         wkt::Geometry::Point(p) => from_wkt_point(p),
@@ -111,9 +301,7 @@ mod tests {
     }
 
     fn get_single_geom(wkt_str: &str) -> Geometry {
-        let mut geoms = parse_wkt(wkt_str).unwrap();
-        assert_eq!(geoms.len(), 1);
-        geoms.remove(0)
+        parse_one(wkt_str).unwrap()
     }
 
     fn assert_equals_point(wkt_str: &str, x: f64, y: f64) {
@@ -184,11 +372,154 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_reader() {
+        let input = "POINT(1 2)\n\nLINESTRING(0 0,\n 1 1)\n\n\nxyz\n\nPOINT(3 4)\n";
+        let mut geoms = parse_wkt_reader(std::io::Cursor::new(input));
+        assert_eq!(
+            geoms.next().unwrap().unwrap(),
+            Geometry::Point((1., 2.).into())
+        );
+        assert_eq!(
+            geoms.next().unwrap().unwrap(),
+            Geometry::LineString(make_positions(vec![(0., 0.), (1., 1.)]))
+        );
+        assert!(geoms.next().unwrap().is_err());
+        assert_eq!(
+            geoms.next().unwrap().unwrap(),
+            Geometry::Point((3., 4.).into())
+        );
+        assert!(geoms.next().is_none());
+
+        // Every geometry of a record is read.
+        let input = "POINT(1 2) POINT EMPTY\nLINESTRING(0 0, 1 1)\n\nPOINT(3 4)";
+        let geoms: Vec<Geometry> = parse_wkt_reader(std::io::Cursor::new(input))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            geoms,
+            vec![
+                Geometry::Point((1., 2.).into()),
+                Geometry::Empty,
+                Geometry::LineString(make_positions(vec![(0., 0.), (1., 1.)])),
+                Geometry::Point((3., 4.).into()),
+            ]
+        );
+    }
+
+    /// A reader whose reads always fail.
+    struct FailingReader;
+
+    impl std::io::Read for FailingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "read failed",
+            ))
+        }
+    }
+
+    #[test]
+    fn check_reader_io_error() {
+        let mut geoms = parse_wkt_reader(std::io::BufReader::new(FailingReader));
+        assert!(matches!(geoms.next(), Some(Err(WktError::Io(_)))));
+        assert!(geoms.next().is_none());
+    }
+
+    #[test]
+    fn check_collection() {
+        let text = "\n\nPOINT(1 2)\n\n\nLINESTRING(0 0,\n 1 1)\n\n\n";
+        assert_eq!(
+            parse_wkt_collection(text).unwrap(),
+            vec![
+                vec![Geometry::Point((1., 2.).into())],
+                vec![Geometry::LineString(make_positions(vec![
+                    (0., 0.),
+                    (1., 1.)
+                ]))],
+            ]
+        );
+        assert_eq!(parse_wkt_collection("\n\n").unwrap(), Vec::<Vec<_>>::new());
+        assert!(matches!(
+            parse_wkt_collection("POINT(1 2)\n\nxyz"),
+            Err(WktError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn check_into_validated() {
+        let geom = get_single_geom("POLYGON((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 1 2, 2 2, 1 1))");
+        let polygon = crate::Polygon::<Validated>::try_from(geom).unwrap();
+        assert_eq!(polygon.shell().coords().len(), 5);
+        assert_eq!(polygon.holes().len(), 1);
+
+        // The hole crosses the shell
+        let geom = get_single_geom("POLYGON((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 1 5, 2 2, 1 1))");
+        assert!(crate::Polygon::<Validated>::try_from(geom).is_err());
+
+        let geom = get_single_geom("LINESTRING(0 0, 1 1, 2 0)");
+        let path = LineString::<Validated>::try_from(geom).unwrap();
+        assert_eq!(path.coords().len(), 3);
+
+        let geom = get_single_geom("POINT(1 1)");
+        assert_eq!(
+            LineString::<Validated>::try_from(geom).unwrap_err(),
+            ValidationError::WrongGeometryType {
+                expected: "LineString"
+            }
+        );
+    }
+
+    #[test]
+    fn check_parse_one() {
+        assert_eq!(
+            parse_one("POINT(1 2)").unwrap(),
+            Geometry::Point((1., 2.).into())
+        );
+        assert!(matches!(parse_one(""), Err(WktError::NotSingleGeometry(0))));
+        assert!(matches!(
+            parse_one("POINT(1 2) LINESTRING(0 0, 1 1)"),
+            Err(WktError::NotSingleGeometry(2))
+        ));
+        assert!(matches!(
+            parse_one("POINT(1 2) xyz"),
+            Err(WktError::Parse(_))
+        ));
+        assert!(matches!(parse_one("xyz"), Err(WktError::Parse(_))));
+    }
+
+    #[test]
+    fn check_into_iter() {
+        let geom = get_single_geom("MULTIPOLYGON(((0 0, 1 0, 1 1, 0 0)), ((2 2, 3 2, 3 3, 2 2)))");
+        let parts: Vec<Geometry> = geom.into_iter().collect();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(
+            parts[1],
+            Geometry::Polygon(Polygon {
+                shell: make_positions(vec![(2., 2.), (3., 2.), (3., 3.), (2., 2.)]),
+                holes: Vec::new(),
+            })
+        );
+
+        let point = Geometry::Point((1., 2.).into());
+        assert_eq!(
+            point.into_iter().collect::<Vec<_>>(),
+            vec![Geometry::Point((1., 2.).into())]
+        );
+        assert_eq!(Geometry::Empty.into_iter().count(), 0);
+    }
+
     #[test]
     fn check_empty_str() {
         assert_eq!(parse_wkt("").unwrap(), Vec::new());
     }
 
+    #[test]
+    fn check_z_unsupported() {
+        assert!(parse_wkt("POINT Z (1 2 3)").is_err());
+        assert!(parse_wkt("POINT (1 2 3)").is_err());
+    }
+
     #[test]
     fn check_bad_str() {
         assert!(parse_wkt("xyz").is_err());
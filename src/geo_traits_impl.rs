@@ -0,0 +1,160 @@
+// Implementations of the geo-traits accessors, behind the `geo-traits`
+// feature, so geometries can be passed to geo algorithms without copying.
+
+use crate::{Coordinate, LineString, LinearRing, Polygon};
+use geo_traits::{CoordTrait, Dimensions, LineStringTrait, PolygonTrait};
+
+impl CoordTrait for Coordinate {
+    type T = f64;
+
+    fn dim(&self) -> Dimensions {
+        Dimensions::Xy
+    }
+
+    fn nth_or_panic(&self, n: usize) -> f64 {
+        match n {
+            0 => self.x,
+            1 => self.y,
+            _ => panic!("Coordinate has 2 dimensions, got index {}", n),
+        }
+    }
+
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+}
+
+impl<S> LineStringTrait for LineString<S> {
+    type T = f64;
+    type CoordType<'a>
+        = Coordinate
+    where
+        S: 'a;
+
+    fn dim(&self) -> Dimensions {
+        Dimensions::Xy
+    }
+
+    fn num_coords(&self) -> usize {
+        self.coords.len()
+    }
+
+    unsafe fn coord_unchecked(&self, i: usize) -> Coordinate {
+        *self.coords.get_unchecked(i)
+    }
+}
+
+impl<S> LineStringTrait for LinearRing<S> {
+    type T = f64;
+    type CoordType<'a>
+        = Coordinate
+    where
+        S: 'a;
+
+    fn dim(&self) -> Dimensions {
+        Dimensions::Xy
+    }
+
+    fn num_coords(&self) -> usize {
+        LinearRing::coords(self).len()
+    }
+
+    unsafe fn coord_unchecked(&self, i: usize) -> Coordinate {
+        *LinearRing::coords(self).get_unchecked(i)
+    }
+}
+
+// The rings of a polygon are borrowed, so the references need the trait too.
+impl<S> LineStringTrait for &LinearRing<S> {
+    type T = f64;
+    type CoordType<'a>
+        = Coordinate
+    where
+        Self: 'a;
+
+    fn dim(&self) -> Dimensions {
+        Dimensions::Xy
+    }
+
+    fn num_coords(&self) -> usize {
+        LinearRing::coords(self).len()
+    }
+
+    unsafe fn coord_unchecked(&self, i: usize) -> Coordinate {
+        *LinearRing::coords(self).get_unchecked(i)
+    }
+}
+
+impl<S> PolygonTrait for Polygon<S> {
+    type T = f64;
+    type RingType<'a>
+        = &'a LinearRing<S>
+    where
+        S: 'a;
+
+    fn dim(&self) -> Dimensions {
+        Dimensions::Xy
+    }
+
+    // An empty polygon has no exterior, as in geo-types.
+    fn exterior(&self) -> Option<&LinearRing<S>> {
+        if self.shell().coords().is_empty() {
+            None
+        } else {
+            Some(self.shell())
+        }
+    }
+
+    fn num_interiors(&self) -> usize {
+        self.holes().len()
+    }
+
+    unsafe fn interior_unchecked(&self, i: usize) -> &LinearRing<S> {
+        self.holes().get_unchecked(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry_state::Validated;
+    use std::convert::TryFrom;
+
+    /// Twice the area of the polygon, using only the trait accessors.
+    fn area_2x<P: PolygonTrait<T = f64>>(polygon: &P) -> f64 {
+        let ring_area_2x = |ring: P::RingType<'_>| {
+            let coords: Vec<(f64, f64)> = ring.coords().map(|c| c.x_y()).collect();
+            coords
+                .windows(2)
+                .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+                .sum::<f64>()
+                .abs()
+        };
+        polygon.exterior().map_or(0., ring_area_2x)
+            - polygon.interiors().map(ring_area_2x).sum::<f64>()
+    }
+
+    #[test]
+    fn test_polygon_trait() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]).unwrap();
+        let hole = LinearRing::try_from(vec![(1., 1.), (1., 2.), (2., 2.), (1., 1.)]).unwrap();
+        let polygon = Polygon::try_new(shell, vec![hole]).unwrap();
+        assert_eq!(area_2x(&polygon), 2. * polygon.area());
+        assert_eq!(polygon.num_interiors(), 1);
+        assert_eq!(
+            polygon.interior(0).unwrap().coord(2).unwrap().x_y(),
+            (2., 2.)
+        );
+        assert!(Polygon::<Validated>::empty().exterior().is_none());
+
+        let path = LineString::try_from(vec![(0., 0.), (1.5, -2.)]).unwrap();
+        let path: &LineString<Validated> = &path;
+        assert_eq!(path.num_coords(), 2);
+        assert_eq!(LineStringTrait::coord(path, 1).unwrap().nth(1), Some(-2.));
+    }
+}
@@ -6,20 +6,73 @@ pub struct Raw;
 #[derive(Debug, Clone)]
 pub struct Prepared {
     pub(crate) rtree: SegRTree,
+    envelope: Rectangle,
+    // The length of the path up to each coordinate.
+    lengths: Vec<f64>,
+}
+
+impl Prepared {
+    pub(crate) fn new(rtree: SegRTree, lengths: Vec<f64>) -> Self {
+        let envelope = rtree.envelope();
+        Prepared {
+            rtree,
+            envelope,
+            lengths,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Validated {
     pub(crate) rtree: SegRTree,
+    envelope: Rectangle,
+    lengths: Vec<f64>,
+    // The shoelace signed area of the path, accumulated during validation.
+    pub(crate) signed_area: f64,
+    // Whether the path is a convex loop, found during validation.
+    pub(crate) is_convex: bool,
+}
+
+impl Validated {
+    pub(crate) fn new(rtree: SegRTree) -> Self {
+        let envelope = rtree.envelope();
+        Validated {
+            rtree,
+            envelope,
+            lengths: Vec::new(),
+            signed_area: 0.,
+            is_convex: false,
+        }
+    }
+
+    pub(crate) fn from_prepared(prepared: Prepared, signed_area: f64, is_convex: bool) -> Self {
+        Validated {
+            rtree: prepared.rtree,
+            envelope: prepared.envelope,
+            lengths: prepared.lengths,
+            signed_area,
+            is_convex,
+        }
+    }
 }
 
 pub trait HasRTree: HasEnvelope {
     fn rtree(&self) -> &SegRTree;
+
+    /// The envelope of the rtree.  States cache this at construction, since
+    /// the rtree is immutable once built.
+    fn rtree_envelope(&self) -> Rectangle {
+        self.rtree().envelope()
+    }
+
+    /// The length of the path up to each coordinate, computed when the
+    /// rtree is built.
+    fn cumulative_lengths(&self) -> &[f64];
 }
 
 impl<C: HasRTree> HasEnvelope for C {
     fn envelope(&self) -> Rectangle {
-        self.rtree().envelope()
+        self.rtree_envelope()
     }
 }
 
@@ -27,10 +80,26 @@ impl HasRTree for Prepared {
     fn rtree(&self) -> &SegRTree {
         &self.rtree
     }
+
+    fn rtree_envelope(&self) -> Rectangle {
+        self.envelope
+    }
+
+    fn cumulative_lengths(&self) -> &[f64] {
+        &self.lengths
+    }
 }
 
 impl HasRTree for Validated {
     fn rtree(&self) -> &SegRTree {
         &self.rtree
     }
+
+    fn rtree_envelope(&self) -> Rectangle {
+        self.envelope
+    }
+
+    fn cumulative_lengths(&self) -> &[f64] {
+        &self.lengths
+    }
 }
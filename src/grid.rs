@@ -0,0 +1,173 @@
+use crate::{HasEnvelope, Rectangle};
+use std::ops::RangeInclusive;
+
+/// A fixed grid of square cells over the extent of its items, each cell
+/// holding the items whose rectangles overlap it.
+///
+/// For uniformly distributed items of similar size, this can be faster to
+/// query than an rtree.  It has the same query_rect as Flatbush, so it can be
+/// used in its place.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    extent: Rectangle,
+    cell_size: f64,
+    num_columns: usize,
+    num_rows: usize,
+    cells: Vec<Vec<usize>>,
+    items: Vec<Rectangle>,
+}
+
+impl HasEnvelope for Grid {
+    fn envelope(&self) -> Rectangle {
+        self.extent
+    }
+}
+
+impl Grid {
+    /// Build a grid with the given cell side length, which must be positive.
+    /// Empty items are indexed, but never returned by queries.
+    ///
+    /// The grid allocates every cell, so memory grows with the extent's area
+    /// divided by cell_size^2: halving cell_size quadruples it.  Panics if
+    /// the cells are too many to allocate.
+    pub fn new(cell_size: f64, items: &[Rectangle]) -> Grid {
+        assert!(
+            cell_size > 0.,
+            "Cell size must be positive, got {}",
+            cell_size
+        );
+        let extent = Rectangle::of(items);
+        let (num_columns, num_rows) = if extent.is_empty() {
+            (0, 0)
+        } else {
+            (
+                // The casts saturate, and so do the additions.
+                (((extent.x_max - extent.x_min) / cell_size) as usize).saturating_add(1),
+                (((extent.y_max - extent.y_min) / cell_size) as usize).saturating_add(1),
+            )
+        };
+        let num_cells = num_columns
+            .checked_mul(num_rows)
+            .filter(|&n| {
+                n.checked_mul(std::mem::size_of::<Vec<usize>>())
+                    .map_or(false, |bytes| bytes <= isize::MAX as usize)
+            })
+            .unwrap_or_else(|| {
+                panic!(
+                    "Too many cells for cell size {}: {} columns by {} rows",
+                    cell_size, num_columns, num_rows
+                )
+            });
+        let mut grid = Grid {
+            extent,
+            cell_size,
+            num_columns,
+            num_rows,
+            cells: vec![Vec::new(); num_cells],
+            items: items.to_vec(),
+        };
+
+        for (index, &item) in items.iter().enumerate() {
+            if item.is_empty() {
+                continue;
+            }
+            let (columns, rows) = grid.cell_ranges(item);
+            for row in rows {
+                for column in columns.clone() {
+                    grid.cells[row * num_columns + column].push(index);
+                }
+            }
+        }
+        grid
+    }
+
+    /**
+     * Find items whose rectangles intersect the query.
+     *
+     * This only checks bounding-box intersection, so the candidates must be
+     * checked by the caller.  The results are in ascending order.
+     */
+    pub fn query_rect(&self, query: Rectangle) -> Vec<usize> {
+        let mut results = Vec::new();
+        if !self.extent.intersects(query) {
+            return results;
+        }
+        let (columns, rows) = self.cell_ranges(query);
+        for row in rows {
+            for column in columns.clone() {
+                results.extend(
+                    self.cells[row * self.num_columns + column]
+                        .iter()
+                        .filter(|&&index| self.items[index].intersects(query)),
+                );
+            }
+        }
+        // Items overlapping several cells are found once per cell.
+        results.sort_unstable();
+        results.dedup();
+        results
+    }
+
+    /// The columns and rows of the cells that rect overlaps, clamped to the
+    /// grid.
+    fn cell_ranges(&self, rect: Rectangle) -> (RangeInclusive<usize>, RangeInclusive<usize>) {
+        let column = |x: f64| self.cell_of(x - self.extent.x_min, self.num_columns);
+        let row = |y: f64| self.cell_of(y - self.extent.y_min, self.num_rows);
+        (
+            column(rect.x_min)..=column(rect.x_max),
+            row(rect.y_min)..=row(rect.y_max),
+        )
+    }
+
+    fn cell_of(&self, distance: f64, num_cells: usize) -> usize {
+        // Negative distances saturate to 0 in the cast.
+        ((distance / self.cell_size) as usize).min(num_cells - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::get_envelopes;
+    use crate::Flatbush;
+
+    fn brute_force(items: &[Rectangle], query: Rectangle) -> Vec<usize> {
+        (0..items.len())
+            .filter(|&i| items[i].intersects(query))
+            .collect()
+    }
+
+    #[test]
+    fn test_query_rect() {
+        let envelopes = get_envelopes();
+        let flatbush = Flatbush::new(16, &envelopes);
+        for &cell_size in &[1., 7.5, 20., 1000.] {
+            let grid = Grid::new(cell_size, &envelopes);
+            for &(x_min, y_min, x_max, y_max) in &[
+                (40., 40., 60., 60.),
+                (0., 0., 10., 10.),
+                (-10., -10., 200., 200.),
+                (57., 18., 57., 18.),
+                (-10., -10., -5., -5.),
+            ] {
+                let query = Rectangle::new((x_min, y_min).into(), (x_max, y_max).into());
+                let expected = brute_force(&envelopes, query);
+                assert_eq!(grid.query_rect(query), expected);
+                let mut results = flatbush.query_rect(query);
+                results.sort_unstable();
+                assert_eq!(results, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty() {
+        let grid = Grid::new(1., &[]);
+        assert!(grid.envelope().is_empty());
+        let query = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        assert!(grid.query_rect(query).is_empty());
+
+        let grid = Grid::new(1., &[Rectangle::new_empty(), query]);
+        assert_eq!(grid.query_rect(query), vec![1]);
+    }
+}
@@ -1,21 +1,29 @@
 mod coordinate;
 mod flatbush;
+#[cfg(feature = "geo-traits")]
+mod geo_traits_impl;
 mod geometry_state;
+mod grid;
 mod line_string;
 mod linear_ring;
+mod multi_point;
 mod polygon;
 mod rectangle;
 mod seg_rtree;
+#[cfg(test)]
+mod test_utils;
 
 pub mod algorithms;
 pub mod errors;
 pub mod from_wkt;
 pub mod utils;
 
-pub use crate::seg_rtree::{SegRTree, SegmentUnion};
+pub use crate::seg_rtree::{GrowthPolicy, SegRTree, SegmentUnion};
 pub use coordinate::Coordinate;
-pub use flatbush::Flatbush;
-pub use line_string::LineString;
+pub use flatbush::{hilbert_index, Flatbush, FlatbushAccumulator, FlatbushNode, Hilbert};
+pub use grid::Grid;
+pub use line_string::{LineString, LineStringEditor};
 pub use linear_ring::LinearRing;
-pub use polygon::Polygon;
-pub use rectangle::{HasEnvelope, Rectangle};
+pub use multi_point::MultiPoint;
+pub use polygon::{Polygon, PolygonStats};
+pub use rectangle::{total_envelope, HasEnvelope, Rectangle, Side};
@@ -1,26 +1,221 @@
-use crate::errors::ValidationError;
+use crate::errors::{CommitError, ValidationError};
 use crate::geometry_state::{HasRTree, Prepared, Raw, Validated};
 use crate::seg_rtree::SegRTree;
-use crate::utils::{intersect_segments, rectangles_from_coordinates};
-use crate::Coordinate;
+use crate::utils::{
+    intersect_segments, is_convex_loop, point_segment_distance, rectangles_from_coordinates,
+};
+use crate::{Coordinate, Rectangle};
 use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Range;
+
+pub const LINE_STRING_DEFAULT_DEGREE: usize = 16;
 
 #[derive(Debug, Clone)]
 pub struct LineString<S> {
     pub(crate) coords: Vec<Coordinate>,
     pub(crate) state: S,
+    pub(crate) id: Option<u64>,
+}
+
+/// Formats as WKT, eg `LINESTRING (0 0, 1 1)`.
+impl<S> fmt::Display for LineString<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.coords.is_empty() {
+            write!(f, "LINESTRING EMPTY")
+        } else {
+            write!(f, "LINESTRING ")?;
+            fmt_wkt_coords(f, &self.coords)
+        }
+    }
+}
+
+/// Write coords as a parenthesized WKT coordinate list.
+pub(crate) fn fmt_wkt_coords(f: &mut fmt::Formatter<'_>, coords: &[Coordinate]) -> fmt::Result {
+    write!(f, "(")?;
+    for (i, coord) in coords.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{} {}", coord.x, coord.y)?;
+    }
+    write!(f, ")")
 }
 
 impl<S: HasRTree> HasRTree for LineString<S> {
     fn rtree(&self) -> &SegRTree {
         self.state.rtree()
     }
+
+    fn rtree_envelope(&self) -> Rectangle {
+        self.state.rtree_envelope()
+    }
+
+    fn cumulative_lengths(&self) -> &[f64] {
+        self.state.cumulative_lengths()
+    }
 }
 
 impl<S> LineString<S> {
     pub fn coords(&self) -> &Vec<Coordinate> {
         &self.coords
     }
+
+    /// Tag the path with an id, which is kept through prepare and validate.
+    pub fn with_id(mut self, id: u64) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+
+    /// The segments of the path, as (start, end) pairs.
+    pub fn segments(&self) -> impl Iterator<Item = (Coordinate, Coordinate)> + '_ {
+        self.coords.windows(2).map(|w| (w[0], w[1]))
+    }
+
+    /// The total length of the segments.
+    pub fn length(&self) -> f64 {
+        self.segments()
+            .map(|(start, end)| start.distance(end))
+            .sum()
+    }
+
+    /// The index range of the longest run of coordinates within tolerance of
+    /// the chord joining the run's ends.
+    ///
+    /// Runs are found greedily: each run is extended as far as it can go,
+    /// and the next starts at its last coordinate.  Paths with fewer than two
+    /// coordinates return the whole (trivial) range.
+    pub fn longest_straight_run(&self, tolerance: f64) -> Range<usize> {
+        let coords = &self.coords;
+        if coords.len() < 2 {
+            return 0..coords.len();
+        }
+        let is_straight = |start: usize, end: usize| {
+            coords[start + 1..end]
+                .iter()
+                .all(|&c| point_segment_distance(c, coords[start], coords[end]) <= tolerance)
+        };
+
+        let mut longest = 0..2;
+        let mut start = 0;
+        while start + 1 < coords.len() {
+            // The run is coords[start..end]
+            let mut end = start + 2;
+            while end < coords.len() && is_straight(start, end) {
+                end += 1;
+            }
+            if end - start > longest.len() {
+                longest = start..end;
+            }
+            start = end - 1;
+        }
+        longest
+    }
+
+    /// A new path of the coordinates in range, eg a span found by
+    /// longest_straight_run or a query.  Like slicing, this panics if the
+    /// range is out of bounds.
+    pub fn slice(&self, range: Range<usize>) -> LineString<Raw> {
+        LineString::new(self.coords[range].to_vec())
+    }
+}
+
+impl<S: HasRTree> LineString<S> {
+    /// The length of the path from its start to the coordinate at index,
+    /// in O(1).  Panics if index is out of bounds.
+    pub fn length_at(&self, index: usize) -> f64 {
+        self.cumulative_lengths()[index]
+    }
+
+    /// The point at the given distance along the path, clamped to the
+    /// endpoints.  Returns None for an empty path.
+    pub fn interpolate(&self, distance: f64) -> Option<Coordinate> {
+        interpolate_along(&self.coords, self.cumulative_lengths(), distance)
+    }
+
+    fn locate(&self, distance: f64) -> Option<(usize, Coordinate)> {
+        locate_along(&self.coords, self.cumulative_lengths(), distance)
+    }
+
+    /// The segments crossing the segment from a to b, as segment indices and
+    /// crossing points, ordered by index.  Where a segment overlaps ab, the
+    /// point is the first point of the overlap along the path.
+    pub fn segments_crossing(&self, a: Coordinate, b: Coordinate) -> Vec<(usize, Coordinate)> {
+        segments_crossing(&self.coords, self.rtree(), a, b)
+    }
+}
+
+/// The length of the path of coords up to each coordinate.
+fn cumulative_lengths(coords: &[Coordinate]) -> Vec<f64> {
+    let mut lengths = Vec::with_capacity(coords.len());
+    let mut traveled = 0.;
+    if !coords.is_empty() {
+        lengths.push(traveled);
+    }
+    for range in coords.windows(2) {
+        traveled += range[0].distance(range[1]);
+        lengths.push(traveled);
+    }
+    lengths
+}
+
+/// The point at the given distance along the path of coords, with the given
+/// cumulative lengths; see LineString::interpolate.
+fn interpolate_along(coords: &[Coordinate], lengths: &[f64], distance: f64) -> Option<Coordinate> {
+    match locate_along(coords, lengths, distance) {
+        Some((_, point)) => Some(point),
+        None => coords.last().copied(),
+    }
+}
+
+/// The segment index and point at the given distance along the path of
+/// coords, with the given cumulative lengths, if it is strictly after the
+/// start and no later than the end.  This binary-searches the lengths.
+fn locate_along(
+    coords: &[Coordinate],
+    lengths: &[f64],
+    distance: f64,
+) -> Option<(usize, Coordinate)> {
+    if distance <= 0. {
+        return coords.first().map(|&c| (0, c));
+    }
+    if distance.is_nan() || distance > *lengths.last()? {
+        return None;
+    }
+    // The first coordinate at or past distance ends the segment.
+    let index = lengths.partition_point(|&length| length < distance) - 1;
+    let (start, end) = (coords[index], coords[index + 1]);
+    let fraction = (distance - lengths[index]) / start.distance(end);
+    Some((index, start.lerp(end, fraction)))
+}
+
+/// The segments of coords (indexed by rtree) crossing the segment from a to
+/// b; see LineString::segments_crossing.
+pub(crate) fn segments_crossing(
+    coords: &[Coordinate],
+    rtree: &SegRTree,
+    a: Coordinate,
+    b: Coordinate,
+) -> Vec<(usize, Coordinate)> {
+    let mut indices = rtree.query_rect(Rectangle::new(a, b));
+    indices.sort_unstable();
+    indices
+        .into_iter()
+        .filter_map(|index| {
+            let seg_start = coords[index];
+            let (start, end) = intersect_segments(seg_start, coords[index + 1], a, b)?;
+            if seg_start.distance(end) < seg_start.distance(start) {
+                Some((index, end))
+            } else {
+                Some((index, start))
+            }
+        })
+        .collect()
 }
 
 impl LineString<Raw> {
@@ -28,32 +223,133 @@ impl LineString<Raw> {
         LineString {
             coords,
             state: Raw {},
+            id: None,
         }
     }
 
+    /// Like interpolate on a prepared path, but the cumulative lengths are
+    /// computed first, so this is O(n).
+    pub fn interpolate(&self, distance: f64) -> Option<Coordinate> {
+        interpolate_along(&self.coords, &cumulative_lengths(&self.coords), distance)
+    }
+
+    /// Apply f to every coordinate, eg to reproject or round them.
+    pub fn map_coords<F: FnMut(Coordinate) -> Coordinate>(self, f: F) -> LineString<Raw> {
+        LineString {
+            coords: self.coords.into_iter().map(f).collect(),
+            state: self.state,
+            id: self.id,
+        }
+    }
+
+    /// Close the path by appending its first coordinate, so it can be made
+    /// into a ring.  Paths that are already closed, or have fewer than 3
+    /// coordinates, are unchanged.
+    pub fn close(mut self) -> LineString<Raw> {
+        if self.coords.len() >= 3 && self.coords.first() != self.coords.last() {
+            self.coords.push(self.coords[0]);
+        }
+        self
+    }
+
+    /// The envelope of the coordinates, without building an rtree.
+    pub fn envelope_only(&self) -> Rectangle {
+        Rectangle::of(&self.coords)
+    }
+
     pub fn prepare(self) -> LineString<Prepared> {
+        self.prepare_with_degree(LINE_STRING_DEFAULT_DEGREE)
+    }
+
+    /// Like prepare, but build the rtree with the given degree (at least 2).
+    pub fn prepare_with_degree(self, degree: usize) -> LineString<Prepared> {
         let rtree = if self.coords.is_empty() {
             SegRTree::new_empty()
         } else {
-            SegRTree::new_loaded(16, &rectangles_from_coordinates(&self.coords))
+            SegRTree::new_loaded(degree, &rectangles_from_coordinates(&self.coords))
         };
+        let lengths = cumulative_lengths(&self.coords);
         LineString {
             coords: self.coords,
-            state: Prepared { rtree },
+            state: Prepared::new(rtree, lengths),
+            id: self.id,
         }
     }
 
     pub fn validate(self) -> Result<LineString<Validated>, ValidationError> {
         self.prepare().validate()
     }
+
+    /// Validate the path, additionally requiring every coordinate to be
+    /// within bounds (inclusive).
+    pub fn validate_within(
+        self,
+        bounds: Rectangle,
+    ) -> Result<LineString<Validated>, ValidationError> {
+        if let Some((index, &position)) = self
+            .coords
+            .iter()
+            .enumerate()
+            .find(|(_, &c)| c.is_finite() && !bounds.contains(c))
+        {
+            return Err(ValidationError::OutOfBounds { index, position });
+        }
+        self.validate()
+    }
 }
 
 impl LineString<Prepared> {
+    /// The points where pairs of segments intersect, as (first index,
+    /// second index, point), ordered by the indices.  Adjacent segments
+    /// meeting at their shared vertex are not reported (nor are the first and
+    /// last segments of a closed path).  For overlapping segments, the point
+    /// is the lexicographically smaller end of the overlap.
+    pub fn self_intersection_points(&self) -> Vec<(usize, usize, Coordinate)> {
+        let coords = &self.coords;
+        let is_closed = coords.len() > 3 && coords.first() == coords.last();
+        let mut points: Vec<(usize, usize, Coordinate)> = self
+            .rtree()
+            .query_self_intersections()
+            .into_iter()
+            .filter_map(|(first, second)| {
+                let (start, end) = intersect_segments(
+                    coords[first],
+                    coords[first + 1],
+                    coords[second],
+                    coords[second + 1],
+                )?;
+                let shared_vertex = if second == first + 1 {
+                    Some(coords[second])
+                } else if is_closed && first == 0 && second == coords.len() - 2 {
+                    Some(coords[0])
+                } else {
+                    None
+                };
+                if start == end && Some(start) == shared_vertex {
+                    None
+                } else {
+                    Some((first, second, start))
+                }
+            })
+            .collect();
+        points.sort_by_key(|&(first, second, _)| (first, second));
+        points
+    }
+
     pub fn validate(self) -> Result<LineString<Validated>, ValidationError> {
+        let signed_area = self.check()?;
+        Ok(self.into_validated(signed_area))
+    }
+
+    /// Check the path is valid, returning its signed area.
+    fn check(&self) -> Result<f64, ValidationError> {
         if self.coords.len() == 1 {
             return Err(ValidationError::SinglePathCoordinate);
         }
+        // Summed as in utils::signed_area, so the cached value is identical.
+        let mut crosses = 0.;
         for (index, range) in self.coords.windows(2).enumerate() {
+            crosses += range[0].cross(range[1]);
             if range[0] == range[1] {
                 return Err(ValidationError::DegenerateSegment {
                     index,
@@ -68,17 +364,106 @@ impl LineString<Prepared> {
         for (index_a, index_b) in self.rtree().query_self_intersections() {
             check_intersection(index_a, index_b, &self.coords)?;
         }
+        Ok(crosses / 2.)
+    }
 
-        Ok(LineString {
+    fn into_validated(self, signed_area: f64) -> LineString<Validated> {
+        let is_closed = self.coords.len() >= 4 && self.coords.first() == self.coords.last();
+        let is_convex = is_closed && is_convex_loop(&self.coords);
+        LineString {
             coords: self.coords,
-            state: Validated {
-                rtree: self.state.rtree,
-            },
-        })
+            state: Validated::from_prepared(self.state, signed_area, is_convex),
+            id: self.id,
+        }
     }
 }
 
-impl LineString<Validated> {}
+impl LineString<Validated> {
+    /// Start editing the coordinates in place, eg while dragging a vertex.
+    /// The rtree is rebuilt, with the same degree, when the edit is
+    /// committed.
+    pub fn edit(self) -> LineStringEditor {
+        let degree = if self.coords.is_empty() {
+            LINE_STRING_DEFAULT_DEGREE
+        } else {
+            self.rtree().degree()
+        };
+        LineStringEditor {
+            coords: self.coords,
+            id: self.id,
+            degree,
+        }
+    }
+
+    /// Split the path at the given distance along it, adding the split point
+    /// to the end of the first part and the start of the second.
+    ///
+    /// If the distance is not strictly between 0 and the length, the first
+    /// part is the whole path and the second is empty.
+    pub fn split_at(&self, distance: f64) -> (LineString<Raw>, LineString<Raw>) {
+        let whole = || {
+            (
+                LineString::new(self.coords.clone()),
+                LineString::new(Vec::new()),
+            )
+        };
+        if !(distance > 0. && distance < self.length()) {
+            return whole();
+        }
+        let (index, point) = match self.locate(distance) {
+            Some(location) => location,
+            None => return whole(),
+        };
+        let mut first = self.coords[..=index].to_vec();
+        first.push(point);
+        let mut second = vec![point];
+        let rest = &self.coords[index + 1..];
+        second.extend(rest.iter().skip_while(|&&c| c == point));
+        (LineString::new(first), LineString::new(second))
+    }
+}
+
+/// Mutable access to the coordinates of a validated path; see
+/// LineString::edit.
+#[derive(Debug, Clone)]
+pub struct LineStringEditor {
+    coords: Vec<Coordinate>,
+    id: Option<u64>,
+    degree: usize,
+}
+
+impl LineStringEditor {
+    pub fn coords(&self) -> &Vec<Coordinate> {
+        &self.coords
+    }
+
+    pub fn coords_mut(&mut self) -> &mut Vec<Coordinate> {
+        &mut self.coords
+    }
+
+    /// Rebuild the rtree and validate the edited path.  If it is invalid,
+    /// the editor is returned in the error, so the edit can be fixed.
+    pub fn commit(self) -> Result<LineString<Validated>, CommitError> {
+        let degree = self.degree;
+        let path = LineString {
+            coords: self.coords,
+            state: Raw,
+            id: self.id,
+        }
+        .prepare_with_degree(degree);
+        match path.check() {
+            Ok(signed_area) => Ok(path.into_validated(signed_area)),
+            Err(error) => {
+                let editor = LineStringEditor {
+                    coords: path.coords,
+                    id: path.id,
+                    degree,
+                };
+                Err(CommitError { editor, error })
+            }
+        }
+    }
+}
 
 impl<IP: Into<Coordinate>> TryFrom<Vec<IP>> for LineString<Validated> {
     type Error = ValidationError;
@@ -90,6 +475,12 @@ impl<IP: Into<Coordinate>> TryFrom<Vec<IP>> for LineString<Validated> {
     }
 }
 
+impl<IP: Into<Coordinate>> FromIterator<IP> for LineString<Raw> {
+    fn from_iter<I: IntoIterator<Item = IP>>(iter: I) -> Self {
+        LineString::new(iter.into_iter().map(|ip| ip.into()).collect())
+    }
+}
+
 fn check_intersection(
     index: usize,
     other_index: usize,
@@ -131,6 +522,19 @@ fn check_intersection(
                         position: isxn_start,
                     })
                 }
+            } else if (isxn_start == first_start || isxn_start == first_end)
+                && (isxn_start == second_start || isxn_start == second_end)
+            {
+                // The path passes through the same vertex twice.
+                let index = if isxn_start == second_start {
+                    second_index
+                } else {
+                    second_index + 1
+                };
+                Err(ValidationError::SelfTouch {
+                    index,
+                    position: isxn_start,
+                })
             } else {
                 Err(ValidationError::SelfIntersection {
                     first_index,
@@ -145,6 +549,14 @@ fn check_intersection(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::HasEnvelope;
+
+    #[test]
+    fn test_display() {
+        let path = LineString::new(Coordinate::vec_from(&[(0., 0.), (1.5, -2.)]));
+        assert_eq!(path.to_string(), "LINESTRING (0 0, 1.5 -2)");
+        assert_eq!(LineString::new(Vec::new()).to_string(), "LINESTRING EMPTY");
+    }
 
     #[test]
     fn test_empty_path() {
@@ -153,6 +565,305 @@ mod tests {
         assert_eq!(path.coords, Vec::new());
     }
 
+    #[test]
+    fn test_collect_path() {
+        let path: LineString<Raw> = (0..4).map(|i| (i as f64, (i * i) as f64)).collect();
+        assert_eq!(
+            path.coords(),
+            &Coordinate::vec_from(&[(0., 0.), (1., 1.), (2., 4.), (3., 9.)])
+        );
+    }
+
+    #[test]
+    fn test_segments_crossing() {
+        let path = LineString::new(Coordinate::vec_from(&[
+            (0., 0.),
+            (1., 2.),
+            (2., 0.),
+            (3., 2.),
+            (4., 1.),
+            (5., 1.),
+        ]))
+        .prepare();
+        let crossings = path.segments_crossing((0.5, 1.).into(), (4.5, 1.).into());
+        assert_eq!(
+            crossings,
+            vec![
+                (0, (0.5, 1.).into()),
+                (1, (1.5, 1.).into()),
+                (2, (2.5, 1.).into()),
+                (3, (4., 1.).into()),
+                (4, (4., 1.).into()),
+            ]
+        );
+        assert!(path
+            .segments_crossing((0., 3.).into(), (5., 3.).into())
+            .is_empty());
+
+        // Overlapping a segment running right to left
+        let path = LineString::new(Coordinate::vec_from(&[(3., 0.), (0., 0.)])).prepare();
+        assert_eq!(
+            path.segments_crossing((1., 0.).into(), (2., 0.).into()),
+            vec![(0, (2., 0.).into())]
+        );
+    }
+
+    #[test]
+    fn test_prepare_with_degree() {
+        let coords: Vec<Coordinate> = (0..40)
+            .map(|i| Coordinate::new(i as f64, (i % 3) as f64))
+            .collect();
+        let path = LineString::new(coords.clone())
+            .prepare_with_degree(4)
+            .validate()
+            .unwrap();
+        let default_path = LineString::new(coords).prepare().validate().unwrap();
+        assert_eq!(path.rtree().degree(), 4);
+        assert_eq!(default_path.rtree().degree(), LINE_STRING_DEFAULT_DEGREE);
+        let rect = Rectangle::new((5.5, 0.).into(), (20.5, 1.).into());
+        let mut results = path.rtree().query_rect(rect);
+        let mut default_results = default_path.rtree().query_rect(rect);
+        results.sort_unstable();
+        default_results.sort_unstable();
+        assert_eq!(results, default_results);
+    }
+
+    #[test]
+    fn test_envelope_only() {
+        let path = LineString::new(Coordinate::vec_from(&[(1., 2.), (-3., 5.), (0., -1.)]));
+        assert_eq!(path.envelope_only(), path.clone().prepare().envelope());
+        assert!(LineString::new(Vec::new()).envelope_only().is_empty());
+    }
+
+    #[test]
+    fn test_cached_envelope() {
+        let path = LineString::new(Coordinate::vec_from(&[(0., 0.), (4., 1.), (2., 3.)])).prepare();
+        let expected = Rectangle::new((0., 0.).into(), (4., 3.).into());
+        assert_eq!(path.envelope(), expected);
+        assert_eq!(path.envelope(), path.rtree().envelope());
+        let path = path.validate().unwrap();
+        assert_eq!(path.envelope(), expected);
+        assert_eq!(path.envelope(), path.rtree().envelope());
+    }
+
+    #[test]
+    fn test_id() {
+        let path = LineString::new(Coordinate::vec_from(&[(0., 0.), (1., 1.)]));
+        assert_eq!(path.id(), None);
+        let path = path.with_id(42);
+        assert_eq!(path.id(), Some(42));
+        let path = path.prepare();
+        assert_eq!(path.id(), Some(42));
+        let path = path.validate().unwrap();
+        assert_eq!(path.id(), Some(42));
+    }
+
+    #[test]
+    fn test_segments() {
+        let coords = Coordinate::vec_from(&[(0., 0.), (1., 0.), (1., 1.), (0., 1.)]);
+        let path = LineString::new(coords.clone());
+        let segments: Vec<_> = path.segments().collect();
+        assert_eq!(segments.len(), coords.len() - 1);
+        assert_eq!(segments[1], (coords[1], coords[2]));
+        assert_eq!(LineString::new(vec![coords[0]]).segments().count(), 0);
+        assert_eq!(LineString::new(Vec::new()).segments().count(), 0);
+    }
+
+    #[test]
+    fn test_slice() {
+        let coords = Coordinate::vec_from(&[(0., 0.), (1., 0.), (1., 1.), (0., 1.), (0., 2.)]);
+        let path = LineString::try_from(coords.clone()).unwrap();
+        let middle = path.slice(1..4);
+        assert_eq!(middle.coords(), &coords[1..4].to_vec());
+        assert_eq!(middle.length(), 2.);
+        assert_eq!(path.slice(0..5).coords(), &coords);
+        assert!(path.slice(2..2).coords().is_empty());
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let path = LineString::new(Coordinate::vec_from(&[(0., 0.), (4., 0.), (4., 2.)]));
+        assert_eq!(path.length(), 6.);
+        assert_eq!(path.interpolate(-1.), Some((0., 0.).into()));
+        assert_eq!(path.interpolate(5.), Some((4., 1.).into()));
+        assert_eq!(path.interpolate(7.), Some((4., 2.).into()));
+        assert_eq!(LineString::new(Vec::new()).interpolate(1.), None);
+    }
+
+    #[test]
+    fn test_length_at() {
+        let coords: Vec<Coordinate> = (0..50)
+            .map(|i| {
+                let i = i as f64;
+                Coordinate::new(i * 0.7, (i * 1.3).sin())
+            })
+            .collect();
+        let raw = LineString::new(coords.clone());
+        let prepared = LineString::new(coords).prepare_with_degree(4);
+        assert_eq!(prepared.length_at(0), 0.);
+        assert_eq!(prepared.length_at(49), raw.length());
+        assert!((1..50).all(|i| prepared.length_at(i) > prepared.length_at(i - 1)));
+
+        let length = raw.length();
+        for i in -2..=42 {
+            let distance = length * i as f64 / 40.;
+            assert_eq!(prepared.interpolate(distance), raw.interpolate(distance));
+        }
+        // At each vertex exactly
+        for i in 0..50 {
+            let distance = prepared.length_at(i);
+            assert_eq!(prepared.interpolate(distance), raw.interpolate(distance));
+        }
+        let empty = LineString::new(Vec::new()).prepare();
+        assert_eq!(empty.interpolate(1.), None);
+    }
+
+    #[test]
+    fn test_close() {
+        let path = LineString::new(Coordinate::vec_from(&[(0., 0.), (1., 0.), (1., 1.)])).close();
+        assert_eq!(
+            path.coords(),
+            &Coordinate::vec_from(&[(0., 0.), (1., 0.), (1., 1.), (0., 0.)])
+        );
+        assert!(path.into_ring().is_ok());
+
+        let closed = Coordinate::vec_from(&[(0., 0.), (1., 0.), (1., 1.), (0., 0.)]);
+        assert_eq!(LineString::new(closed.clone()).close().coords(), &closed);
+        let short = Coordinate::vec_from(&[(0., 0.), (1., 0.)]);
+        assert_eq!(LineString::new(short.clone()).close().coords(), &short);
+    }
+
+    #[test]
+    fn test_map_coords() {
+        let path = LineString::new(Coordinate::vec_from(&[(0.2, 0.7), (1.6, -2.4)])).with_id(3);
+        let rounded = path.map_coords(|c| Coordinate::new(c.x.round(), c.y.round()));
+        assert_eq!(
+            rounded.coords(),
+            &Coordinate::vec_from(&[(0., 1.), (2., -2.)])
+        );
+        assert_eq!(rounded.id(), Some(3));
+    }
+
+    #[test]
+    fn test_self_intersection_points() {
+        // A figure eight, crossing itself at (1, 1)
+        let path = LineString::new(Coordinate::vec_from(&[
+            (0., 0.),
+            (2., 2.),
+            (2., 0.),
+            (0., 2.),
+            (0., 0.),
+        ]))
+        .prepare();
+        assert_eq!(
+            path.self_intersection_points(),
+            vec![(0, 2, (1., 1.).into())]
+        );
+
+        // Doubling back overlaps the previous segment.
+        let path = LineString::new(Coordinate::vec_from(&[(0., 0.), (2., 0.), (1., 0.)])).prepare();
+        assert_eq!(
+            path.self_intersection_points(),
+            vec![(0, 1, (1., 0.).into())]
+        );
+
+        let path = LineString::new(Coordinate::vec_from(&[(0., 0.), (1., 0.), (1., 1.)])).prepare();
+        assert!(path.self_intersection_points().is_empty());
+    }
+
+    #[test]
+    fn test_longest_straight_run() {
+        let path = LineString::new(Coordinate::vec_from(&[
+            (0., 0.),
+            (1., 1.),
+            (2., 0.),
+            (3., 0.),
+            (4., 0.001),
+            (5., 0.),
+            (6., 0.),
+            (7., 2.),
+            (8., 0.),
+        ]));
+        assert_eq!(path.longest_straight_run(0.01), 2..7);
+        assert_eq!(path.longest_straight_run(0.), 0..2);
+        assert_eq!(LineString::new(Vec::new()).longest_straight_run(1.), 0..0);
+    }
+
+    #[test]
+    fn test_split_at() {
+        let path = LineString::try_from(vec![(0., 0.), (4., 0.), (4., 2.)]).unwrap();
+        let (first, second) = path.split_at(1.);
+        assert_eq!(first.coords(), &Coordinate::vec_from(&[(0., 0.), (1., 0.)]));
+        assert_eq!(
+            second.coords(),
+            &Coordinate::vec_from(&[(1., 0.), (4., 0.), (4., 2.)])
+        );
+
+        // At a vertex
+        let (first, second) = path.split_at(4.);
+        assert_eq!(first.coords(), &Coordinate::vec_from(&[(0., 0.), (4., 0.)]));
+        assert_eq!(
+            second.coords(),
+            &Coordinate::vec_from(&[(4., 0.), (4., 2.)])
+        );
+
+        for &distance in &[0., 6., 10.] {
+            let (first, second) = path.split_at(distance);
+            assert_eq!(first.coords(), path.coords());
+            assert!(second.coords().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_edit() {
+        let path = LineString::try_from(vec![(0., 0.), (4., 0.), (4., 2.), (2., 2.)])
+            .unwrap()
+            .with_id(7);
+        let mut editor = path.edit();
+        editor.coords_mut()[3] = Coordinate::new(2., 1.);
+        let path = editor.commit().unwrap();
+        assert_eq!(
+            path.coords(),
+            &Coordinate::vec_from(&[(0., 0.), (4., 0.), (4., 2.), (2., 1.)])
+        );
+        assert_eq!(path.id(), Some(7));
+        assert_eq!(path.rtree().envelope(), Rectangle::of(path.coords()));
+        assert_eq!(path.length(), 6. + 5_f64.sqrt());
+
+        // Dragging the last vertex across the first segment
+        let mut editor = path.edit();
+        editor.coords_mut()[3] = Coordinate::new(2., -1.);
+        let CommitError { mut editor, error } = editor.commit().unwrap_err();
+        assert!(matches!(
+            error,
+            ValidationError::SelfIntersection {
+                first_index: 0,
+                second_index: 2,
+                ..
+            }
+        ));
+        // The failed edit is kept, and can be fixed.
+        assert_eq!(editor.coords()[3], Coordinate::new(2., -1.));
+        editor.coords_mut()[3] = Coordinate::new(2., 3.);
+        let path = editor.commit().unwrap();
+        assert_eq!(
+            path.coords(),
+            &Coordinate::vec_from(&[(0., 0.), (4., 0.), (4., 2.), (2., 3.)])
+        );
+        assert_eq!(path.id(), Some(7));
+
+        // The error converts to a ValidationError, eg with ?.
+        let drag = |path: LineString<Validated>, to: Coordinate| {
+            let mut editor = path.edit();
+            editor.coords_mut()[3] = to;
+            Ok::<_, ValidationError>(editor.commit()?)
+        };
+        assert!(matches!(
+            drag(path, Coordinate::new(2., -1.)),
+            Err(ValidationError::SelfIntersection { .. })
+        ));
+    }
+
     fn assert_path_ok(coords: Vec<(f64, f64)>) {
         let positions: Vec<Coordinate> = coords.clone().into_iter().map(|c| c.into()).collect();
         let path = LineString::try_from(coords).expect("Construction should not fail");
@@ -172,6 +883,50 @@ mod tests {
         assert_eq!(err, expected);
     }
 
+    #[test]
+    fn test_validate_within() {
+        let bounds = Rectangle::new((-180., -90.).into(), (180., 90.).into());
+        let path = LineString::new(Coordinate::vec_from(&[(-122., 37.), (2., 48.)]));
+        assert!(path.validate_within(bounds).is_ok());
+
+        // Latitude and longitude swapped
+        let path = LineString::new(Coordinate::vec_from(&[(37., -122.), (48., 2.)]));
+        assert_eq!(
+            path.validate_within(bounds).unwrap_err(),
+            ValidationError::OutOfBounds {
+                index: 0,
+                position: (37., -122.).into(),
+            }
+        );
+
+        // Normal validation still applies
+        let path = LineString::new(Coordinate::vec_from(&[(0., 0.)]));
+        assert_eq!(
+            path.validate_within(bounds).unwrap_err(),
+            ValidationError::SinglePathCoordinate
+        );
+    }
+
+    #[test]
+    fn test_self_touch() {
+        // A figure-eight ring, pinched at (1, 1)
+        assert_invalid_path(
+            vec![
+                (0., 0.),
+                (1., 1.),
+                (2., 0.),
+                (2., 2.),
+                (1., 1.),
+                (0., 2.),
+                (0., 0.),
+            ],
+            ValidationError::SelfTouch {
+                index: 4,
+                position: (1., 1.).into(),
+            },
+        );
+    }
+
     #[test]
     fn test_basic_paths() {
         assert_path_ok(vec![(0., 0.), (1., 1.)]);
@@ -179,6 +934,22 @@ mod tests {
         assert_path_ok(vec![(0., 0.), (1., 0.), (0., 1.), (0., 0.)]);
     }
 
+    #[test]
+    fn test_overlap_segment() {
+        // Doubling back from (0, 1) along the first segment
+        let err = LineString::try_from(vec![(0., 0.), (0., 1.), (0., 0.5)]).unwrap_err();
+        assert_eq!(
+            err.overlap_segment(),
+            Some(((0., 0.5).into(), (0., 1.).into()))
+        );
+        let err = LineString::try_from(vec![(2., 1.), (0., 0.), (1., 0.5)]).unwrap_err();
+        assert_eq!(
+            err.overlap_segment(),
+            Some(((0., 0.).into(), (1., 0.5).into()))
+        );
+        assert_eq!(ValidationError::NotARing.overlap_segment(), None);
+    }
+
     #[test]
     fn test_invalid_paths() {
         assert_invalid_path(vec![(0., 0.)], ValidationError::SinglePathCoordinate);
@@ -195,10 +966,17 @@ mod tests {
             ValidationError::OverlappingSegments {
                 first_index: 0,
                 second_index: 1,
-                // start: (0.0, 1.0).into(),
-                // end: (0.0, 0.5).into(),
-                end: (0.0, 1.0).into(),
                 start: (0.0, 0.5).into(),
+                end: (0.0, 1.0).into(),
+            },
+        );
+        assert_invalid_path(
+            vec![(0., 1.), (0., 0.), (0., 0.5)],
+            ValidationError::OverlappingSegments {
+                first_index: 0,
+                second_index: 1,
+                start: (0.0, 0.0).into(),
+                end: (0.0, 0.5).into(),
             },
         );
         assert_invalid_path(
@@ -218,9 +996,8 @@ mod tests {
                 (1., 0.),
                 (0.5, 0.5),
             ],
-            ValidationError::SelfIntersection {
-                first_index: 2,
-                second_index: 4,
+            ValidationError::SelfTouch {
+                index: 5,
                 position: (0.5, 0.5).into(),
             },
         );
@@ -1,6 +1,7 @@
 use crate::errors::ValidationError;
 use crate::geometry_state::{HasRTree, Prepared, Raw, Validated};
-use crate::{Coordinate, LineString, SegRTree};
+use crate::line_string::segments_crossing;
+use crate::{Coordinate, LineString, Rectangle, SegRTree};
 use std::convert::TryFrom;
 
 #[derive(Debug, Clone)]
@@ -20,6 +21,19 @@ impl<S> LineString<S> {
         }
     }
 
+    /// The first and last coordinates and the distance between them, if
+    /// they differ, eg to decide whether to snap a nearly-closed ring shut.
+    /// Returns None for a closed or empty path.
+    pub fn closure_gap(&self) -> Option<(Coordinate, Coordinate, f64)> {
+        let first = *self.coords().first()?;
+        let last = *self.coords().last()?;
+        if first == last {
+            None
+        } else {
+            Some((first, last, first.distance(last)))
+        }
+    }
+
     pub fn is_ring(&self) -> bool {
         self.validate_ring().is_ok()
     }
@@ -37,25 +51,99 @@ impl<S> LinearRing<S> {
     pub fn coords(&self) -> &Vec<Coordinate> {
         &self.coords
     }
+
+    /// Whether the rings have the same coordinates, up to which vertex they
+    /// start from.  The direction must match: a reversed ring is not equal.
+    pub fn equals_rotation(&self, other: &LinearRing<S>) -> bool {
+        if self.coords.len() != other.coords.len() {
+            return false;
+        }
+        if self.coords.is_empty() {
+            return true;
+        }
+        // Drop the closing coordinates, so the vertices wrap around.
+        let a = &self.coords[..self.coords.len() - 1];
+        let b = &other.coords[..other.coords.len() - 1];
+        let n = a.len();
+        (0..n).any(|offset| (0..n).all(|i| a[(i + offset) % n] == b[i]))
+    }
+
+    /// The segments of the ring, as (start, end) pairs.
+    pub fn segments(&self) -> impl Iterator<Item = (Coordinate, Coordinate)> + '_ {
+        self.coords.windows(2).map(|w| (w[0], w[1]))
+    }
+
+    pub(crate) fn to_raw(&self) -> LinearRing<Raw> {
+        LinearRing {
+            coords: self.coords.clone(),
+            state: Raw {},
+        }
+    }
+}
+
+impl<S: HasRTree> LinearRing<S> {
+    /// The segments crossing the segment from a to b, as segment indices and
+    /// crossing points, ordered by index.
+    pub fn segments_crossing(&self, a: Coordinate, b: Coordinate) -> Vec<(usize, Coordinate)> {
+        segments_crossing(&self.coords, self.rtree(), a, b)
+    }
 }
 
 impl<S: HasRTree> HasRTree for LinearRing<S> {
     fn rtree(&self) -> &SegRTree {
         self.state.rtree()
     }
+
+    fn rtree_envelope(&self) -> Rectangle {
+        self.state.rtree_envelope()
+    }
+
+    fn cumulative_lengths(&self) -> &[f64] {
+        self.state.cumulative_lengths()
+    }
 }
 
 impl LinearRing<Raw> {
+    /// Apply f to every vertex.  The closing coordinate is a copy of the
+    /// mapped first vertex rather than mapped itself, so the ring stays
+    /// closed even if f is stateful (eg adds jitter).
+    pub fn map_coords<F: FnMut(Coordinate) -> Coordinate>(self, f: F) -> LinearRing<Raw> {
+        let num_vertices = self.coords.len().saturating_sub(1).max(1);
+        let closed = self.coords.len() > 1;
+        let mut coords: Vec<Coordinate> =
+            self.coords.into_iter().take(num_vertices).map(f).collect();
+        if closed {
+            coords.push(coords[0]);
+        }
+        LinearRing {
+            coords,
+            state: self.state,
+        }
+    }
+
     pub fn prepare(self) -> LinearRing<Prepared> {
         LineString {
             coords: self.coords,
             state: self.state,
+            id: None,
         }
         .prepare()
         .into_ring()
         .unwrap()
     }
 
+    /// Like prepare, but build the rtree with the given degree (at least 2).
+    pub fn prepare_with_degree(self, degree: usize) -> LinearRing<Prepared> {
+        LineString {
+            coords: self.coords,
+            state: self.state,
+            id: None,
+        }
+        .prepare_with_degree(degree)
+        .into_ring()
+        .unwrap()
+    }
+
     pub fn validate(self) -> Result<LinearRing<Validated>, ValidationError> {
         self.prepare().validate()
     }
@@ -66,6 +154,7 @@ impl LinearRing<Prepared> {
         Ok(LineString {
             coords: self.coords,
             state: self.state,
+            id: None,
         }
         .validate()?
         .into_ring()
@@ -73,6 +162,28 @@ impl LinearRing<Prepared> {
     }
 }
 
+impl LinearRing<Validated> {
+    /// A ring with no coordinates.
+    pub(crate) fn empty() -> Self {
+        LinearRing {
+            coords: Vec::new(),
+            state: Validated::new(SegRTree::new_empty()),
+        }
+    }
+
+    /// The signed area of the ring, positive if counter-clockwise.  This is
+    /// computed during validation, so it is O(1).
+    pub fn signed_area(&self) -> f64 {
+        self.state.signed_area
+    }
+
+    /// Whether the ring is convex, allowing collinear vertices.  This is
+    /// found during validation, so it is O(1).  An empty ring is not convex.
+    pub fn is_convex(&self) -> bool {
+        self.state.is_convex
+    }
+}
+
 impl<IP: Into<Coordinate>> TryFrom<Vec<IP>> for LinearRing<Validated> {
     type Error = ValidationError;
 
@@ -83,3 +194,84 @@ impl<IP: Into<Coordinate>> TryFrom<Vec<IP>> for LinearRing<Validated> {
             .into_ring()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::signed_area;
+
+    #[test]
+    fn test_equals_rotation() {
+        let corners = [(0., 0.), (1., 0.), (1., 1.), (0., 1.)];
+        let make_ring = |start: usize| {
+            let mut coords: Vec<(f64, f64)> = (0..5).map(|i| corners[(start + i) % 4]).collect();
+            coords[4] = coords[0];
+            LinearRing::try_from(coords).unwrap()
+        };
+        let square = make_ring(0);
+        for start in 0..4 {
+            assert!(square.equals_rotation(&make_ring(start)));
+            assert!(make_ring(start).equals_rotation(&square));
+        }
+
+        let reversed =
+            LinearRing::try_from(vec![(0., 0.), (0., 1.), (1., 1.), (1., 0.), (0., 0.)]).unwrap();
+        assert!(!square.equals_rotation(&reversed));
+        let other =
+            LinearRing::try_from(vec![(0., 0.), (2., 0.), (1., 1.), (0., 1.), (0., 0.)]).unwrap();
+        assert!(!square.equals_rotation(&other));
+    }
+
+    #[test]
+    fn test_map_coords_stateful() {
+        let ring = LinearRing::try_from(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)])
+            .unwrap()
+            .to_raw();
+        // Jitter that differs on every call, so the closing coordinate would
+        // not match the first if it were mapped too.
+        let mut calls = 0.;
+        let jittered = ring.map_coords(|c| {
+            calls += 1.;
+            c + Coordinate::new(calls * 1e-3, 0.)
+        });
+        assert_eq!(calls, 4.);
+        assert_eq!(jittered.coords().len(), 5);
+        assert_eq!(jittered.coords().first(), jittered.coords().last());
+        assert_eq!(jittered.coords()[1], Coordinate::new(4.002, 0.));
+        assert_eq!(jittered.prepare().coords().len(), 5);
+
+        assert!(LinearRing::empty()
+            .to_raw()
+            .map_coords(|c| c)
+            .coords()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_closure_gap() {
+        let mut coords =
+            Coordinate::vec_from(&[(0., 0.), (4., 0.), (4., 3.), (0., 3.), (0., 1e-3)]);
+        let path = LineString::new(coords.clone());
+        assert_eq!(path.validate_ring(), Err(ValidationError::NotClosed));
+        assert_eq!(path.closure_gap(), Some((coords[0], coords[4], 1e-3)));
+
+        coords[4] = coords[0];
+        assert_eq!(LineString::new(coords).closure_gap(), None);
+        assert_eq!(LineString::new(Vec::new()).closure_gap(), None);
+    }
+
+    #[test]
+    fn test_signed_area() {
+        let ccw = vec![(0., 0.), (3., 0.), (3., 1.), (1., 2.), (0., 1.), (0., 0.)];
+        let ring = LinearRing::try_from(ccw.clone()).unwrap();
+        assert_eq!(ring.signed_area(), signed_area(ring.coords()));
+        assert_eq!(ring.signed_area(), 4.5);
+
+        let cw: Vec<(f64, f64)> = ccw.into_iter().rev().collect();
+        let ring = LinearRing::try_from(cw).unwrap();
+        assert_eq!(ring.signed_area(), signed_area(ring.coords()));
+        assert_eq!(ring.signed_area(), -4.5);
+
+        assert_eq!(LinearRing::empty().signed_area(), 0.);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{Coordinate, Flatbush, HasEnvelope, Rectangle};
+
+pub const MULTI_POINT_DEFAULT_DEGREE: usize = 16;
+
+/// A collection of points, indexed for spatial queries.
+#[derive(Debug)]
+pub struct MultiPoint {
+    coords: Vec<Coordinate>,
+    index: Flatbush,
+}
+
+impl HasEnvelope for MultiPoint {
+    fn envelope(&self) -> Rectangle {
+        self.index.envelope()
+    }
+}
+
+impl MultiPoint {
+    pub fn new(coords: Vec<Coordinate>) -> Self {
+        let envelopes: Vec<Rectangle> = coords.iter().map(|c| c.envelope()).collect();
+        let index = Flatbush::new(MULTI_POINT_DEFAULT_DEGREE, &envelopes);
+        MultiPoint { coords, index }
+    }
+
+    pub fn coords(&self) -> &Vec<Coordinate> {
+        &self.coords
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.coords.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.coords.len()
+    }
+
+    /// Indices of the (up to) k points nearest to point, closest first.
+    pub fn nearest(&self, point: Coordinate, k: usize) -> Vec<usize> {
+        self.index.query_nearest(point, k)
+    }
+
+    /// Indices of the points within rect, in no particular order.
+    pub fn within(&self, rect: Rectangle) -> Vec<usize> {
+        self.index.query_rect(rect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_points() -> MultiPoint {
+        MultiPoint::new(Coordinate::vec_from(&[
+            (5., 5.),
+            (0., 0.),
+            (9., 1.),
+            (2., 8.),
+            (4., 6.),
+            (7., 7.),
+            (1., 3.),
+            (6., 2.),
+            (3., 3.),
+            (8., 9.),
+        ]))
+    }
+
+    #[test]
+    fn test_nearest() {
+        let points = get_points();
+        assert_eq!(points.nearest((4.1, 4.).into(), 3), vec![0, 8, 4]);
+        assert_eq!(points.nearest((4., 4.).into(), 0), Vec::<usize>::new());
+        assert_eq!(points.nearest((4., 4.).into(), 20).len(), 10);
+    }
+
+    #[test]
+    fn test_within() {
+        let points = get_points();
+        let mut results = points.within(Rectangle::new((0., 0.).into(), (4., 4.).into()));
+        results.sort_unstable();
+        assert_eq!(results, vec![1, 6, 8]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let points = MultiPoint::new(Vec::new());
+        assert!(points.is_empty());
+        assert!(points.envelope().is_empty());
+        assert_eq!(points.nearest((0., 0.).into(), 3), Vec::<usize>::new());
+        let rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        assert_eq!(points.within(rect), Vec::<usize>::new());
+    }
+}
@@ -1,12 +1,33 @@
-use crate::algorithms::validate_polygon;
+use crate::algorithms::point_in_polygon::ContainRelation;
+use crate::algorithms::{clip_polygon, convex_hull, point_in_polygon, validate_polygon};
 use crate::errors::ValidationError;
 use crate::geometry_state::{HasRTree, Prepared, Raw, Validated};
+use crate::line_string::fmt_wkt_coords;
+use crate::utils::{intersect_segments, signed_area};
 use crate::LinearRing;
-use crate::{HasEnvelope, Rectangle};
+use crate::{Coordinate, Flatbush, HasEnvelope, Rectangle};
+use std::convert::TryFrom;
+use std::fmt;
 
+const HOLE_INDEX_DEGREE: usize = 16;
+
+#[derive(Debug, Clone)]
 pub struct Polygon<S> {
     shell: LinearRing<S>,
     holes: Vec<LinearRing<S>>,
+    // An index of the hole envelopes, built for Validated polygons with holes.
+    hole_index: Option<Flatbush>,
+}
+
+/// Summary sizes of a polygon, eg for choosing a level of detail.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolygonStats {
+    /// The number of segments, over the shell and holes.
+    pub num_segments: usize,
+    pub num_holes: usize,
+    pub shell_envelope: Rectangle,
+    /// The sum of the areas of the shell's and holes' envelopes.
+    pub total_envelope_area: f64,
 }
 
 impl<S> Polygon<S> {
@@ -17,6 +38,90 @@ impl<S> Polygon<S> {
     pub fn holes(&self) -> &[LinearRing<S>] {
         &self.holes
     }
+
+    /// The coordinates of the shell, including the closing coordinate.
+    pub fn exterior_coords(&self) -> &[Coordinate] {
+        self.shell.coords()
+    }
+
+    /// The coordinates of each hole, including their closing coordinates.
+    pub fn interior_coords(&self) -> Vec<&[Coordinate]> {
+        self.holes.iter().map(|hole| &hole.coords()[..]).collect()
+    }
+
+    /// A polygon with the same shell, but no holes.
+    pub fn without_holes(&self) -> Polygon<Raw> {
+        Polygon::<Raw>::new(self.shell.to_raw(), Vec::new())
+    }
+
+    pub fn stats(&self) -> PolygonStats {
+        let rings = || std::iter::once(&self.shell).chain(&self.holes);
+        let envelope_area = |ring: &LinearRing<S>| {
+            let envelope = Rectangle::of(ring.coords());
+            if envelope.is_empty() {
+                0.
+            } else {
+                envelope.area()
+            }
+        };
+        PolygonStats {
+            num_segments: rings()
+                .map(|ring| ring.coords().len().saturating_sub(1))
+                .sum(),
+            num_holes: self.holes.len(),
+            shell_envelope: Rectangle::of(self.shell.coords()),
+            total_envelope_area: rings().map(envelope_area).sum(),
+        }
+    }
+
+    /// The area of the shell, less the area of the holes.
+    pub fn area(&self) -> f64 {
+        let hole_area: f64 = self
+            .holes
+            .iter()
+            .map(|hole| signed_area(hole.coords()).abs())
+            .sum();
+        signed_area(self.shell.coords()).abs() - hole_area
+    }
+
+    pub(crate) fn to_raw(&self) -> Polygon<Raw> {
+        Polygon {
+            shell: self.shell.to_raw(),
+            holes: self.holes.iter().map(|hole| hole.to_raw()).collect(),
+            hole_index: None,
+        }
+    }
+}
+
+/// Polygons are equal if their shells and holes have the same coordinates.
+/// This is order-sensitive: the same holes in a different order, or rings
+/// with different start points, are unequal.
+impl<S> PartialEq for Polygon<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.shell.coords() == other.shell.coords()
+            && self.holes.len() == other.holes.len()
+            && self
+                .holes
+                .iter()
+                .zip(&other.holes)
+                .all(|(hole, other_hole)| hole.coords() == other_hole.coords())
+    }
+}
+
+/// Formats as WKT, eg `POLYGON ((0 0, 1 0, 1 1, 0 0))`.
+impl<S> fmt::Display for Polygon<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.shell.coords().is_empty() {
+            return write!(f, "POLYGON EMPTY");
+        }
+        write!(f, "POLYGON (")?;
+        fmt_wkt_coords(f, self.shell.coords())?;
+        for hole in &self.holes {
+            write!(f, ", ")?;
+            fmt_wkt_coords(f, hole.coords())?;
+        }
+        write!(f, ")")
+    }
 }
 
 impl<S: HasRTree> HasEnvelope for Polygon<S> {
@@ -27,19 +132,42 @@ impl<S: HasRTree> HasEnvelope for Polygon<S> {
 
 impl Polygon<Raw> {
     pub fn new(shell: LinearRing<Raw>, holes: Vec<LinearRing<Raw>>) -> Self {
-        Polygon { shell, holes }
+        Polygon {
+            shell,
+            holes,
+            hole_index: None,
+        }
+    }
+
+    /// Apply f to every coordinate of the shell and holes.
+    pub fn map_coords<F: FnMut(Coordinate) -> Coordinate>(self, mut f: F) -> Polygon<Raw> {
+        let shell = self.shell.map_coords(&mut f);
+        let holes = self
+            .holes
+            .into_iter()
+            .map(|hole| hole.map_coords(&mut f))
+            .collect();
+        Polygon::<Raw>::new(shell, holes)
     }
 
     pub fn prepare(self) -> Polygon<Prepared> {
         let shell = self.shell.prepare();
         let holes = self.holes.into_iter().map(|hole| hole.prepare()).collect();
-        Polygon { shell, holes }
+        Polygon {
+            shell,
+            holes,
+            hole_index: None,
+        }
     }
 }
 
 impl Polygon<Prepared> {
     pub fn new(shell: LinearRing<Prepared>, holes: Vec<LinearRing<Prepared>>) -> Self {
-        Polygon { shell, holes }
+        Polygon {
+            shell,
+            holes,
+            hole_index: None,
+        }
     }
 
     pub fn validate(self) -> Result<Polygon<Validated>, ValidationError> {
@@ -52,11 +180,689 @@ impl Polygon<Prepared> {
 }
 
 impl Polygon<Validated> {
+    /// A polygon with no rings, containing no points.
+    pub fn empty() -> Self {
+        Polygon {
+            shell: LinearRing::empty(),
+            holes: Vec::new(),
+            hole_index: None,
+        }
+    }
+
+    /// The distance from the point to the polygon, which is 0 if the point
+    /// is in the interior or on the boundary.  Returns infinity for an empty
+    /// polygon.
+    pub fn distance(&self, point: Coordinate) -> f64 {
+        if point_in_polygon(point, self) != ContainRelation::Exterior {
+            return 0.;
+        }
+        self.boundary_distance(point)
+    }
+
+    /// The distance from the point to the boundary, negative if the point
+    /// is in the interior, eg for a signed distance field.  Returns infinity
+    /// for an empty polygon.
+    pub fn signed_distance(&self, point: Coordinate) -> f64 {
+        match point_in_polygon(point, self) {
+            ContainRelation::Interior => -self.boundary_distance(point),
+            ContainRelation::Boundary => 0.,
+            ContainRelation::Exterior => self.boundary_distance(point),
+        }
+    }
+
+    fn boundary_distance(&self, point: Coordinate) -> f64 {
+        std::iter::once(&self.shell)
+            .chain(&self.holes)
+            .map(|ring| ring.rtree().distance_to_segments(point, ring.coords()))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Whether the polygon is convex: it has no holes, and its shell turns
+    /// the same way at every vertex.  Collinear vertices are allowed, but an
+    /// empty polygon is not convex.
+    pub fn is_convex(&self) -> bool {
+        self.holes.is_empty() && self.shell.is_convex()
+    }
+
+    /// The convex hull of the shell, as a counter-clockwise ring.  Holes
+    /// are inside the shell, so they don't affect the hull.  Returns an
+    /// empty ring for an empty polygon.
+    pub fn convex_hull(&self) -> LinearRing<Validated> {
+        let hull = convex_hull(self.shell.coords());
+        if hull.len() < 4 {
+            // Only an empty shell has no area.
+            return LinearRing::empty();
+        }
+        LinearRing::try_from(hull).expect("A convex hull is a valid ring")
+    }
+
+    /// A point guaranteed to be in the interior, eg for placing a label.
+    ///
+    /// This scans a horizontal line near the centroid, and returns the
+    /// midpoint of the widest interior span along it.  The line avoids
+    /// vertices, so every crossing is proper.  Returns NaN for an empty
+    /// polygon.
+    pub fn representative_point(&self) -> Coordinate {
+        let rings = || std::iter::once(&self.shell).chain(&self.holes);
+        let mut ys: Vec<f64> = rings()
+            .flat_map(|ring| ring.coords().iter().map(|c| c.y))
+            .collect();
+        if ys.is_empty() {
+            return Coordinate::new(f64::NAN, f64::NAN);
+        }
+        ys.sort_by(|a, b| a.total_cmp(b));
+        ys.dedup();
+
+        // Scan midway between the vertex heights around the centroid.
+        let centroid_y = self.centroid_y();
+        let upper = ys
+            .iter()
+            .position(|&y| y > centroid_y)
+            .unwrap_or(ys.len() - 1)
+            .max(1);
+        let scan_y = (ys[upper - 1] + ys[upper]) / 2.;
+
+        let envelope = self.envelope();
+        let left = Coordinate::new(envelope.x_min, scan_y);
+        let right = Coordinate::new(envelope.x_max, scan_y);
+        let mut xs: Vec<f64> = rings()
+            .flat_map(|ring| ring.segments_crossing(left, right))
+            .map(|(_, crossing)| crossing.x)
+            .collect();
+        xs.sort_by(|a, b| a.total_cmp(b));
+
+        let (start, end) = xs
+            .chunks_exact(2)
+            .map(|span| (span[0], span[1]))
+            .max_by(|a, b| (a.1 - a.0).total_cmp(&(b.1 - b.0)))
+            .unwrap();
+        Coordinate::new((start + end) / 2., scan_y)
+    }
+
+    /// The y of the centroid of the polygon's area.
+    fn centroid_y(&self) -> f64 {
+        let mut moment = 0.;
+        let mut area = 0.;
+        for ring in std::iter::once(&self.shell).chain(&self.holes) {
+            // Holes are subtracted, whatever their orientation.
+            let sign = if std::ptr::eq(ring, &self.shell) {
+                1.
+            } else {
+                -1.
+            };
+            let ring_area = signed_area(ring.coords());
+            let ring_moment: f64 = ring
+                .segments()
+                .map(|(start, end)| start.cross(end) * (start.y + end.y))
+                .sum::<f64>()
+                / 6.;
+            let orientation = ring_area.signum();
+            moment += sign * orientation * ring_moment;
+            area += sign * ring_area.abs();
+        }
+        moment / area
+    }
+
+    /// Whether the point is in the interior of the polygon.
+    pub fn contains(&self, point: Coordinate) -> bool {
+        point_in_polygon(point, self) == ContainRelation::Interior
+    }
+
     pub fn try_new(
         shell: LinearRing<Validated>,
         holes: Vec<LinearRing<Validated>>,
     ) -> Result<Self, ValidationError> {
         validate_polygon(&shell, &holes)?;
-        Ok(Polygon { shell, holes })
+        let hole_index = if holes.is_empty() {
+            None
+        } else {
+            let envelopes: Vec<Rectangle> = holes.iter().map(|hole| hole.envelope()).collect();
+            Some(Flatbush::new(HOLE_INDEX_DEGREE, &envelopes))
+        };
+        Ok(Polygon {
+            shell,
+            holes,
+            hole_index,
+        })
+    }
+
+    /// The points where the shells of self and other meet, in lexicographic
+    /// order (by x, then y) without duplicates.  Where the shells share an
+    /// edge, the ends of the shared part are returned.
+    pub fn boundary_intersections(&self, other: &Polygon<Validated>) -> Vec<Coordinate> {
+        let coords = self.shell.coords();
+        let other_coords = other.shell.coords();
+        let mut points: Vec<Coordinate> = self
+            .shell
+            .rtree()
+            .query_other_intersections(other.shell.rtree())
+            .into_iter()
+            .filter_map(|(i, j)| {
+                intersect_segments(
+                    coords[i],
+                    coords[i + 1],
+                    other_coords[j],
+                    other_coords[j + 1],
+                )
+            })
+            .flat_map(|(start, end)| [start, end])
+            .collect();
+        points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        points.dedup();
+        points
+    }
+
+    /// Apply f to every coordinate, eg to change projection, and validate
+    /// the result.  Reprojection can make a valid polygon invalid (eg near
+    /// the poles or antimeridian), which gives the validation error.
+    pub fn reproject<F: Fn(Coordinate) -> Coordinate>(
+        &self,
+        f: F,
+    ) -> Result<Polygon<Validated>, ValidationError> {
+        if self.shell.coords().is_empty() {
+            return Ok(Polygon::empty());
+        }
+        let holes = self.holes.iter().map(|hole| hole.to_raw()).collect();
+        let reprojected = Polygon::<Raw>::new(self.shell.to_raw(), holes).map_coords(f);
+        // A non-finite closing coordinate would leave a ring that can't be
+        // prepared, so check before preparing.
+        let is_finite = std::iter::once(&reprojected.shell)
+            .chain(&reprojected.holes)
+            .all(|ring| ring.coords().iter().all(Coordinate::is_finite));
+        if !is_finite {
+            return Err(ValidationError::NonFiniteCoordinate);
+        }
+        reprojected.prepare().validate()
+    }
+
+    /// Build and validate the rings and the polygon, from the shape of
+    /// from_wkt::Polygon.
+    pub fn try_from_coords(
+        shell: Vec<Coordinate>,
+        holes: Vec<Vec<Coordinate>>,
+    ) -> Result<Self, ValidationError> {
+        let shell = LinearRing::try_from(shell)?;
+        let holes = holes
+            .into_iter()
+            .map(LinearRing::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Polygon::try_new(shell, holes)
+    }
+
+    /// The indices of the holes whose envelopes contain the point, ascending.
+    pub(crate) fn candidate_holes(&self, point: Coordinate) -> Vec<usize> {
+        match &self.hole_index {
+            Some(index) => {
+                let mut candidates = index.query_rect(Rectangle::new(point, point));
+                candidates.sort_unstable();
+                candidates
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Clip the polygon by intersecting with a rectangle, returning the
+    /// resulting polygons.  Degenerate results (eg, where the polygon only
+    /// touches the rectangle) are dropped.
+    #[allow(clippy::float_cmp)]
+    pub fn clip(&self, rect: Rectangle) -> Vec<Polygon<Validated>> {
+        if rect.contains(self.envelope()) {
+            return vec![self.clone()];
+        }
+        clip_polygon(rect, self)
+            .into_iter()
+            .filter(|poly| signed_area(poly.shell().coords()) != 0.)
+            .filter_map(|poly| poly.prepare().validate().ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_without_holes() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]).unwrap();
+        let hole = LinearRing::try_from(vec![(1., 1.), (1., 2.), (2., 2.), (1., 1.)]).unwrap();
+        let polygon = Polygon::try_new(shell, vec![hole]).unwrap();
+        let outline = polygon.without_holes();
+        assert!(outline.holes().is_empty());
+        assert_eq!(outline.exterior_coords(), polygon.exterior_coords());
+        assert_eq!(outline.area(), 16.);
+        assert_eq!(polygon.area(), 15.5);
+    }
+
+    #[test]
+    fn test_stats() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (10., 0.), (10., 10.), (0., 10.), (0., 0.)])
+                .unwrap();
+        let holes = vec![
+            LinearRing::try_from(vec![(1., 1.), (1., 3.), (3., 3.), (3., 1.), (1., 1.)]).unwrap(),
+            LinearRing::try_from(vec![(5., 5.), (5., 6.), (8., 5.), (5., 5.)]).unwrap(),
+        ];
+        let polygon = Polygon::try_new(shell, holes).unwrap();
+        assert_eq!(
+            polygon.stats(),
+            PolygonStats {
+                num_segments: 11,
+                num_holes: 2,
+                shell_envelope: Rectangle::new((0., 0.).into(), (10., 10.).into()),
+                total_envelope_area: 100. + 4. + 3.,
+            }
+        );
+        assert_eq!(Polygon::empty().stats().total_envelope_area, 0.);
+    }
+
+    #[test]
+    fn test_map_coords() {
+        let ring = |coords: &[(f64, f64)]| {
+            crate::LineString::new(Coordinate::vec_from(coords))
+                .into_ring()
+                .unwrap()
+        };
+        let polygon = Polygon::<Raw>::new(
+            ring(&[(0.1, -0.2), (4.2, 0.3), (3.9, 4.4), (0.1, -0.2)]),
+            vec![ring(&[(1.2, 1.1), (2.1, 1.4), (1.8, 2.3), (1.2, 1.1)])],
+        );
+        let rounded = polygon
+            .map_coords(|c| Coordinate::new(c.x.round(), c.y.round()))
+            .prepare();
+        assert_eq!(
+            rounded.exterior_coords(),
+            &Coordinate::vec_from(&[(0., 0.), (4., 0.), (4., 4.), (0., 0.)])[..]
+        );
+        assert_eq!(
+            rounded.holes()[0].coords(),
+            &Coordinate::vec_from(&[(1., 1.), (2., 1.), (2., 2.), (1., 1.)])
+        );
+    }
+
+    #[test]
+    fn test_boundary_intersections() {
+        let square = |x_min: f64, y_min: f64, x_max: f64, y_max: f64| {
+            let shell = LinearRing::try_from(vec![
+                (x_min, y_min),
+                (x_max, y_min),
+                (x_max, y_max),
+                (x_min, y_max),
+                (x_min, y_min),
+            ])
+            .unwrap();
+            Polygon::try_new(shell, Vec::new()).unwrap()
+        };
+        let a = square(0., 0., 4., 4.);
+        // Shares part of a's right edge
+        let b = square(4., 1., 6., 3.);
+        let shared = Coordinate::vec_from(&[(4., 1.), (4., 3.)]);
+        assert_eq!(a.boundary_intersections(&b), shared);
+        assert_eq!(b.boundary_intersections(&a), shared);
+
+        // Crosses a's top edge
+        let c = square(1., 3., 2., 5.);
+        assert_eq!(
+            a.boundary_intersections(&c),
+            Coordinate::vec_from(&[(1., 4.), (2., 4.)])
+        );
+        assert!(a
+            .boundary_intersections(&square(10., 10., 11., 11.))
+            .is_empty());
+        assert!(a.boundary_intersections(&Polygon::empty()).is_empty());
+    }
+
+    #[test]
+    fn test_try_from_coords() {
+        let shell = Coordinate::vec_from(&[(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]);
+        let hole = Coordinate::vec_from(&[(1., 1.), (1., 2.), (2., 2.), (1., 1.)]);
+        let polygon = Polygon::try_from_coords(shell.clone(), vec![hole.clone()]).unwrap();
+        assert_eq!(polygon.exterior_coords(), &shell[..]);
+        assert_eq!(polygon.interior_coords(), vec![&hole[..]]);
+        assert_eq!(polygon.area(), 15.5);
+
+        // An invalid ring, or a hole outside the shell
+        let open = Coordinate::vec_from(&[(0., 0.), (4., 0.), (4., 4.)]);
+        assert!(Polygon::try_from_coords(open, Vec::new()).is_err());
+        let far_hole = Coordinate::vec_from(&[(5., 5.), (5., 6.), (6., 6.), (5., 5.)]);
+        assert_eq!(
+            Polygon::try_from_coords(shell, vec![far_hole]).unwrap_err(),
+            ValidationError::HoleNotValid
+        );
+    }
+
+    #[test]
+    fn test_reproject() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]).unwrap();
+        let hole = LinearRing::try_from(vec![(1., 1.), (1., 2.), (2., 2.), (1., 1.)]).unwrap();
+        let polygon = Polygon::try_new(shell, vec![hole]).unwrap();
+
+        // Scale by 2 and shear, which multiplies areas by 4.
+        let affine = |c: Coordinate| Coordinate::new(2. * c.x + c.y + 10., 2. * c.y - 5.);
+        let reprojected = polygon.reproject(affine).unwrap();
+        assert_eq!(reprojected.area(), 4. * polygon.area());
+        assert_eq!(
+            reprojected.exterior_coords()[..3],
+            Coordinate::vec_from(&[(10., -5.), (18., -5.), (22., 3.)])[..]
+        );
+        assert!(reprojected.contains(affine(Coordinate::new(3., 3.))));
+        assert!(!reprojected.contains(affine(Coordinate::new(1.2, 1.5))));
+
+        // Flattening the polygon makes its segments overlap.
+        assert!(polygon.reproject(|c| Coordinate::new(c.x, 0.)).is_err());
+        // A projection undefined at the first vertex
+        let log = |c: Coordinate| Coordinate::new(c.x.ln(), c.y);
+        assert_eq!(
+            polygon.reproject(log),
+            Err(ValidationError::NonFiniteCoordinate)
+        );
+        assert_eq!(
+            polygon.reproject(|c| Coordinate::new(c.x, f64::NAN)),
+            Err(ValidationError::NonFiniteCoordinate)
+        );
+        assert_eq!(Polygon::empty().reproject(affine), Ok(Polygon::empty()));
+    }
+
+    #[test]
+    fn test_display() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]).unwrap();
+        let hole = LinearRing::try_from(vec![(1., 1.), (1., 2.), (2., 2.), (1., 1.)]).unwrap();
+        let polygon = Polygon::try_new(shell, vec![hole]).unwrap();
+        assert_eq!(
+            polygon.to_string(),
+            "POLYGON ((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 1 2, 2 2, 1 1))"
+        );
+        assert_eq!(Polygon::empty().to_string(), "POLYGON EMPTY");
+    }
+
+    #[test]
+    fn test_coords_accessors() {
+        let shell = vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)];
+        let hole = vec![(1., 1.), (1., 2.), (2., 2.), (1., 1.)];
+        let polygon = Polygon::try_new(
+            LinearRing::try_from(shell.clone()).unwrap(),
+            vec![LinearRing::try_from(hole.clone()).unwrap()],
+        )
+        .unwrap();
+        assert_eq!(polygon.exterior_coords(), &Coordinate::vec_from(&shell)[..]);
+        assert_eq!(
+            polygon.interior_coords(),
+            vec![&Coordinate::vec_from(&hole)[..]]
+        );
+        assert_eq!(
+            polygon.exterior_coords().first(),
+            polygon.exterior_coords().last()
+        );
+    }
+
+    #[test]
+    fn test_eq() {
+        let make = |holes: Vec<Vec<(f64, f64)>>| {
+            let shell =
+                LinearRing::try_from(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)])
+                    .unwrap();
+            let holes = holes
+                .into_iter()
+                .map(|hole| LinearRing::try_from(hole).unwrap())
+                .collect();
+            Polygon::try_new(shell, holes).unwrap()
+        };
+        let hole_a = vec![(1., 1.), (1., 2.), (2., 2.), (2., 1.), (1., 1.)];
+        let hole_b = vec![(3., 3.), (3., 3.5), (3.5, 3.5), (3.5, 3.), (3., 3.)];
+        let hole_c = vec![(1., 1.), (1., 2.5), (2., 2.5), (2., 1.), (1., 1.)];
+
+        let polygon = make(vec![hole_a.clone(), hole_b.clone()]);
+        assert_eq!(polygon, make(vec![hole_a.clone(), hole_b.clone()]));
+        assert_eq!(polygon, polygon.clone());
+        assert_ne!(polygon, make(vec![hole_c, hole_b.clone()]));
+        assert_ne!(polygon, make(vec![hole_a.clone()]));
+        // Order-sensitive
+        assert_ne!(polygon, make(vec![hole_b, hole_a]));
+    }
+
+    #[test]
+    fn test_empty() {
+        let polygon = Polygon::empty();
+        assert!(polygon.envelope().is_empty());
+        assert_eq!(polygon.area(), 0.);
+        assert!(!polygon.contains((0., 0.).into()));
+        assert!(polygon
+            .clip(Rectangle::new((-1., -1.).into(), (1., 1.).into()))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_area_and_contains() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]).unwrap();
+        let hole =
+            LinearRing::try_from(vec![(1., 1.), (1., 2.), (2., 2.), (2., 1.), (1., 1.)]).unwrap();
+        let polygon = Polygon::try_new(shell, vec![hole]).unwrap();
+        assert_eq!(polygon.area(), 15.);
+        assert!(polygon.contains((3., 3.).into()));
+        assert!(!polygon.contains((1.5, 1.5).into()));
+        assert!(!polygon.contains((0., 2.).into()));
+        assert!(!polygon.contains((5., 2.).into()));
+    }
+
+    #[test]
+    fn test_candidate_holes() {
+        let shell = LinearRing::try_from(vec![
+            (0., 0.),
+            (100., 0.),
+            (100., 100.),
+            (0., 100.),
+            (0., 0.),
+        ])
+        .unwrap();
+        let holes: Vec<LinearRing<Validated>> = (0..100)
+            .map(|i| {
+                let (x, y) = ((i % 10) as f64 * 10. + 1., (i / 10) as f64 * 10. + 1.);
+                LinearRing::try_from(vec![
+                    (x, y),
+                    (x, y + 5.),
+                    (x + 5., y + 5.),
+                    (x + 5., y),
+                    (x, y),
+                ])
+                .unwrap()
+            })
+            .collect();
+        let polygon = Polygon::try_new(shell, holes).unwrap();
+        // In hole 34, and next to it
+        assert_eq!(polygon.candidate_holes((43., 33.).into()), vec![34]);
+        assert!(!polygon.contains((43., 33.).into()));
+        assert!(polygon.candidate_holes((48., 33.).into()).is_empty());
+        assert!(polygon.contains((48., 33.).into()));
+    }
+
+    #[test]
+    fn test_representative_point() {
+        // A C-shape, whose centroid is in the gap
+        let shell = LinearRing::try_from(vec![
+            (0., 0.),
+            (4., 0.),
+            (4., 1.),
+            (1., 1.),
+            (1., 3.),
+            (4., 3.),
+            (4., 4.),
+            (0., 4.),
+            (0., 0.),
+        ])
+        .unwrap();
+        let polygon = Polygon::try_new(shell, Vec::new()).unwrap();
+        assert_eq!(polygon.centroid_y(), 2.);
+        assert!(!polygon.contains((2., 2.).into()));
+        let point = polygon.representative_point();
+        assert!(polygon.contains(point));
+
+        // A square with a hole at its center
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (6., 0.), (6., 6.), (0., 6.), (0., 0.)]).unwrap();
+        let hole =
+            LinearRing::try_from(vec![(1., 1.), (1., 5.), (4., 5.), (4., 1.), (1., 1.)]).unwrap();
+        let polygon = Polygon::try_new(shell, vec![hole]).unwrap();
+        let point = polygon.representative_point();
+        assert!(polygon.contains(point));
+        assert!(Polygon::empty().representative_point().x.is_nan());
+    }
+
+    #[test]
+    fn test_is_convex() {
+        let square = vec![(0., 0.), (2., 0.), (2., 1.), (2., 2.), (0., 2.), (0., 0.)];
+        let polygon = Polygon::try_new(LinearRing::try_from(square.clone()).unwrap(), Vec::new());
+        assert!(polygon.unwrap().is_convex());
+
+        let star = vec![
+            (0., 2.),
+            (0.25, 0.25),
+            (2., 0.),
+            (0.25, -0.25),
+            (0., -2.),
+            (-0.25, -0.25),
+            (-2., 0.),
+            (-0.25, 0.25),
+            (0., 2.),
+        ];
+        let polygon = Polygon::try_new(LinearRing::try_from(star).unwrap(), Vec::new());
+        assert!(!polygon.unwrap().is_convex());
+
+        let hole = vec![(0.5, 0.5), (0.5, 1.), (1., 1.), (0.5, 0.5)];
+        let polygon = Polygon::try_new(
+            LinearRing::try_from(square).unwrap(),
+            vec![LinearRing::try_from(hole).unwrap()],
+        );
+        assert!(!polygon.unwrap().is_convex());
+        assert!(!Polygon::empty().is_convex());
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let l_shape = vec![
+            (0., 0.),
+            (2., 0.),
+            (2., 1.),
+            (1., 1.),
+            (1., 2.),
+            (0., 2.),
+            (0., 0.),
+        ];
+        let polygon = Polygon::try_new(LinearRing::try_from(l_shape).unwrap(), Vec::new()).unwrap();
+        // The inner corner is cut off.
+        let expected = LinearRing::try_from(vec![
+            (0., 0.),
+            (2., 0.),
+            (2., 1.),
+            (1., 2.),
+            (0., 2.),
+            (0., 0.),
+        ])
+        .unwrap();
+        assert!(polygon.convex_hull().equals_rotation(&expected));
+
+        // A clockwise U shape with a hole has its enclosing rectangle as hull.
+        let u_shape = vec![
+            (0., 0.),
+            (0., 3.),
+            (1., 3.),
+            (1., 1.),
+            (2., 1.),
+            (2., 3.),
+            (3., 3.),
+            (3., 0.),
+            (0., 0.),
+        ];
+        let hole = vec![(0.2, 0.2), (0.8, 0.2), (0.8, 0.8), (0.2, 0.2)];
+        let polygon = Polygon::try_new(
+            LinearRing::try_from(u_shape).unwrap(),
+            vec![LinearRing::try_from(hole).unwrap()],
+        )
+        .unwrap();
+        let expected =
+            LinearRing::try_from(vec![(0., 0.), (3., 0.), (3., 3.), (0., 3.), (0., 0.)]).unwrap();
+        assert!(polygon.convex_hull().equals_rotation(&expected));
+
+        assert!(Polygon::empty().convex_hull().coords().is_empty());
+    }
+
+    #[test]
+    fn test_distance() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]).unwrap();
+        let hole =
+            LinearRing::try_from(vec![(1., 1.), (1., 3.), (3., 3.), (3., 1.), (1., 1.)]).unwrap();
+        let polygon = Polygon::try_new(shell, vec![hole]).unwrap();
+        // Inside, and on the boundary
+        assert_eq!(polygon.distance((0.5, 2.).into()), 0.);
+        assert_eq!(polygon.distance((1., 2.).into()), 0.);
+        // Inside the hole
+        assert_eq!(polygon.distance((2., 1.5).into()), 0.5);
+        // Outside
+        assert_eq!(polygon.distance((7., 8.).into()), 5.);
+        assert_eq!(polygon.distance((2., -3.).into()), 3.);
+        assert_eq!(Polygon::empty().distance((0., 0.).into()), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_signed_distance() {
+        let shell =
+            LinearRing::try_from(vec![(0., 0.), (4., 0.), (4., 4.), (0., 4.), (0., 0.)]).unwrap();
+        let hole =
+            LinearRing::try_from(vec![(1., 1.), (1., 3.), (3., 3.), (3., 1.), (1., 1.)]).unwrap();
+        let polygon = Polygon::try_new(shell, vec![hole]).unwrap();
+        // Inside, nearer the hole than the shell
+        assert_eq!(polygon.signed_distance((0.75, 2.).into()), -0.25);
+        // On the shell, and on the hole
+        assert_eq!(polygon.signed_distance((4., 2.).into()), 0.);
+        assert_eq!(polygon.signed_distance((1., 2.).into()), 0.);
+        // Outside, and inside the hole
+        assert_eq!(polygon.signed_distance((2., 7.).into()), 3.);
+        assert_eq!(polygon.signed_distance((2., 1.5).into()), 0.5);
+        assert_eq!(
+            Polygon::empty().signed_distance((0., 0.).into()),
+            f64::INFINITY
+        );
+    }
+
+    #[test]
+    fn test_clip_into_pieces() {
+        // A U-shape, whose arms are separated by the clip
+        let shell = LinearRing::try_from(vec![
+            (0., 0.),
+            (3., 0.),
+            (3., 3.),
+            (2., 3.),
+            (2., 1.),
+            (1., 1.),
+            (1., 3.),
+            (0., 3.),
+            (0., 0.),
+        ])
+        .unwrap();
+        let polygon = Polygon::try_new(shell, Vec::new()).unwrap();
+
+        let pieces = polygon.clip(Rectangle::new((-1., 2.).into(), (4., 4.).into()));
+        let mut envelopes: Vec<Rectangle> = pieces.iter().map(|p| p.envelope()).collect();
+        envelopes.sort_by(|a, b| a.x_min.total_cmp(&b.x_min));
+        assert_eq!(
+            envelopes,
+            vec![
+                Rectangle::new((0., 2.).into(), (1., 3.).into()),
+                Rectangle::new((2., 2.).into(), (3., 3.).into()),
+            ]
+        );
+        assert!(pieces.iter().all(|p| signed_area(p.shell().coords()) == 1.));
+
+        // Fully contained
+        let pieces = polygon.clip(Rectangle::new((-1., -1.).into(), (4., 4.).into()));
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(pieces[0].shell().coords(), polygon.shell().coords());
+
+        // Only touching
+        let pieces = polygon.clip(Rectangle::new((3., 0.).into(), (4., 4.).into()));
+        assert!(pieces.is_empty());
     }
 }
@@ -1,4 +1,5 @@
 use crate::Coordinate;
+use std::fmt;
 
 use self::Side::*;
 #[derive(Copy, Clone, Debug)]
@@ -26,6 +27,20 @@ pub trait HasEnvelope {
     fn envelope(&self) -> Rectangle;
 }
 
+impl fmt::Display for Rectangle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            write!(f, "EMPTY")
+        } else {
+            write!(
+                f,
+                "[{}, {}, {}, {}]",
+                self.x_min, self.y_min, self.x_max, self.y_max
+            )
+        }
+    }
+}
+
 impl HasEnvelope for Coordinate {
     fn envelope(&self) -> Rectangle {
         Rectangle {
@@ -43,6 +58,21 @@ impl HasEnvelope for Rectangle {
     }
 }
 
+/// The envelope of all the items, which is empty if there are none.  Unlike
+/// Rectangle::of, this takes any iterator.
+pub fn total_envelope<I, T>(items: I) -> Rectangle
+where
+    I: IntoIterator<Item = T>,
+    T: HasEnvelope,
+{
+    items
+        .into_iter()
+        .fold(Rectangle::new_empty(), |mut total, item| {
+            total.expand(item);
+            total
+        })
+}
+
 impl Rectangle {
     pub fn new(p1: Coordinate, p2: Coordinate) -> Self {
         Rectangle {
@@ -66,6 +96,12 @@ impl Rectangle {
         self.x_min.is_nan() || self.y_min.is_nan() || self.x_max.is_nan() || self.y_max.is_nan()
     }
 
+    /// Whether the width or height is at most epsilon, eg for points and
+    /// lines.  Empty rectangles are also degenerate.
+    pub fn is_degenerate(&self, epsilon: f64) -> bool {
+        self.is_empty() || self.x_max - self.x_min <= epsilon || self.y_max - self.y_min <= epsilon
+    }
+
     pub fn of<T: HasEnvelope>(items: &[T]) -> Self {
         items.iter().fold(Rectangle::new_empty(), |mut s, r| {
             s.expand(r.envelope());
@@ -80,6 +116,49 @@ impl Rectangle {
         }
     }
 
+    /// The distance from coord to the closest point of the rectangle, which
+    /// is 0 if the rectangle contains coord.  Empty rectangles return NaN.
+    pub fn distance_to_coord(&self, coord: Coordinate) -> f64 {
+        if self.is_empty() {
+            return f64::NAN;
+        }
+        let dx = (self.x_min - coord.x).max(coord.x - self.x_max).max(0.);
+        let dy = (self.y_min - coord.y).max(coord.y - self.y_max).max(0.);
+        dx.hypot(dy)
+    }
+
+    /// The point on the perimeter of the rectangle closest to coord.  If
+    /// coord is inside, this is its projection onto the nearest side (the
+    /// first in Side order, on ties).  Empty rectangles return NaN components.
+    pub fn closest_boundary_point(&self, coord: Coordinate) -> Coordinate {
+        if self.is_empty() {
+            return Coordinate::new(f64::NAN, f64::NAN);
+        }
+        if !self.contains(coord) {
+            return Coordinate::new(
+                coord.x.max(self.x_min).min(self.x_max),
+                coord.y.max(self.y_min).min(self.y_max),
+            );
+        }
+
+        let mut closest = (self.y_max - coord.y, Top);
+        for &(distance, side) in &[
+            (self.x_max - coord.x, Right),
+            (coord.y - self.y_min, Bottom),
+            (coord.x - self.x_min, Left),
+        ] {
+            if distance < closest.0 {
+                closest = (distance, side);
+            }
+        }
+        match closest.1 {
+            Top => Coordinate::new(coord.x, self.y_max),
+            Right => Coordinate::new(self.x_max, coord.y),
+            Bottom => Coordinate::new(coord.x, self.y_min),
+            Left => Coordinate::new(self.x_min, coord.y),
+        }
+    }
+
     pub fn intersects<T: HasEnvelope>(&self, item: T) -> bool {
         let other = item.envelope();
         self.x_min <= other.x_max
@@ -96,24 +175,244 @@ impl Rectangle {
             && self.y_max >= other.y_max
     }
 
+    /// Like contains, but allow the item's envelope to stick out by up to
+    /// eps on each side, eg to absorb rounding from intersection arithmetic.
+    pub fn contains_eps<T: HasEnvelope>(&self, item: T, eps: f64) -> bool {
+        let other = item.envelope();
+        self.x_min - eps <= other.x_min
+            && self.x_max + eps >= other.x_max
+            && self.y_min - eps <= other.y_min
+            && self.y_max + eps >= other.y_max
+    }
+
+    /// Whether the item's envelope is within the interior of this rectangle,
+    /// not touching its boundary.
+    pub fn contains_strict<T: HasEnvelope>(&self, item: T) -> bool {
+        let other = item.envelope();
+        self.x_min < other.x_min
+            && self.x_max > other.x_max
+            && self.y_min < other.y_min
+            && self.y_max > other.y_max
+    }
+
     pub fn merge<T: HasEnvelope>(&self, item: T) -> Self {
-        let rect = item.envelope();
-        Rectangle {
-            x_min: self.x_min.min(rect.x_min),
-            y_min: self.y_min.min(rect.y_min),
-            x_max: self.x_max.max(rect.x_max),
-            y_max: self.y_max.max(rect.y_max),
-        }
+        let mut merged = *self;
+        merged.expand(item);
+        merged
     }
 
+    /// Grow to contain the item's envelope.  Empty envelopes, including those
+    /// with only some NaN bounds, are ignored.
     pub fn expand<T: HasEnvelope>(&mut self, item: T) {
         let rect = item.envelope();
+        if rect.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            *self = rect;
+            return;
+        }
         self.x_min = self.x_min.min(rect.x_min);
         self.y_min = self.y_min.min(rect.y_min);
         self.x_max = self.x_max.max(rect.x_max);
         self.y_max = self.y_max.max(rect.y_max);
     }
 
+    /// The rectangle common to both, which is empty if they don't intersect.
+    pub fn intersection<T: HasEnvelope>(&self, item: T) -> Self {
+        let rect = item.envelope();
+        if !self.intersects(rect) {
+            return Rectangle::new_empty();
+        }
+        Rectangle {
+            x_min: self.x_min.max(rect.x_min),
+            y_min: self.y_min.max(rect.y_min),
+            x_max: self.x_max.min(rect.x_max),
+            y_max: self.y_max.min(rect.y_max),
+        }
+    }
+
+    /// Up to four rectangles covering the part of this rectangle outside the
+    /// item's envelope: full-width strips below and above it, and pieces to
+    /// its left and right.  The pieces share edges, but do not overlap.
+    pub fn difference<T: HasEnvelope>(&self, item: T) -> Vec<Rectangle> {
+        let other = item.envelope();
+        if self.is_empty() || other.contains(*self) {
+            return Vec::new();
+        }
+        if !self.intersects(other) {
+            return vec![*self];
+        }
+        let middle = self.intersection(other);
+        let mut pieces = Vec::with_capacity(4);
+        if middle.y_min > self.y_min {
+            pieces.push(Rectangle {
+                y_max: middle.y_min,
+                ..*self
+            });
+        }
+        if middle.y_max < self.y_max {
+            pieces.push(Rectangle {
+                y_min: middle.y_max,
+                ..*self
+            });
+        }
+        if middle.x_min > self.x_min {
+            pieces.push(Rectangle {
+                x_min: self.x_min,
+                x_max: middle.x_min,
+                ..middle
+            });
+        }
+        if middle.x_max < self.x_max {
+            pieces.push(Rectangle {
+                x_min: middle.x_max,
+                x_max: self.x_max,
+                ..middle
+            });
+        }
+        pieces
+    }
+
+    /// The area of the intersection, which is 0 if they are disjoint or
+    /// either is empty.
+    pub fn overlap_area<T: HasEnvelope>(&self, item: T) -> f64 {
+        self.intersection(item).area()
+    }
+
+    /// The intersection-over-union of the two rectangles: the overlap area
+    /// divided by the area of their union.  Returns 0 if the union has no
+    /// area, eg if both are empty.
+    pub fn iou<T: HasEnvelope>(&self, item: T) -> f64 {
+        let other = item.envelope();
+        let overlap = self.overlap_area(other);
+        let union = self.area() + other.area() - overlap;
+        if union > 0. {
+            overlap / union
+        } else {
+            0.
+        }
+    }
+
+    /// The rectangle grown by distance on every side, or shrunk if distance
+    /// is negative.  Shrinking past the center gives an empty rectangle.
+    pub fn buffer(&self, distance: f64) -> Rectangle {
+        let buffered = Rectangle {
+            x_min: self.x_min - distance,
+            y_min: self.y_min - distance,
+            x_max: self.x_max + distance,
+            y_max: self.y_max + distance,
+        };
+        if buffered.x_min > buffered.x_max || buffered.y_min > buffered.y_max {
+            return Rectangle::new_empty();
+        }
+        buffered
+    }
+
+    /// The distance between the closest points of the two rectangles, which
+    /// is 0 if they intersect.  Empty rectangles return NaN.
+    pub fn distance<T: HasEnvelope>(&self, item: T) -> f64 {
+        let rect = item.envelope();
+        if self.is_empty() || rect.is_empty() {
+            return f64::NAN;
+        }
+        let dx = (self.x_min - rect.x_max)
+            .max(rect.x_min - self.x_max)
+            .max(0.);
+        let dy = (self.y_min - rect.y_max)
+            .max(rect.y_min - self.y_max)
+            .max(0.);
+        dx.hypot(dy)
+    }
+
+    /// The rectangle with width and height scaled by factor, about the same
+    /// center.  Negative factors give an empty rectangle.
+    pub fn scaled(&self, factor: f64) -> Rectangle {
+        if self.is_empty() || factor < 0. {
+            return Rectangle::new_empty();
+        }
+        let center = self.center();
+        let half_width = (self.x_max - self.x_min) * factor / 2.;
+        let half_height = (self.y_max - self.y_min) * factor / 2.;
+        Rectangle {
+            x_min: center.x - half_width,
+            y_min: center.y - half_height,
+            x_max: center.x + half_width,
+            y_max: center.y + half_height,
+        }
+    }
+
+    /// The area, which is 0 if empty.
+    pub fn area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.;
+        }
+        (self.x_max - self.x_min) * (self.y_max - self.y_min)
+    }
+
+    /// Clip the polyline defined by coords, returning the sections within
+    /// the rectangle.  This clips each segment in turn without an rtree, so
+    /// it's suited to short polylines; it gives the same sections as
+    /// clip_path.
+    pub fn intersect_polyline(&self, coords: &[Coordinate]) -> Vec<Vec<Coordinate>> {
+        let mut sections: Vec<Vec<Coordinate>> = Vec::new();
+        // Whether the previous segment ended inside, so the next continues it
+        let mut continuing = false;
+        for segment in coords.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            match self.intersect_segment(start, end) {
+                Some((isxn_start, isxn_end)) => {
+                    if !continuing {
+                        sections.push(vec![isxn_start]);
+                    }
+                    let section = sections.last_mut().unwrap();
+                    if isxn_end != isxn_start {
+                        section.push(isxn_end);
+                    }
+                    continuing = isxn_end == end;
+                }
+                None => continuing = false,
+            }
+        }
+
+        // Rejoin a loop that was clipped into pieces across its start.
+        if sections.len() > 1
+            && sections.first().and_then(|s| s.first()) == sections.last().and_then(|s| s.last())
+        {
+            let mut last_section = sections.pop().unwrap();
+            last_section.pop();
+            last_section.extend_from_slice(&sections[0]);
+            sections[0] = last_section;
+        }
+        sections
+    }
+
+    /// The side that the segment from inside to outside crosses as it
+    /// leaves the rectangle, or None unless inside is in the rectangle and
+    /// outside is not.  If it leaves through a corner, choose the side first
+    /// in order, as find_side does.
+    pub fn exit_side(&self, inside: Coordinate, outside: Coordinate) -> Option<Side> {
+        if !self.contains(inside) || self.contains(outside) {
+            return None;
+        }
+        let delta = outside - inside;
+        // The fraction of the way along the segment that it crosses each
+        // side's line, for the sides it heads towards.
+        let crossings = [
+            (Top, delta.y > 0., (self.y_max - inside.y) / delta.y),
+            (Right, delta.x > 0., (self.x_max - inside.x) / delta.x),
+            (Bottom, delta.y < 0., (self.y_min - inside.y) / delta.y),
+            (Left, delta.x < 0., (self.x_min - inside.x) / delta.x),
+        ];
+        let mut exit: Option<(Side, f64)> = None;
+        for &(side, heads_towards, t) in &crossings {
+            if heads_towards && exit.map_or(true, |(_, exit_t)| t < exit_t) {
+                exit = Some((side, t));
+            }
+        }
+        exit.map(|(side, _)| side)
+    }
+
     /// Return the intersection of the segment defined by start and end.
     /// Uses the Liang-Barsky algorithm:
     /// https://www.skytopia.com/project/articles/compsci/clipping.html
@@ -177,6 +476,7 @@ pub enum Side {
 impl Side {
     /// Find the side of a rect a coordinate is on.  If it is on a corner,
     /// choose the side first in order.
+    #[allow(dead_code)]
     pub fn find_side(coord: Coordinate, rectangle: Rectangle) -> Option<Side> {
         if coord.y == rectangle.y_max {
             Some(Top)
@@ -198,6 +498,269 @@ static SIDES: [Side; 4] = [Top, Right, Bottom, Left];
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_total_envelope() {
+        let points = Coordinate::vec_from(&[(-1., 5.), (1., 1.)]);
+        let rect = Rectangle::new((0., 0.).into(), (2., 2.).into());
+        let items = points
+            .iter()
+            .map(HasEnvelope::envelope)
+            .chain(std::iter::once(rect));
+        assert_eq!(
+            total_envelope(items),
+            Rectangle::new((-1., 0.).into(), (2., 5.).into())
+        );
+        assert_eq!(
+            total_envelope(points.iter().copied()),
+            Rectangle::of(&points)
+        );
+        assert!(total_envelope(Vec::<Rectangle>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_expand_empty() {
+        assert!(Rectangle::of(&[Rectangle::new_empty(); 5]).is_empty());
+        assert!(Rectangle::of::<Rectangle>(&[]).is_empty());
+
+        let rect = Rectangle::new((0., 0.).into(), (1., 2.).into());
+        let mut expanded = rect;
+        expanded.expand(Rectangle::new_empty());
+        assert_eq!(expanded, rect);
+        // A partly-NaN rectangle is empty, so none of its bounds are used.
+        let partly_nan = Rectangle {
+            x_min: f64::NAN,
+            y_min: -5.,
+            x_max: 3.,
+            y_max: 5.,
+        };
+        expanded.expand(partly_nan);
+        assert_eq!(expanded, rect);
+
+        let mut expanded = Rectangle::new_empty();
+        expanded.expand(rect);
+        assert_eq!(expanded, rect);
+        let mut expanded = partly_nan;
+        expanded.expand(rect);
+        assert_eq!(expanded, rect);
+
+        assert_eq!(
+            Rectangle::of(&[Rectangle::new_empty(), rect, partly_nan]),
+            rect
+        );
+        assert_eq!(rect.merge(Rectangle::new_empty()), rect);
+        assert_eq!(Rectangle::new_empty().merge(rect), rect);
+    }
+
+    #[test]
+    fn test_contains_eps() {
+        let rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        let noisy = Rectangle::new((-1e-15, 0.5).into(), (0.5, 1. + 1e-15).into());
+        assert!(!rect.contains(noisy));
+        assert!(rect.contains_eps(noisy, 1e-12));
+        assert!(!rect.contains_eps(noisy, 1e-16));
+        assert!(rect.contains_eps(rect, 0.));
+        let outside = Rectangle::new((0.5, 0.5).into(), (1.1, 1.).into());
+        assert!(!rect.contains_eps(outside, 1e-12));
+    }
+
+    #[test]
+    fn test_display() {
+        let rect = Rectangle::new((0., -1.).into(), (2.5, 3.).into());
+        assert_eq!(rect.to_string(), "[0, -1, 2.5, 3]");
+        assert_eq!(Rectangle::new_empty().to_string(), "EMPTY");
+    }
+
+    #[test]
+    fn test_difference() {
+        let rect = |x_min: f64, y_min: f64, x_max: f64, y_max: f64| {
+            Rectangle::new((x_min, y_min).into(), (x_max, y_max).into())
+        };
+        let a = rect(0., 0., 4., 4.);
+        // Overlapping the top-right corner leaves an L shape.
+        let pieces = a.difference(rect(2., 2., 6., 6.));
+        assert_eq!(pieces, vec![rect(0., 0., 4., 2.), rect(0., 2., 2., 4.)]);
+        assert_eq!(pieces.iter().map(|p| p.area()).sum::<f64>(), 12.);
+
+        // A hole in the middle leaves a frame of four pieces.
+        let pieces = a.difference(rect(1., 1., 3., 2.));
+        assert_eq!(
+            pieces,
+            vec![
+                rect(0., 0., 4., 1.),
+                rect(0., 2., 4., 4.),
+                rect(0., 1., 1., 2.),
+                rect(3., 1., 4., 2.),
+            ]
+        );
+
+        assert_eq!(a.difference(rect(5., 5., 6., 6.)), vec![a]);
+        assert_eq!(a.difference(Rectangle::new_empty()), vec![a]);
+        assert!(a.difference(a).is_empty());
+        assert!(a.difference(rect(-1., -1., 5., 5.)).is_empty());
+        assert!(Rectangle::new_empty().difference(a).is_empty());
+    }
+
+    #[test]
+    fn test_overlap_area() {
+        let a = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        let b = Rectangle::new((0.5, 0.5).into(), (1.5, 1.5).into());
+        assert_eq!(a.overlap_area(b), 0.25);
+        assert_eq!(a.overlap_area(a), 1.);
+        let c = Rectangle::new((2., 2.).into(), (3., 3.).into());
+        assert_eq!(a.overlap_area(c), 0.);
+        assert_eq!(a.overlap_area(Rectangle::new_empty()), 0.);
+    }
+
+    #[test]
+    fn test_scaled() {
+        let rect = Rectangle::new((0., 0.).into(), (4., 2.).into());
+        assert_eq!(
+            rect.scaled(2.),
+            Rectangle::new((-2., -1.).into(), (6., 3.).into())
+        );
+        assert_eq!(
+            rect.scaled(0.5),
+            Rectangle::new((1., 0.5).into(), (3., 1.5).into())
+        );
+        assert_eq!(
+            rect.scaled(0.),
+            Rectangle::new((2., 1.).into(), (2., 1.).into())
+        );
+        assert!(rect.scaled(-1.).is_empty());
+    }
+
+    #[test]
+    fn test_iou() {
+        let a = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        assert_eq!(a.iou(a), 1.);
+        let b = Rectangle::new((2., 2.).into(), (3., 3.).into());
+        assert_eq!(a.iou(b), 0.);
+        // Overlap 0.5, union 1.5
+        let c = Rectangle::new((0.5, 0.).into(), (1.5, 1.).into());
+        assert_eq!(a.iou(c), 1. / 3.);
+        assert_eq!(Rectangle::new_empty().iou(Rectangle::new_empty()), 0.);
+        let point = Rectangle::new((0.5, 0.5).into(), (0.5, 0.5).into());
+        assert_eq!(point.iou(point), 0.);
+    }
+
+    #[allow(clippy::float_cmp)]
+    #[test]
+    fn test_distance_to_coord() {
+        let rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        assert_eq!(rect.distance_to_coord((0.5, 0.5).into()), 0.);
+        assert_eq!(rect.distance_to_coord((0.5, 3.).into()), 2.);
+        assert_eq!(rect.distance_to_coord((-3., 0.5).into()), 3.);
+        assert_eq!(rect.distance_to_coord((4., 5.).into()), 5.);
+        assert!(Rectangle::new_empty()
+            .distance_to_coord((0., 0.).into())
+            .is_nan());
+    }
+
+    #[test]
+    fn test_is_degenerate() {
+        let point = Rectangle::new((1., 1.).into(), (1., 1.).into());
+        assert!(point.is_degenerate(0.));
+        let thin = Rectangle::new((0., 0.).into(), (1e-10, 5.).into());
+        assert!(thin.is_degenerate(1e-9));
+        assert!(!thin.is_degenerate(0.));
+        let rect = Rectangle::new((0., 0.).into(), (1., 2.).into());
+        assert!(!rect.is_degenerate(1e-9));
+        assert!(Rectangle::new_empty().is_degenerate(1e-9));
+    }
+
+    #[test]
+    fn test_intersection() {
+        let a = Rectangle::new((0., 0.).into(), (2., 2.).into());
+        let b = Rectangle::new((1., -1.).into(), (3., 1.).into());
+        assert_eq!(
+            a.intersection(b),
+            Rectangle::new((1., 0.).into(), (2., 1.).into())
+        );
+        let c = Rectangle::new((5., 5.).into(), (6., 6.).into());
+        assert!(a.intersection(c).is_empty());
+        assert!(a.intersection(Rectangle::new_empty()).is_empty());
+    }
+
+    #[test]
+    fn test_buffer_and_distance() {
+        let rect = Rectangle::new((0., 0.).into(), (4., 2.).into());
+        assert_eq!(
+            rect.buffer(1.),
+            Rectangle::new((-1., -1.).into(), (5., 3.).into())
+        );
+        assert_eq!(
+            rect.buffer(-1.),
+            Rectangle::new((1., 1.).into(), (3., 1.).into())
+        );
+        assert!(rect.buffer(-2.).is_empty());
+        assert!(Rectangle::new_empty().buffer(1.).is_empty());
+
+        let far = Rectangle::new((7., 6.).into(), (8., 7.).into());
+        assert_eq!(rect.distance(far), 5.);
+        assert_eq!(far.distance(rect), 5.);
+        let beside = Rectangle::new((5., -1.).into(), (6., 1.).into());
+        assert_eq!(rect.distance(beside), 1.);
+        assert_eq!(rect.distance(rect.buffer(-0.5)), 0.);
+        assert!(rect.distance(Rectangle::new_empty()).is_nan());
+    }
+
+    #[test]
+    fn test_closest_boundary_point() {
+        let rect = Rectangle::new((0., 0.).into(), (2., 1.).into());
+        // Inside
+        assert_eq!(
+            rect.closest_boundary_point((0.5, 0.4).into()),
+            (0.5, 0.).into()
+        );
+        assert_eq!(
+            rect.closest_boundary_point((1.8, 0.5).into()),
+            (2., 0.5).into()
+        );
+        // Outside to the side
+        assert_eq!(
+            rect.closest_boundary_point((-1., 0.5).into()),
+            (0., 0.5).into()
+        );
+        // Outside past a corner
+        assert_eq!(
+            rect.closest_boundary_point((3., 4.).into()),
+            (2., 1.).into()
+        );
+        assert!(!Rectangle::new_empty()
+            .closest_boundary_point((0., 0.).into())
+            .is_finite());
+    }
+
+    #[test]
+    fn test_exit_side() {
+        let rect = Rectangle::new((0., 0.).into(), (4., 2.).into());
+        let center = Coordinate::new(2., 1.);
+        assert_eq!(rect.exit_side(center, (3., 5.).into()), Some(Top));
+        assert_eq!(rect.exit_side(center, (6., 1.5).into()), Some(Right));
+        assert_eq!(rect.exit_side(center, (1., -1.).into()), Some(Bottom));
+        assert_eq!(rect.exit_side(center, (-3., 0.).into()), Some(Left));
+        // Steep enough to leave through the top before reaching the right
+        assert_eq!(rect.exit_side(center, (5., 10.).into()), Some(Top));
+        // From a point on the boundary
+        assert_eq!(
+            rect.exit_side((4., 1.).into(), (5., 1.).into()),
+            Some(Right)
+        );
+
+        // Through the corners, choosing the first side in order
+        assert_eq!(rect.exit_side(center, (6., 3.).into()), Some(Top));
+        assert_eq!(rect.exit_side(center, (6., -1.).into()), Some(Right));
+        assert_eq!(rect.exit_side(center, (-2., -1.).into()), Some(Bottom));
+        assert_eq!(rect.exit_side(center, (-2., 3.).into()), Some(Top));
+
+        assert_eq!(rect.exit_side(center, (3., 1.5).into()), None);
+        assert_eq!(rect.exit_side((5., 5.).into(), (6., 6.).into()), None);
+        assert_eq!(
+            Rectangle::new_empty().exit_side(center, (9., 9.).into()),
+            None
+        );
+    }
+
     #[test]
     fn test_clip() {
         let rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
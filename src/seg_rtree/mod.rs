@@ -1,5 +1,5 @@
 mod rtree;
 mod segment_union;
 
-pub use rtree::SegRTree;
+pub use rtree::{GrowthPolicy, SegRTree};
 pub use segment_union::SegmentUnion;
@@ -1,5 +1,11 @@
-use crate::utils::{calculate_level_indices, copy_into_slice};
-use crate::{Coordinate, HasEnvelope, Rectangle};
+use crate::algorithms::point_in_polygon::{point_in_coords_loop, ContainRelation};
+use crate::errors::{ContainmentError, DecodeError};
+use crate::utils::{calculate_level_indices, copy_into_slice, point_segment_distance};
+use crate::{Coordinate, FlatbushNode, HasEnvelope, Rectangle};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::ops::Range;
 
 #[derive(Debug, Clone)]
 pub struct SegRTree {
@@ -9,6 +15,33 @@ pub struct SegRTree {
     current_level: usize,
     level_indices: Vec<usize>,
     tree: Vec<Rectangle>,
+    growth_policy: GrowthPolicy,
+}
+
+/// How a SegRTree grows when pushed past its capacity, and shrinks when
+/// segments are removed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GrowthPolicy {
+    /// The factor (finite, and at least 1) to multiply the capacity by when
+    /// the tree is full.  Larger factors rebuild less often, but the tree
+    /// may be as little as 1 / growth_factor full.  At least 1 slot is
+    /// always added.
+    pub growth_factor: f64,
+    /// The fill ratio (from 0 to 1) below which removing the last segment
+    /// compacts the tree: it is rebuilt with just enough capacity for its
+    /// segments.  Indices are unchanged, so only the removed segments after
+    /// the last remaining one are dropped.  With 0, the tree is never
+    /// compacted.
+    pub min_fill: f64,
+}
+
+impl Default for GrowthPolicy {
+    fn default() -> Self {
+        GrowthPolicy {
+            growth_factor: 2.,
+            min_fill: 0.,
+        }
+    }
 }
 
 impl HasEnvelope for SegRTree {
@@ -17,6 +50,12 @@ impl HasEnvelope for SegRTree {
     }
 }
 
+impl Default for SegRTree {
+    fn default() -> Self {
+        SegRTree::new_empty()
+    }
+}
+
 #[allow(dead_code)]
 impl SegRTree {
     pub(crate) fn len(&self) -> usize {
@@ -34,6 +73,20 @@ impl SegRTree {
         self.degree
     }
 
+    /// The number of rectangles that can still be added.
+    pub fn remaining_capacity(&self) -> usize {
+        self.max_size - self.current_size
+    }
+
+    /// The fraction of the capacity that is used, which is 1 for a tree
+    /// with no capacity.
+    pub fn fill_ratio(&self) -> f64 {
+        if self.max_size == 0 {
+            return 1.;
+        }
+        self.current_size as f64 / self.max_size as f64
+    }
+
     pub fn new_empty() -> Self {
         SegRTree {
             degree: 2,
@@ -42,6 +95,7 @@ impl SegRTree {
             current_level: 0,
             level_indices: vec![0],
             tree: vec![Rectangle::new_empty()],
+            growth_policy: GrowthPolicy::default(),
         }
     }
 
@@ -57,7 +111,16 @@ impl SegRTree {
             current_level: 0,
             level_indices,
             tree: vec![empty_rect; tree_size],
+            growth_policy: GrowthPolicy::default(),
+        }
+    }
+
+    /// Like new, but return an error if degree < 2 rather than using 2.
+    pub fn try_new(degree: usize, max_size: usize) -> Result<Self, String> {
+        if degree < 2 {
+            return Err(format!("Degree must be at least 2, got {}", degree));
         }
+        Ok(SegRTree::new(degree, max_size))
     }
 
     pub fn new_loaded(mut degree: usize, rects: &[Rectangle]) -> Self {
@@ -69,25 +132,75 @@ impl SegRTree {
         let mut tree = vec![empty_rect; tree_size];
         copy_into_slice(&mut tree, 0, rects);
 
-        for level in 1..level_indices.len() {
-            let level_index = level_indices[level];
-            let previous_items = &tree[level_indices[level - 1]..level_index];
-            let next_items: Vec<Rectangle> = previous_items
-                .chunks(degree)
-                .map(|items| Rectangle::of(items))
-                .collect();
-            copy_into_slice(&mut tree, level_index, &next_items);
-        }
-
         tree.shrink_to_fit();
-        SegRTree {
+        let mut rtree = SegRTree {
             degree,
             max_size,
             current_size: max_size,
             current_level: level_indices.len() - 1,
             level_indices,
             tree,
+            growth_policy: GrowthPolicy::default(),
+        };
+        rtree.tighten();
+        rtree
+    }
+
+    /// Build a tree for each list of rectangles, as by new_loaded.  With the
+    /// `rayon` feature, the trees are built in parallel.
+    pub fn build_many(degree: usize, rect_lists: &[Vec<Rectangle>]) -> Vec<SegRTree> {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            rect_lists
+                .par_iter()
+                .map(|rects| SegRTree::new_loaded(degree, rects))
+                .collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            rect_lists
+                .iter()
+                .map(|rects| SegRTree::new_loaded(degree, rects))
+                .collect()
+        }
+    }
+
+    /// Like new_loaded, but also describe every node of the tree, level by
+    /// level from the leaves up, eg to attach metadata to nodes or render
+    /// the tree.  An empty tree has no nodes.
+    pub fn new_loaded_with_map(degree: usize, rects: &[Rectangle]) -> (Self, Vec<FlatbushNode>) {
+        let rtree = SegRTree::new_loaded(degree, rects);
+        let mut nodes = Vec::with_capacity(rtree.tree.len());
+        // Levels may be padded, so count the nodes of each level from the
+        // leaves up.
+        let mut level_size = rtree.len();
+        for level in 0..=rtree.height() {
+            let level_start = rtree.level_indices[level];
+            nodes.extend((0..level_size).map(|sibling_index| FlatbushNode {
+                level,
+                tree_index: level_start + sibling_index,
+                sibling_index,
+                envelope: rtree.tree[level_start + sibling_index],
+            }));
+            level_size = (level_size + rtree.degree - 1) / rtree.degree;
+        }
+        (rtree, nodes)
+    }
+
+    /// Build a new tree of the segments of a followed by those of b.  The
+    /// segments of b are offset by a.len() in the new tree.
+    ///
+    /// This rebuilds the tree from the leaves (with the degree of a), rather
+    /// than splicing the existing nodes.
+    pub fn merge(a: &SegRTree, b: &SegRTree) -> SegRTree {
+        let mut rects = Vec::with_capacity(a.len() + b.len());
+        rects.extend_from_slice(&a.tree[0..a.len()]);
+        rects.extend_from_slice(&b.tree[0..b.len()]);
+        if rects.is_empty() {
+            return SegRTree::new_empty();
         }
+        SegRTree::new_loaded(a.degree, &rects)
     }
 
     pub fn add(&mut self, mut rect: Rectangle) -> Result<(), String> {
@@ -111,15 +224,257 @@ impl SegRTree {
             level += 1;
         }
 
+        // The capacity check above keeps the new item within the levels.
+        debug_assert!(level < self.level_indices.len());
         self.current_level = level;
         self.current_size += 1;
         Ok(())
     }
 
+    /// Use the policy to grow the tree in push, and compact it in remove.
+    pub fn with_growth_policy(mut self, policy: GrowthPolicy) -> Self {
+        assert!(
+            policy.growth_factor.is_finite() && policy.growth_factor >= 1.,
+            "Growth factor must be finite and at least 1, got {}",
+            policy.growth_factor
+        );
+        assert!(
+            (0. ..=1.).contains(&policy.min_fill),
+            "Minimum fill must be between 0 and 1, got {}",
+            policy.min_fill
+        );
+        self.growth_policy = policy;
+        self
+    }
+
+    /// Like add, but if the tree is full, first rebuild it with more
+    /// capacity, as set by its GrowthPolicy.
+    pub fn push(&mut self, rect: Rectangle) {
+        if self.remaining_capacity() == 0 {
+            let grown_size = (self.max_size as f64 * self.growth_policy.growth_factor).ceil();
+            // Each leaf is a rectangle, so this bounds the allocation.
+            let max_leaves = isize::MAX as usize / std::mem::size_of::<Rectangle>();
+            assert!(
+                grown_size <= max_leaves as f64,
+                "Cannot grow the tree to {} segments",
+                grown_size
+            );
+            self.rebuild((grown_size as usize).max(self.max_size + 1));
+        }
+        self.add(rect).expect("The tree has capacity after growing");
+    }
+
+    /// Rebuild the tree with the given capacity, keeping its rectangles.
+    fn rebuild(&mut self, max_size: usize) {
+        let mut rebuilt = SegRTree::new(self.degree, max_size);
+        copy_into_slice(&mut rebuilt.tree, 0, &self.tree[..self.current_size]);
+        rebuilt.current_size = self.current_size;
+        rebuilt.current_level = rebuilt.level_indices.len() - 1;
+        rebuilt.growth_policy = self.growth_policy;
+        rebuilt.tighten();
+        *self = rebuilt;
+    }
+
+    /// Remove the segment at index, returning its rectangle if it was present.
+    ///
+    /// The indices of the other segments are unchanged, and the envelopes of
+    /// the ancestor nodes are not shrunk; call tighten to do so.  Removing
+    /// the last segment also drops the removed segments before it, and may
+    /// compact the tree, as set by its GrowthPolicy.
+    pub fn remove(&mut self, index: usize) -> Option<Rectangle> {
+        if index >= self.current_size || self.tree[index].is_empty() {
+            return None;
+        }
+        let rect = self.tree[index];
+        self.tree[index] = Rectangle::new_empty();
+        if index + 1 == self.current_size {
+            while self.current_size > 0 && self.tree[self.current_size - 1].is_empty() {
+                self.current_size -= 1;
+            }
+            if self.fill_ratio() < self.growth_policy.min_fill {
+                self.rebuild(self.current_size);
+            }
+        }
+        Some(rect)
+    }
+
+    /// Recompute the rectangle of every internal node from the leaves, so
+    /// that each is the tightest envelope of its children.
+    pub fn tighten(&mut self) {
+        for level in 1..self.level_indices.len() {
+            let level_index = self.level_indices[level];
+            let previous_items = &self.tree[self.level_indices[level - 1]..level_index];
+            let next_items: Vec<Rectangle> = previous_items
+                .chunks(self.degree)
+                .map(Rectangle::of)
+                .collect();
+            copy_into_slice(&mut self.tree, level_index, &next_items);
+        }
+    }
+
+    /// The leaf rectangles, in insertion order: the rectangle at offset i is
+    /// the envelope of segment i.
+    pub fn leaf_rectangles(&self) -> &[Rectangle] {
+        &self.tree[0..self.current_size]
+    }
+
+    /// Iterate over the (segment index, envelope) of each leaf, skipping
+    /// leaves that have been removed.
+    pub fn iter_leaves(&self) -> impl Iterator<Item = (usize, Rectangle)> + '_ {
+        self.leaf_rectangles()
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, rect)| !rect.is_empty())
+    }
+
     pub fn query_rect(&self, rect: Rectangle) -> Vec<usize> {
         self.query(|rtree_rect| rtree_rect.intersects(rect))
     }
 
+    /// Like query_rect, but the results are in ascending order.  This sorts
+    /// the results, costing O(k log k) extra for k results.
+    pub fn query_rect_ordered(&self, rect: Rectangle) -> Vec<usize> {
+        let mut results = self.query_rect(rect);
+        results.sort_unstable();
+        results
+    }
+
+    /// Like query_rect, but along with each hit, return the path the descent
+    /// took to reach it: at each level below the root, the position of the
+    /// node among its siblings.  This is for debugging queries, and is slower
+    /// than query_rect.
+    pub fn query_rect_trace(&self, rect: Rectangle) -> Vec<(usize, Vec<usize>)> {
+        let mut results = Vec::new();
+        if self.is_empty() || !self.envelope().intersects(rect) {
+            return results;
+        }
+        let (root_level, root_offset) = self.root();
+        let mut stack = vec![(root_level, root_offset, Vec::new())];
+        while let Some((level, offset, path)) = stack.pop() {
+            if level == 0 {
+                results.push((offset, path));
+                continue;
+            }
+            let child_level = level - 1;
+            for child_offset in self.child_offsets(level, offset) {
+                if self
+                    .get_rectangle(child_level, child_offset)
+                    .intersects(rect)
+                {
+                    let mut child_path = path.clone();
+                    child_path.push(child_offset - self.degree * offset);
+                    stack.push((child_level, child_offset, child_path));
+                }
+            }
+        }
+        results
+    }
+
+    /// Like query_rect, but also return the envelopes of the nodes the
+    /// descent checked and pruned, eg to visualize how selective the index
+    /// is.  Empty (removed) nodes are not included.  This is for debugging
+    /// queries, and is slower than query_rect.
+    pub fn query_rect_with_pruned(&self, rect: Rectangle) -> (Vec<usize>, Vec<Rectangle>) {
+        let mut results = Vec::new();
+        let mut pruned = Vec::new();
+        if self.is_empty() {
+            return (results, pruned);
+        }
+        let mut stack = vec![self.root()];
+        while let Some((level, offset)) = stack.pop() {
+            let node_rect = self.get_rectangle(level, offset);
+            if !node_rect.intersects(rect) {
+                if !node_rect.is_empty() {
+                    pruned.push(node_rect);
+                }
+                continue;
+            }
+            if level == 0 {
+                results.push(offset);
+                continue;
+            }
+            for child_offset in self.child_offsets(level, offset) {
+                stack.push((level - 1, child_offset));
+            }
+        }
+        (results, pruned)
+    }
+
+    /// Like query_rect, but grouped by the internal nodes the query entered:
+    /// for each, its (level, offset) and the hits in its subtree, in
+    /// ascending order.  Nodes are ordered from the root down, and may have
+    /// no hits.  This is for seeing how a query fans out, eg as a heatmap.
+    pub fn query_rect_by_node(&self, rect: Rectangle) -> Vec<(usize, usize, Vec<usize>)> {
+        if self.is_empty() || !self.envelope().intersects(rect) {
+            return Vec::new();
+        }
+        let mut groups: BTreeMap<(usize, usize), Vec<usize>> = BTreeMap::new();
+        let mut stack = vec![self.root()];
+        while let Some((level, offset)) = stack.pop() {
+            if level == 0 {
+                for ancestor_level in 1..=self.height() {
+                    let ancestor_offset = offset / self.degree.pow(ancestor_level as u32);
+                    if let Some(hits) = groups.get_mut(&(ancestor_level, ancestor_offset)) {
+                        hits.push(offset);
+                    }
+                }
+                continue;
+            }
+            groups.insert((level, offset), Vec::new());
+            let child_level = level - 1;
+            for child_offset in self.child_offsets(level, offset) {
+                if self
+                    .get_rectangle(child_level, child_offset)
+                    .intersects(rect)
+                {
+                    stack.push((child_level, child_offset));
+                }
+            }
+        }
+        let mut results: Vec<(usize, usize, Vec<usize>)> = groups
+            .into_iter()
+            .map(|((level, offset), mut hits)| {
+                hits.sort_unstable();
+                (level, offset, hits)
+            })
+            .collect();
+        results.sort_by_key(|&(level, offset, _)| (Reverse(level), offset));
+        results
+    }
+
+    /// Whether any segment's rectangle intersects rect.  This stops at the
+    /// first hit, so it is cheaper than checking query_rect is nonempty.
+    pub fn intersects_rect(&self, rect: Rectangle) -> bool {
+        if self.is_empty() || !self.envelope().intersects(rect) {
+            return false;
+        }
+        let mut stack = vec![self.root()];
+        while let Some((level, offset)) = stack.pop() {
+            if level == 0 {
+                return true;
+            }
+            let child_level = level - 1;
+            for child_offset in self.child_offsets(level, offset) {
+                if self
+                    .get_rectangle(child_level, child_offset)
+                    .intersects(rect)
+                {
+                    stack.push((child_level, child_offset));
+                }
+            }
+        }
+        false
+    }
+
+    /// Find segments whose rectangles are within radius of center.  This
+    /// prunes by the distance to each node, so it's tighter than a square
+    /// query for radial searches, but the results are still candidates that
+    /// the caller must refine against the segments.
+    pub fn query_circle(&self, center: Coordinate, radius: f64) -> Vec<usize> {
+        self.query(|rtree_rect| rtree_rect.distance_to_coord(center) <= radius)
+    }
+
     pub fn query_point(&self, point: Coordinate) -> Vec<usize> {
         self.query(|rtree_rect| rtree_rect.contains(point))
     }
@@ -127,25 +482,45 @@ impl SegRTree {
     fn query<P>(&self, predicate: P) -> Vec<usize>
     where
         P: Fn(Rectangle) -> bool,
+    {
+        self.query_indexed(|_, rect| predicate(rect))
+    }
+
+    /// Find the leaves for which the predicate holds, descending only into
+    /// nodes for which it holds.  The predicate gets the range of leaf
+    /// offsets under the node (just the offset, for a leaf) along with its
+    /// rectangle, so it can select by position, eg "segments after k that
+    /// intersect rect".  It must hold for a node whenever it holds for any
+    /// of its leaves.
+    pub fn query_indexed<P>(&self, predicate: P) -> Vec<usize>
+    where
+        P: Fn(Range<usize>, Rectangle) -> bool,
     {
         let mut results = Vec::new();
         if self.is_empty() {
             return results;
         }
+        let node_range = |level, offset| {
+            let (low, high) = self.get_low_high(level, offset);
+            low..high
+        };
 
         // Stack entries: (level, offset)
         let mut stack = Vec::new();
-        if predicate(self.envelope()) {
-            stack.push(self.root())
+        let (root_level, root_offset) = self.root();
+        if predicate(node_range(root_level, root_offset), self.envelope()) {
+            stack.push((root_level, root_offset))
         }
         while let Some((level, offset)) = stack.pop() {
             if level == 0 {
                 results.push(offset);
             } else {
                 let child_level = level - 1;
-                let first_child_offset = self.degree * offset;
-                for child_offset in first_child_offset..(first_child_offset + self.degree) {
-                    if predicate(self.get_rectangle(child_level, child_offset)) {
+                for child_offset in self.child_offsets(level, offset) {
+                    if predicate(
+                        node_range(child_level, child_offset),
+                        self.get_rectangle(child_level, child_offset),
+                    ) {
                         stack.push((child_level, child_offset));
                     }
                 }
@@ -156,18 +531,45 @@ impl SegRTree {
     }
 
     pub fn query_self_intersections(&self) -> Vec<(usize, usize)> {
+        self.self_intersections_iter().collect()
+    }
+
+    /// The number of pairs query_self_intersections would find, without
+    /// collecting them.  These are pairs of segments whose rectangles
+    /// intersect, which is an upper bound on the number of true crossings.
+    pub fn count_self_intersections(&self) -> usize {
+        self.self_intersections_iter().count()
+    }
+
+    /// Lazily find the pairs of segments whose rectangles intersect, as
+    /// (low, high) indices.  Pairs are produced as the descent finds them.
+    pub fn self_intersections_iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        // Stack entries: (level_a, offset_a, level_b, offset_b)
+        let stack = if self.is_empty() {
+            Vec::new()
+        } else {
+            vec![(self.height(), 0, self.height(), 0)]
+        };
+        SelfIntersections { rtree: self, stack }
+    }
+
+    /// Find the pairs of segments whose rectangles are within distance of
+    /// each other, as (low, high) indices, eg to find near-duplicate
+    /// segments.  With distance 0, this is query_self_intersections.
+    pub fn query_self_within(&self, distance: f64) -> Vec<(usize, usize)> {
         let mut results = Vec::new();
         if self.is_empty() {
             return results;
         }
 
-        // Stack entries: (level, offset)
+        // Stack entries: (level_a, offset_a, level_b, offset_b)
         let mut stack = vec![(self.height(), 0, self.height(), 0)];
-
         while let Some((level_a, offset_a, level_b, offset_b)) = stack.pop() {
             let rect_a = self.get_rectangle(level_a, offset_a);
             let rect_b = self.get_rectangle(level_b, offset_b);
-            if !rect_a.intersects(rect_b) {
+            // Empty (removed) rectangles have NaN distance, so are never near.
+            let is_near = rect_a.distance(rect_b) <= distance;
+            if !is_near {
                 continue;
             }
 
@@ -176,22 +578,15 @@ impl SegRTree {
                     results.push((offset_a, offset_b));
                 }
             } else if level_a == level_b {
-                let child_level = level_a - 1;
-                let first_child_offset = self.degree * offset_a;
-                for child_offset in first_child_offset..(first_child_offset + self.degree) {
-                    stack.push((child_level, child_offset, level_b, offset_b));
+                for child_offset in self.child_offsets(level_a, offset_a) {
+                    stack.push((level_a - 1, child_offset, level_b, offset_b));
                 }
             } else {
-                assert_eq!(level_a + 1, level_b);
-                let child_level = level_b - 1;
-                let first_child_offset = self.degree * offset_b;
-                let last_child_offset = first_child_offset + self.degree;
-                for child_offset in first_child_offset..last_child_offset {
-                    stack.push((level_a, offset_a, child_level, child_offset));
+                for child_offset in self.child_offsets(level_b, offset_b) {
+                    stack.push((level_a, offset_a, level_b - 1, child_offset));
                 }
             }
         }
-
         results
     }
 
@@ -215,15 +610,12 @@ impl SegRTree {
                 results.push((offset_a, offset_b));
             } else if level_a >= level_b {
                 let child_level = level_a - 1;
-                let first_child_offset = self.degree * offset_a;
-                for child_offset in first_child_offset..(first_child_offset + self.degree) {
+                for child_offset in self.child_offsets(level_a, offset_a) {
                     stack.push((child_level, child_offset, level_b, offset_b));
                 }
             } else {
                 let child_level = level_b - 1;
-                let first_child_offset = other.degree * offset_b;
-                let last_child_offset = first_child_offset + other.degree;
-                for child_offset in first_child_offset..last_child_offset {
+                for child_offset in other.child_offsets(level_b, offset_b) {
                     stack.push((level_a, offset_a, child_level, child_offset));
                 }
             }
@@ -232,6 +624,156 @@ impl SegRTree {
         results
     }
 
+    /// Check if point is in the interior or on the boundary of the loop
+    /// formed by coords, whose segments this rtree indexes.
+    pub fn check_containment(
+        &self,
+        point: Coordinate,
+        coords: &[Coordinate],
+    ) -> Result<bool, ContainmentError> {
+        if coords.len() != self.len() + 1 {
+            return Err(ContainmentError::MismatchedCoordinates {
+                num_coords: coords.len(),
+                num_segments: self.len(),
+            });
+        }
+        if coords.first() != coords.last() {
+            return Err(ContainmentError::NotClosed);
+        }
+        Ok(point_in_coords_loop(point, coords, self) != ContainRelation::Exterior)
+    }
+
+    /// Encode the tree in a compact little-endian format, to be loaded by
+    /// from_bytes.  Every count is a u64, and every rectangle is 4 f64s.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let num_words = 6 + self.level_indices.len() + 4 * self.tree.len();
+        let mut bytes = Vec::with_capacity(8 * num_words);
+        let push_usize = |bytes: &mut Vec<u8>, n: usize| {
+            bytes.extend_from_slice(&(n as u64).to_le_bytes());
+        };
+        push_usize(&mut bytes, self.degree);
+        push_usize(&mut bytes, self.max_size);
+        push_usize(&mut bytes, self.current_size);
+        push_usize(&mut bytes, self.current_level);
+        push_usize(&mut bytes, self.level_indices.len());
+        for &index in &self.level_indices {
+            push_usize(&mut bytes, index);
+        }
+        push_usize(&mut bytes, self.tree.len());
+        for rect in &self.tree {
+            for &value in &[rect.x_min, rect.y_min, rect.x_max, rect.y_max] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Decode a tree encoded by to_bytes, checking that its levels have the
+    /// layout SegRTree::new would give them.  The growth policy is not
+    /// encoded, so the tree has the default one; set it again with
+    /// with_growth_policy.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.len() % 8 != 0 {
+            return Err(DecodeError::TrailingBytes(bytes.len() % 8));
+        }
+        let mut words = bytes.chunks_exact(8).map(|chunk| {
+            let mut word = [0; 8];
+            word.copy_from_slice(chunk);
+            word
+        });
+        let mut next_usize = || -> Result<usize, DecodeError> {
+            let word = words.next().ok_or(DecodeError::Truncated)?;
+            usize::try_from(u64::from_le_bytes(word))
+                .map_err(|_| DecodeError::InvalidLayout("count does not fit in usize"))
+        };
+
+        let degree = next_usize()?;
+        let max_size = next_usize()?;
+        let current_size = next_usize()?;
+        let current_level = next_usize()?;
+        if degree < 2 {
+            return Err(DecodeError::InvalidLayout("degree is less than 2"));
+        }
+        if current_size > max_size {
+            return Err(DecodeError::InvalidLayout("size is greater than capacity"));
+        }
+        // Each leaf has a rectangle, so this also bounds the layout below.
+        if max_size > bytes.len() / 32 {
+            return Err(DecodeError::Truncated);
+        }
+        let num_levels = next_usize()?;
+        let level_indices = (0..num_levels)
+            .map(|_| next_usize())
+            .collect::<Result<Vec<_>, _>>()?;
+        if level_indices != calculate_level_indices(degree, max_size) {
+            return Err(DecodeError::InvalidLayout(
+                "level indices do not match degree and capacity",
+            ));
+        }
+        if current_level >= num_levels {
+            return Err(DecodeError::InvalidLayout("height is greater than levels"));
+        }
+
+        let expected = level_indices[num_levels - 1] + 1;
+        let found = next_usize()?;
+        if found != expected {
+            return Err(DecodeError::MismatchedTreeSize { expected, found });
+        }
+        let mut next_f64 = || words.next().map(f64::from_le_bytes);
+        let mut tree = Vec::with_capacity(expected.min(bytes.len() / 32));
+        for _ in 0..expected {
+            match (next_f64(), next_f64(), next_f64(), next_f64()) {
+                (Some(x_min), Some(y_min), Some(x_max), Some(y_max)) => tree.push(Rectangle {
+                    x_min,
+                    y_min,
+                    x_max,
+                    y_max,
+                }),
+                _ => return Err(DecodeError::Truncated),
+            }
+        }
+        let num_trailing = words.count();
+        if num_trailing > 0 {
+            return Err(DecodeError::TrailingBytes(8 * num_trailing));
+        }
+
+        Ok(SegRTree {
+            degree,
+            max_size,
+            current_size,
+            current_level,
+            level_indices,
+            tree,
+            growth_policy: GrowthPolicy::default(),
+        })
+    }
+
+    /// The minimum distance from point to the segments of coords, which this
+    /// rtree indexes.  Subtrees further than the best distance so far are
+    /// pruned.  Returns infinity if there are no segments.
+    pub(crate) fn distance_to_segments(&self, point: Coordinate, coords: &[Coordinate]) -> f64 {
+        let mut best = f64::INFINITY;
+        if self.is_empty() {
+            return best;
+        }
+        let mut stack = vec![self.root()];
+        while let Some((level, offset)) = stack.pop() {
+            let rect = self.get_rectangle(level, offset);
+            if rect.is_empty() || rect.distance_to_coord(point) >= best {
+                continue;
+            }
+            if level == 0 {
+                let distance = point_segment_distance(point, coords[offset], coords[offset + 1]);
+                best = best.min(distance);
+            } else {
+                for child_offset in self.child_offsets(level, offset) {
+                    stack.push((level - 1, child_offset));
+                }
+            }
+        }
+        best
+    }
+
     pub(crate) fn get_rectangle(&self, level: usize, offset: usize) -> Rectangle {
         self.tree[self.level_indices[level] + offset]
     }
@@ -243,14 +785,64 @@ impl SegRTree {
         (width * offset, max_index.min(width * (offset + 1)))
     }
 
+    /// The offsets of the children of the node at (level, offset), which
+    /// stop at the end of the child level if it is not full.
+    pub(crate) fn child_offsets(&self, level: usize, offset: usize) -> Range<usize> {
+        let first_child_offset = self.degree * offset;
+        let level_size = self.level_indices[level] - self.level_indices[level - 1];
+        first_child_offset..level_size.min(first_child_offset + self.degree)
+    }
+
     pub(crate) fn root(&self) -> (usize, usize) {
         (self.height(), 0)
     }
 }
 
+struct SelfIntersections<'a> {
+    rtree: &'a SegRTree,
+    stack: Vec<(usize, usize, usize, usize)>,
+}
+
+impl<'a> Iterator for SelfIntersections<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((level_a, offset_a, level_b, offset_b)) = self.stack.pop() {
+            let rect_a = self.rtree.get_rectangle(level_a, offset_a);
+            let rect_b = self.rtree.get_rectangle(level_b, offset_b);
+            if !rect_a.intersects(rect_b) {
+                continue;
+            }
+
+            if level_a == 0 && level_b == 0 {
+                if offset_a < offset_b {
+                    return Some((offset_a, offset_b));
+                }
+            } else if level_a == level_b {
+                let child_level = level_a - 1;
+                for child_offset in self.rtree.child_offsets(level_a, offset_a) {
+                    self.stack
+                        .push((child_level, child_offset, level_b, offset_b));
+                }
+            } else {
+                assert_eq!(level_a + 1, level_b);
+                let child_level = level_b - 1;
+                for child_offset in self.rtree.child_offsets(level_b, offset_b) {
+                    self.stack
+                        .push((level_a, offset_a, child_level, child_offset));
+                }
+            }
+        }
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::from_wkt::{parse_one, Geometry};
+    use crate::test_utils::get_envelopes;
+    use crate::utils::rectangles_from_coordinates;
     use rand::rngs::SmallRng;
     use rand::{Rng, SeedableRng};
 
@@ -271,6 +863,452 @@ mod tests {
         assert!(tree.add(r).is_err());
     }
 
+    #[test]
+    fn test_query_self_within() {
+        let coords = Coordinate::vec_from(&[(0., 0.), (4., 0.)]);
+        let mut rects = rectangles_from_coordinates(&coords);
+        rects.extend(rectangles_from_coordinates(&Coordinate::vec_from(&[
+            (1., 0.5),
+            (3., 0.5),
+        ])));
+        rects.push(Rectangle::new((10., 10.).into(), (11., 11.).into()));
+        let tree = SegRTree::new_loaded(2, &rects);
+        assert_eq!(tree.query_self_within(1.), vec![(0, 1)]);
+        assert!(tree.query_self_within(0.25).is_empty());
+        assert!(SegRTree::new_empty().query_self_within(1.).is_empty());
+
+        let envelopes = get_envelopes();
+        let tree = SegRTree::new_loaded(4, &envelopes);
+        for &distance in &[0., 2., 10.] {
+            let mut pairs = tree.query_self_within(distance);
+            pairs.sort_unstable();
+            let expected: Vec<(usize, usize)> = (0..envelopes.len())
+                .flat_map(|i| (i + 1..envelopes.len()).map(move |j| (i, j)))
+                .filter(|&(i, j)| envelopes[i].distance(envelopes[j]) <= distance)
+                .collect();
+            assert_eq!(pairs, expected);
+        }
+        let mut intersections = tree.query_self_intersections();
+        intersections.sort_unstable();
+        let mut within = tree.query_self_within(0.);
+        within.sort_unstable();
+        assert_eq!(within, intersections);
+    }
+
+    #[test]
+    fn test_single_item() {
+        let item = Rectangle::new((1., 1.).into(), (2., 2.).into());
+        let tree = SegRTree::new_loaded(16, &[item]);
+        assert_eq!(tree.height(), 0);
+        assert_eq!(tree.envelope(), item);
+        let hit = Rectangle::new((0., 0.).into(), (1.5, 1.5).into());
+        assert_eq!(tree.query_rect(hit), vec![0]);
+        let miss = Rectangle::new((3., 3.).into(), (4., 4.).into());
+        assert!(tree.query_rect(miss).is_empty());
+    }
+
+    #[test]
+    fn test_query_rect_trace() {
+        let coords: Vec<Coordinate> = (0..=20).map(|i| (i as f64, 0.).into()).collect();
+        let tree = SegRTree::new_loaded(3, &rectangles_from_coordinates(&coords));
+        assert_eq!(tree.height(), 3);
+        // 14 is 112 in base 3, so its path is the digits.
+        let rect = Rectangle::new((14.2, -1.).into(), (14.8, 1.).into());
+        assert_eq!(tree.query_rect_trace(rect), vec![(14, vec![1, 1, 2])]);
+
+        let rect = Rectangle::new((3.5, -1.).into(), (5.5, 1.).into());
+        let mut hits: Vec<usize> = tree
+            .query_rect_trace(rect)
+            .into_iter()
+            .map(|(offset, _)| offset)
+            .collect();
+        hits.sort_unstable();
+        assert_eq!(hits, tree.query_rect_ordered(rect));
+    }
+
+    #[test]
+    fn test_query_rect_with_pruned() {
+        let coords: Vec<Coordinate> = (0..=20).map(|i| (i as f64, 0.).into()).collect();
+        let tree = SegRTree::new_loaded(3, &rectangles_from_coordinates(&coords));
+        let rect = Rectangle::new((14.2, -1.).into(), (14.8, 1.).into());
+        let (hits, pruned) = tree.query_rect_with_pruned(rect);
+        assert_eq!(hits, vec![14]);
+        // Two siblings are pruned at each of the levels below the root.
+        assert_eq!(pruned.len(), 6);
+        assert!(pruned.iter().all(|node| !node.intersects(rect)));
+
+        let rect = Rectangle::new((3.5, -1.).into(), (5.5, 1.).into());
+        let (mut hits, _) = tree.query_rect_with_pruned(rect);
+        hits.sort_unstable();
+        assert_eq!(hits, tree.query_rect_ordered(rect));
+
+        let rect = Rectangle::new((0., 5.).into(), (1., 6.).into());
+        assert_eq!(
+            tree.query_rect_with_pruned(rect),
+            (Vec::new(), vec![tree.envelope()])
+        );
+    }
+
+    #[test]
+    fn test_query_indexed() {
+        let coords: Vec<Coordinate> = (0..=20).map(|i| (i as f64, 0.).into()).collect();
+        let tree = SegRTree::new_loaded(3, &rectangles_from_coordinates(&coords));
+        let rect = Rectangle::new((3.5, -1.).into(), (15.5, 1.).into());
+        let mut hits = tree.query_indexed(|range, node| range.end > 10 && node.intersects(rect));
+        hits.sort_unstable();
+        assert_eq!(hits, (10..=15).collect::<Vec<usize>>());
+
+        let mut all = tree.query_indexed(|_, node| node.intersects(rect));
+        all.sort_unstable();
+        assert_eq!(all, tree.query_rect_ordered(rect));
+        assert!(SegRTree::new_empty().query_indexed(|_, _| true).is_empty());
+    }
+
+    #[test]
+    fn test_query_rect_by_node() {
+        let rects: Vec<Rectangle> = (0..6)
+            .map(|i| Rectangle::new((i as f64, i as f64).into(), (i as f64, i as f64).into()))
+            .collect();
+        let tree = SegRTree::new_loaded(2, &rects);
+        assert_eq!(tree.height(), 3);
+
+        // Leaf 2 is under (1, 1); (1, 0) is not entered.
+        let rect = Rectangle::new((1.5, 1.5).into(), (2.5, 2.5).into());
+        assert_eq!(
+            tree.query_rect_by_node(rect),
+            vec![(3, 0, vec![2]), (2, 0, vec![2]), (1, 1, vec![2])]
+        );
+
+        let rect = Rectangle::new((0.5, 0.5).into(), (4.5, 4.5).into());
+        assert_eq!(
+            tree.query_rect_by_node(rect),
+            vec![
+                (3, 0, vec![1, 2, 3, 4]),
+                (2, 0, vec![1, 2, 3]),
+                (2, 1, vec![4]),
+                (1, 0, vec![1]),
+                (1, 1, vec![2, 3]),
+                (1, 2, vec![4]),
+            ]
+        );
+
+        let miss = Rectangle::new((7., 7.).into(), (8., 8.).into());
+        assert!(tree.query_rect_by_node(miss).is_empty());
+    }
+
+    #[test]
+    fn test_query_circle() {
+        let envelopes = get_envelopes();
+        let tree = SegRTree::new_loaded(8, &envelopes);
+        for &(x, y, radius) in &[
+            (50., 50., 10.),
+            (0., 0., 30.),
+            (57., 18., 0.),
+            (-50., -50., 5.),
+        ] {
+            let center = Coordinate::new(x, y);
+            let expected: Vec<usize> = (0..envelopes.len())
+                .filter(|&i| envelopes[i].distance_to_coord(center) <= radius)
+                .collect();
+            let mut results = tree.query_circle(center, radius);
+            results.sort_unstable();
+            assert_eq!(results, expected);
+        }
+        // A corner of the square around the circle, but outside the circle
+        let rect = Rectangle::new((9., 9.).into(), (10., 10.).into());
+        let tree = SegRTree::new_loaded(8, &[rect]);
+        assert!(tree.query_circle((0., 0.).into(), 10.).is_empty());
+        assert_eq!(tree.query_circle((0., 0.).into(), 13.), vec![0]);
+    }
+
+    #[test]
+    fn test_intersects_rect() {
+        // An L-shaped path, which misses the corner it wraps around.
+        let coords: Vec<Coordinate> = (0..=10)
+            .map(|i| (i as f64, 0.))
+            .chain((1..=10).map(|i| (10., i as f64)))
+            .map(Coordinate::from)
+            .collect();
+        let tree = SegRTree::new_loaded(4, &rectangles_from_coordinates(&coords));
+        let entered = Rectangle::new((8., -1.).into(), (9., 1.).into());
+        assert!(tree.intersects_rect(entered));
+        let missed = Rectangle::new((2., 2.).into(), (8., 8.).into());
+        assert!(tree.envelope().intersects(missed));
+        assert!(!tree.intersects_rect(missed));
+        assert!(!SegRTree::new_empty().intersects_rect(entered));
+    }
+
+    #[test]
+    fn test_remaining_capacity() {
+        let rect = Rectangle::new((0., 0.).into(), (1., 1.).into());
+        let mut tree = SegRTree::new(2, 5);
+        assert_eq!(tree.remaining_capacity(), 5);
+        while tree.remaining_capacity() > 0 {
+            tree.add(rect).unwrap();
+        }
+        assert_eq!(tree.len(), 5);
+        assert!(tree.add(rect).is_err());
+        assert_eq!(SegRTree::new_loaded(4, &[rect; 3]).remaining_capacity(), 0);
+    }
+
+    #[test]
+    fn test_push() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let coords: Vec<Coordinate> = (0..=200)
+            .map(|_| Coordinate::new(rng.gen_range(0., 100.), rng.gen_range(0., 100.)))
+            .collect();
+        let rects = rectangles_from_coordinates(&coords);
+        let policy = GrowthPolicy {
+            growth_factor: 1.5,
+            ..GrowthPolicy::default()
+        };
+        let mut tree = SegRTree::new(4, 4).with_growth_policy(policy);
+        for (i, &rect) in rects.iter().enumerate() {
+            tree.push(rect);
+            assert_eq!(tree.len(), i + 1);
+            // Once the tree has grown, it is never less than 1 / 1.5 full.
+            if i >= 4 {
+                assert!(tree.fill_ratio() > 2. / 3.);
+            }
+        }
+        assert_eq!(tree.envelope(), Rectangle::of(&rects));
+        for &query in &rects[..20] {
+            let expected: Vec<usize> = (0..rects.len())
+                .filter(|&i| rects[i].intersects(query))
+                .collect();
+            let mut results = tree.query_rect(query);
+            results.sort_unstable();
+            assert_eq!(results, expected);
+        }
+
+        // Using the default policy
+        let mut tree = SegRTree::new_empty();
+        assert_eq!(tree.fill_ratio(), 1.);
+        tree.push(rects[0]);
+        tree.push(rects[1]);
+        assert!(tree.query_rect(rects[1]).contains(&1));
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.envelope(), Rectangle::of(&rects[..2]));
+    }
+
+    #[test]
+    fn test_min_fill() {
+        let rects: Vec<Rectangle> = (0..40)
+            .map(|i| Rectangle::new((i as f64, 0.).into(), (i as f64 + 1., 1.).into()))
+            .collect();
+        let policy = GrowthPolicy {
+            growth_factor: 2.,
+            min_fill: 0.5,
+        };
+        let mut tree = SegRTree::new(4, 1).with_growth_policy(policy);
+        for &rect in &rects {
+            tree.push(rect);
+        }
+        assert_eq!(tree.remaining_capacity(), 24);
+
+        // Removing before the end leaves the tree as it is.
+        assert_eq!(tree.remove(5), Some(rects[5]));
+        assert_eq!(tree.len(), 40);
+        for index in (32..40).rev() {
+            assert_eq!(tree.remove(index), Some(rects[index]));
+        }
+        assert_eq!(tree.remaining_capacity(), 32);
+        // A fill of 31 / 64 is below 0.5, so the tree is compacted.
+        assert_eq!(tree.remove(31), Some(rects[31]));
+        assert_eq!(tree.len(), 31);
+        assert_eq!(tree.remaining_capacity(), 0);
+        assert_eq!(tree.envelope(), Rectangle::of(&rects[..31]));
+        for index in (20..31).rev() {
+            assert_eq!(tree.remove(index), Some(rects[index]));
+        }
+        assert_eq!(tree.remaining_capacity(), 11);
+        assert_eq!(tree.remove(5), None);
+        assert_eq!(tree.query_rect_ordered(rects[6]), vec![6, 7]);
+        tree.push(rects[0]);
+        assert_eq!(tree.len(), 21);
+
+        // The removed segment before the last is dropped with it.
+        let mut tree = SegRTree::new_loaded(4, &rects[..10]);
+        tree.remove(8);
+        tree.remove(9);
+        assert_eq!(tree.len(), 8);
+        assert_eq!(tree.remaining_capacity(), 2);
+    }
+
+    #[test]
+    fn test_build_many() {
+        let rect_lists: Vec<Vec<Rectangle>> = (1..20)
+            .map(|n| {
+                let coords: Vec<Coordinate> = (0..=n)
+                    .map(|i| (i as f64, (i * n % 5) as f64).into())
+                    .collect();
+                rectangles_from_coordinates(&coords)
+            })
+            .chain(std::iter::once(Vec::new()))
+            .collect();
+        let trees = SegRTree::build_many(4, &rect_lists);
+        assert_eq!(trees.len(), rect_lists.len());
+        for (tree, rects) in trees.iter().zip(&rect_lists) {
+            let expected = SegRTree::new_loaded(4, rects);
+            assert_eq!(tree.len(), rects.len());
+            assert_eq!(tree.tree, expected.tree);
+        }
+    }
+
+    #[test]
+    fn test_new_loaded_with_map() {
+        let coords: Vec<Coordinate> = (0..40).map(|i| (i as f64, (i % 7) as f64).into()).collect();
+        let rects = rectangles_from_coordinates(&coords);
+        let (tree, nodes) = SegRTree::new_loaded_with_map(4, &rects);
+        // 39 leaves, then 10, 3 and 1 nodes
+        assert_eq!(nodes.len(), 39 + 10 + 3 + 1);
+        for node in &nodes {
+            assert_eq!(
+                node.envelope,
+                tree.get_rectangle(node.level, node.sibling_index)
+            );
+            assert_eq!(node.envelope, tree.tree[node.tree_index]);
+        }
+        assert_eq!(nodes.iter().filter(|n| n.level == 0).count(), rects.len());
+        let root = nodes.last().unwrap();
+        assert_eq!((root.level, root.sibling_index), tree.root());
+        assert_eq!(root.envelope, tree.envelope());
+
+        let (tree, nodes) = SegRTree::new_loaded_with_map(4, &[]);
+        assert!(tree.is_empty());
+        assert!(nodes.is_empty());
+    }
+
+    #[test]
+    fn test_compact_small_tree() {
+        let coords: Vec<Coordinate> = (0..4).map(|i| (i as f64, (i * i) as f64).into()).collect();
+        let rects = rectangles_from_coordinates(&coords);
+        let tree = SegRTree::new_loaded(16, &rects);
+        assert_eq!(tree.level_indices, vec![0, 3]);
+        assert_eq!(tree.tree.len(), 4);
+        assert_eq!(tree.envelope(), Rectangle::of(&rects));
+        assert_eq!(tree.query_rect_ordered(tree.envelope()), vec![0, 1, 2]);
+        let mut pairs = tree.query_self_intersections();
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(0, 1), (1, 2)]);
+
+        let mut tree = SegRTree::new(16, 3);
+        assert_eq!(tree.tree.len(), 4);
+        for rect in rects {
+            tree.add(rect).unwrap();
+        }
+        assert_eq!(tree.query_point((1.5, 2.).into()), vec![1]);
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let coords = match parse_one(include_str!("../../benches/testdata/plane.wkt")) {
+            Ok(Geometry::LineString(coords)) => coords,
+            other => panic!("Expected a linestring, got {:?}", other),
+        };
+        let rtree = SegRTree::new_loaded(8, &rectangles_from_coordinates(&coords));
+        let loaded = SegRTree::from_bytes(&rtree.to_bytes()).unwrap();
+        assert_eq!(loaded.len(), rtree.len());
+        assert_eq!(loaded.height(), rtree.height());
+        assert_eq!(loaded.envelope(), rtree.envelope());
+        let envelope = rtree.envelope();
+        let center = envelope.center();
+        for &(dx, dy) in &[(0., 0.), (10., 10.), (-20., 5.), (1000., 1000.)] {
+            let query = Rectangle::new(center, center + Coordinate::new(dx, dy));
+            assert_eq!(loaded.query_rect(query), rtree.query_rect(query));
+        }
+        assert_eq!(
+            loaded.query_self_intersections(),
+            rtree.query_self_intersections()
+        );
+
+        let mut partial = SegRTree::new(4, 10);
+        partial
+            .add(Rectangle::new((0., 0.).into(), (1., 1.).into()))
+            .unwrap();
+        let loaded = SegRTree::from_bytes(&partial.to_bytes()).unwrap();
+        assert_eq!(loaded.remaining_capacity(), 9);
+        let empty = SegRTree::from_bytes(&SegRTree::new_empty().to_bytes()).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_from_bytes_invalid() {
+        let rtree = SegRTree::new_loaded(4, &get_envelopes()[..20]);
+        let bytes = rtree.to_bytes();
+        assert_eq!(
+            SegRTree::from_bytes(&bytes[..bytes.len() - 8]).unwrap_err(),
+            DecodeError::Truncated
+        );
+        assert_eq!(
+            SegRTree::from_bytes(&bytes[..bytes.len() - 3]).unwrap_err(),
+            DecodeError::TrailingBytes(5)
+        );
+        let mut extended = bytes.clone();
+        extended.extend_from_slice(&[0; 16]);
+        assert_eq!(
+            SegRTree::from_bytes(&extended).unwrap_err(),
+            DecodeError::TrailingBytes(16)
+        );
+
+        // A different degree gives a different layout.
+        let mut wrong_degree = bytes.clone();
+        wrong_degree[..8].copy_from_slice(&8_u64.to_le_bytes());
+        assert!(matches!(
+            SegRTree::from_bytes(&wrong_degree).unwrap_err(),
+            DecodeError::InvalidLayout(_)
+        ));
+        let tree_size_word = 5 + rtree.level_indices.len();
+        let mut wrong_size = bytes;
+        wrong_size[8 * tree_size_word..8 * (tree_size_word + 1)]
+            .copy_from_slice(&3_u64.to_le_bytes());
+        assert_eq!(
+            SegRTree::from_bytes(&wrong_size).unwrap_err(),
+            DecodeError::MismatchedTreeSize {
+                expected: rtree.tree.len(),
+                found: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_leaf_rectangles() {
+        let coords: Vec<Coordinate> = (0..20).map(|i| (i as f64, (i * i) as f64).into()).collect();
+        let rects = rectangles_from_coordinates(&coords);
+        let tree = SegRTree::new_loaded(4, &rects);
+        assert_eq!(tree.leaf_rectangles(), &rects[..]);
+        assert!(SegRTree::new(4, 10).leaf_rectangles().is_empty());
+    }
+
+    #[test]
+    fn test_iter_leaves() {
+        let coords: Vec<Coordinate> = (0..10).map(|i| (i as f64, (i % 3) as f64).into()).collect();
+        let rects = rectangles_from_coordinates(&coords);
+        let mut tree = SegRTree::new_loaded(4, &rects);
+        assert!(tree.iter_leaves().eq(rects.iter().copied().enumerate()));
+
+        tree.remove(4);
+        let leaves: Vec<(usize, Rectangle)> = tree.iter_leaves().collect();
+        assert_eq!(leaves.len(), rects.len() - 1);
+        assert!(leaves.iter().all(|&(i, rect)| i != 4 && rect == rects[i]));
+        assert_eq!(SegRTree::new(4, 10).iter_leaves().count(), 0);
+    }
+
+    #[test]
+    fn test_default() {
+        let tree = SegRTree::default();
+        assert!(tree.is_empty());
+        assert!(tree.envelope().is_empty());
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(SegRTree::try_new(1, 10).is_err());
+        let tree = SegRTree::try_new(4, 10).unwrap();
+        assert_eq!(tree.degree(), 4);
+        assert_eq!(SegRTree::new(1, 10).degree(), 2);
+    }
+
     fn _assert_add(i: usize, tree: &mut SegRTree, rects: &[Rectangle]) {
         tree.add(rects[i]).unwrap();
         assert_eq!(tree.current_size, i + 1);
@@ -344,6 +1382,138 @@ mod tests {
         assert_eq!(results, vec![1, 2, 3]);
     }
 
+    #[test]
+    fn test_query_rect_ordered() {
+        let mut rng = SmallRng::seed_from_u64(7);
+        let coords: Vec<Coordinate> = (0..200)
+            .map(|_| Coordinate::new(rng.gen_range(0., 10.), rng.gen_range(0., 10.)))
+            .collect();
+        let rtree = SegRTree::new_loaded(4, &rectangles_from_coordinates(&coords));
+        let rect = Rectangle::new((2., 2.).into(), (6., 5.).into());
+        let results = rtree.query_rect_ordered(rect);
+        assert!(!results.is_empty());
+        assert!(results.windows(2).all(|w| w[0] < w[1]));
+        let mut expected = rtree.query_rect(rect);
+        expected.sort_unstable();
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn test_count_self_intersections() {
+        let mut rng = SmallRng::seed_from_u64(5);
+        let coords: Vec<Coordinate> = (0..200)
+            .map(|_| Coordinate::new(rng.gen_range(0., 10.), rng.gen_range(0., 10.)))
+            .collect();
+        let rtree = SegRTree::new_loaded(8, &rectangles_from_coordinates(&coords));
+        assert_eq!(
+            rtree.count_self_intersections(),
+            rtree.query_self_intersections().len()
+        );
+        assert_eq!(SegRTree::new_empty().count_self_intersections(), 0);
+    }
+
+    #[test]
+    fn test_self_intersections_iter() {
+        let mut rng = SmallRng::seed_from_u64(11);
+        let coords: Vec<Coordinate> = (0..100)
+            .map(|_| Coordinate::new(rng.gen_range(0., 10.), rng.gen_range(0., 10.)))
+            .collect();
+        let rects = rectangles_from_coordinates(&coords);
+        let rtree = SegRTree::new_loaded(4, &rects);
+        let mut lazy: Vec<(usize, usize)> = rtree.self_intersections_iter().collect();
+        lazy.sort_unstable();
+        let expected: Vec<(usize, usize)> = (0..rects.len())
+            .flat_map(|i| (i + 1..rects.len()).map(move |j| (i, j)))
+            .filter(|&(i, j)| rects[i].intersects(rects[j]))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(lazy, expected);
+        assert!(rtree.self_intersections_iter().next().is_some());
+        assert!(SegRTree::new_empty()
+            .self_intersections_iter()
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_check_containment() {
+        let coords: Vec<Coordinate> =
+            Coordinate::vec_from(&[(0., 0.), (0., 1.), (1., 1.), (1., 0.), (0., 0.)]);
+        let rtree = SegRTree::new_loaded(4, &rectangles_from_coordinates(&coords));
+        assert_eq!(
+            rtree.check_containment((0.5, 0.5).into(), &coords),
+            Ok(true)
+        );
+        assert_eq!(rtree.check_containment((0., 0.5).into(), &coords), Ok(true));
+        assert_eq!(
+            rtree.check_containment((1.5, 0.5).into(), &coords),
+            Ok(false)
+        );
+
+        let open = &coords[..4];
+        let rtree = SegRTree::new_loaded(4, &rectangles_from_coordinates(open));
+        assert_eq!(
+            rtree.check_containment((0.5, 0.5).into(), open),
+            Err(ContainmentError::NotClosed)
+        );
+        assert_eq!(
+            rtree.check_containment((0.5, 0.5).into(), &coords),
+            Err(ContainmentError::MismatchedCoordinates {
+                num_coords: 5,
+                num_segments: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let rects_a: Vec<Rectangle> = (0..20)
+            .map(|i| Rectangle::new((i as f64, 0.).into(), (i as f64 + 1., 1.).into()))
+            .collect();
+        let rects_b: Vec<Rectangle> = (0..10)
+            .map(|i| Rectangle::new((i as f64, 2.).into(), (i as f64 + 1., 3.).into()))
+            .collect();
+        let a = SegRTree::new_loaded(4, &rects_a);
+        let b = SegRTree::new_loaded(8, &rects_b);
+        let merged = SegRTree::merge(&a, &b);
+        assert_eq!(merged.len(), 30);
+        assert_eq!(merged.degree(), 4);
+
+        let query = Rectangle::new((2.5, 0.5).into(), (3.5, 2.5).into());
+        let mut results = merged.query_rect(query);
+        results.sort_unstable();
+        assert_eq!(results, vec![2, 3, 22, 23]);
+
+        let empty = SegRTree::new_empty();
+        assert!(SegRTree::merge(&empty, &empty).is_empty());
+        let mut results = SegRTree::merge(&empty, &b).query_rect(query);
+        results.sort_unstable();
+        assert_eq!(results, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_remove_and_tighten() {
+        let rects: Vec<Rectangle> = (0..10)
+            .map(|i| Rectangle::new((i as f64, 0.).into(), (i as f64 + 1., 1.).into()))
+            .collect();
+        let mut tree = SegRTree::new_loaded(4, &rects);
+        let full_envelope = Rectangle::new((0., 0.).into(), (10., 1.).into());
+        assert_eq!(tree.envelope(), full_envelope);
+
+        assert_eq!(tree.remove(9), Some(rects[9]));
+        assert_eq!(tree.remove(9), None);
+        assert_eq!(tree.remove(10), None);
+        assert_eq!(tree.query_rect(rects[9]), vec![8]);
+        assert_eq!(tree.envelope(), full_envelope);
+
+        tree.tighten();
+        assert_eq!(
+            tree.envelope(),
+            Rectangle::new((0., 0.).into(), (9., 1.).into())
+        );
+        assert_eq!(tree.query_rect(rects[9]), vec![8]);
+    }
+
     fn assert_low_high(rtree: &SegRTree, height: usize, offset: usize, size: usize) {
         let (low, high) = rtree.get_low_high(height, offset);
         assert!(low <= size);
@@ -1,52 +1,189 @@
-use std::collections::BTreeSet;
+use std::collections::BTreeMap;
 
+/// A union of inclusive index ranges `low..=high`, stored as a map from each
+/// range's low to its high.
+///
+/// Ranges are merged when they share an endpoint: adding `(0, 3)` then
+/// `(3, 6)` gives the single range `(0, 6)`.  This works by toggling each
+/// endpoint, so an index added twice cancels.  The ranges added should
+/// otherwise be disjoint; overlapping ranges give their symmetric
+/// difference rather than their union.
 #[derive(Default)]
 pub struct SegmentUnion {
-    set: BTreeSet<usize>,
+    ranges: BTreeMap<usize, usize>,
 }
 
 impl SegmentUnion {
     pub fn new() -> Self {
         SegmentUnion {
-            set: BTreeSet::new(),
+            ranges: BTreeMap::new(),
         }
     }
 
     pub fn add(&mut self, low: usize, high: usize) {
-        self._add(low);
-        self._add(high);
+        // Toggling two endpoints only changes the ranges between them, so
+        // re-pair the endpoints of the ranges touching low..=high.
+        let (min, max) = (low.min(high), low.max(high));
+        let mut starts: Vec<usize> = self
+            .ranges
+            .range(..min)
+            .next_back()
+            .filter(|(_, &end)| end >= min)
+            .map(|(&start, _)| start)
+            .into_iter()
+            .collect();
+        starts.extend(self.ranges.range(min..=max).map(|(&start, _)| start));
+
+        let mut endpoints = Vec::with_capacity(2 * starts.len() + 2);
+        for start in starts {
+            if let Some(end) = self.ranges.remove(&start) {
+                endpoints.push(start);
+                endpoints.push(end);
+            }
+        }
+        Self::toggle(&mut endpoints, low);
+        Self::toggle(&mut endpoints, high);
+        for pair in endpoints.chunks(2) {
+            self.ranges.insert(pair[0], pair[1]);
+        }
     }
 
-    fn _add(&mut self, entry: usize) {
-        if self.set.contains(&entry) {
-            self.set.remove(&entry);
-        } else {
-            self.set.insert(entry);
+    fn toggle(endpoints: &mut Vec<usize>, entry: usize) {
+        match endpoints.binary_search(&entry) {
+            Ok(position) => {
+                endpoints.remove(position);
+            }
+            Err(position) => endpoints.insert(position, entry),
         }
     }
 
-    pub fn peek(&self) -> Option<usize> {
-        // Really?  This is a little ridiculous. https://github.com/rust-lang/rust/issues/62924
-        Some(*self.set.iter().next()?)
+    /// Whether the index is within one of the ranges, including endpoints.
+    pub fn contains(&self, index: usize) -> bool {
+        self.ranges
+            .range(..=index)
+            .next_back()
+            .map_or(false, |(_, &end)| index <= end)
+    }
+
+    /// The merged ranges, as inclusive low-high pairs in increasing order.
+    pub fn ranges(&self) -> Vec<(usize, usize)> {
+        self.ranges
+            .iter()
+            .map(|(&low, &high)| (low, high))
+            .collect()
+    }
+
+    /// Merge the ranges of other into this union.  Unlike add, overlapping
+    /// ranges are combined into their union.
+    pub fn union_with(&mut self, other: &SegmentUnion) {
+        let mut ranges = self.ranges();
+        ranges.extend(other.ranges());
+        ranges.sort_unstable();
+
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+        for (low, high) in ranges {
+            match merged.last_mut() {
+                Some(last) if low <= last.1 => last.1 = last.1.max(high),
+                _ => merged.push((low, high)),
+            }
+        }
+
+        self.ranges.clear();
+        for (low, high) in merged {
+            self.add(low, high);
+        }
     }
 
-    fn _pop(&mut self) -> Option<usize> {
-        let ret = self.peek()?;
-        self.set.remove(&ret);
-        Some(ret)
+    pub fn peek(&self) -> Option<usize> {
+        self.ranges.keys().next().copied()
     }
 
-    /// Pop two elements, as a low-high pair.
+    /// Pop the lowest range, as a low-high pair.
     pub fn pop(&mut self) -> Option<(usize, usize)> {
-        Some((self._pop()?, self._pop()?))
+        let low = self.peek()?;
+        let high = self.ranges.remove(&low)?;
+        Some((low, high))
     }
 
     pub fn is_empty(&self) -> bool {
-        self.set.is_empty()
+        self.ranges.is_empty()
     }
 
     /// Number of contained indicies.  This is twice the number of low-high pairs.
     pub fn len(&self) -> usize {
-        self.set.len()
+        2 * self.ranges.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjacent_ranges_merge() {
+        let mut union = SegmentUnion::new();
+        union.add(0, 3);
+        union.add(3, 6);
+        assert_eq!(union.ranges(), vec![(0, 6)]);
+        assert_eq!(union.len(), 2);
+
+        union.add(8, 9);
+        union.add(7, 8);
+        assert_eq!(union.ranges(), vec![(0, 6), (7, 9)]);
+        assert_eq!(union.pop(), Some((0, 6)));
+        assert_eq!(union.pop(), Some((7, 9)));
+        assert!(union.is_empty());
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut union = SegmentUnion::new();
+        assert!(!union.contains(0));
+        union.add(2, 4);
+        union.add(6, 7);
+        let contained: Vec<usize> = (0..10).filter(|&i| union.contains(i)).collect();
+        assert_eq!(contained, vec![2, 3, 4, 6, 7]);
+    }
+
+    #[test]
+    fn test_union_with() {
+        let mut union = SegmentUnion::new();
+        union.add(0, 3);
+        let mut other = SegmentUnion::new();
+        other.add(2, 5);
+        union.union_with(&other);
+        assert_eq!(union.ranges(), vec![(0, 5)]);
+
+        let mut other = SegmentUnion::new();
+        other.add(5, 6);
+        other.add(8, 9);
+        other.add(1, 2);
+        union.union_with(&other);
+        assert_eq!(union.ranges(), vec![(0, 6), (8, 9)]);
+    }
+
+    #[test]
+    fn test_repeated_range_cancels() {
+        let mut union = SegmentUnion::new();
+        union.add(1, 5);
+        union.add(1, 5);
+        assert!(union.is_empty());
+        assert!(union.ranges().is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_ranges_toggle() {
+        let mut union = SegmentUnion::new();
+        union.add(0, 6);
+        union.add(0, 3);
+        assert_eq!(union.ranges(), vec![(3, 6)]);
+        union.add(1, 8);
+        assert_eq!(union.ranges(), vec![(1, 3), (6, 8)]);
+        union.add(2, 2);
+        assert_eq!(union.ranges(), vec![(1, 3), (6, 8)]);
+        union.add(3, 6);
+        assert_eq!(union.ranges(), vec![(1, 8)]);
+        assert!(union.contains(5));
+        assert!(!union.contains(9));
     }
 }
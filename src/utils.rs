@@ -1,4 +1,5 @@
 use crate::{Coordinate, Rectangle};
+use std::collections::HashMap;
 
 pub(crate) fn rectangles_from_coordinates(coords: &[Coordinate]) -> Vec<Rectangle> {
     coords
@@ -7,8 +8,142 @@ pub(crate) fn rectangles_from_coordinates(coords: &[Coordinate]) -> Vec<Rectangl
         .collect()
 }
 
+/// Twice the signed area of the triangle abc: positive if c is to the left
+/// of the line from a to b, negative if to the right, and 0 if collinear.
+pub(crate) fn orientation(a: Coordinate, b: Coordinate, c: Coordinate) -> f64 {
+    (b - a).cross(c - a)
+}
+
+/// Like orientation, but with the exact sign even for nearly collinear
+/// points, where the naive cross product can be wrong.
+///
+/// This uses Shewchuk's adaptive approach: the fast value is returned when an
+/// error bound shows its sign is correct, else the determinant is computed
+/// exactly as a floating-point expansion.
+#[cfg(feature = "exact-predicates")]
+pub fn orientation_robust(a: Coordinate, b: Coordinate, c: Coordinate) -> f64 {
+    // Relative error bound of the fast path, from Shewchuk's ccwerrboundA.
+    const HALF_EPSILON: f64 = f64::EPSILON / 2.;
+    const ERROR_BOUND: f64 = (3. + 16. * HALF_EPSILON) * HALF_EPSILON;
+
+    let left = (b.x - a.x) * (c.y - a.y);
+    let right = (b.y - a.y) * (c.x - a.x);
+    let det = left - right;
+    if det.abs() >= ERROR_BOUND * (left.abs() + right.abs()) {
+        return det;
+    }
+
+    // Expanding the products, the a.x * a.y terms cancel.
+    let mut expansion = Vec::with_capacity(12);
+    let products = [
+        (b.x, c.y),
+        (-b.x, a.y),
+        (-a.x, c.y),
+        (-b.y, c.x),
+        (b.y, a.x),
+        (a.y, c.x),
+    ];
+    for &(x, y) in &products {
+        let (product, error) = two_product(x, y);
+        grow_expansion(&mut expansion, error);
+        grow_expansion(&mut expansion, product);
+    }
+    // The components are non-overlapping and increasing in magnitude, so
+    // summing them in order gives the correct sign.
+    expansion.iter().sum()
+}
+
+/// The rounded sum of a and b, and its rounding error.
+#[cfg(feature = "exact-predicates")]
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+    (sum, (a - a_virtual) + (b - b_virtual))
+}
+
+/// The rounded product of a and b, and its rounding error.
+#[cfg(feature = "exact-predicates")]
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let product = a * b;
+    (product, a.mul_add(b, -product))
+}
+
+/// Add value to the expansion exactly, dropping zero components.
+#[cfg(feature = "exact-predicates")]
+fn grow_expansion(expansion: &mut Vec<f64>, value: f64) {
+    let mut carry = value;
+    let mut grown = Vec::with_capacity(expansion.len() + 1);
+    for &component in expansion.iter() {
+        let (sum, error) = two_sum(carry, component);
+        if error != 0. {
+            grown.push(error);
+        }
+        carry = sum;
+    }
+    if carry != 0. {
+        grown.push(carry);
+    }
+    *expansion = grown;
+}
+
+/// Whether p lies exactly on the segment from a to b, including its
+/// endpoints.
+pub(crate) fn point_on_segment(p: Coordinate, a: Coordinate, b: Coordinate) -> bool {
+    (b - a).cross(p - a) == 0. && Rectangle::new(a, b).contains(p)
+}
+
+/// The distance from point to the closest point of the segment from start
+/// to end.
+pub(crate) fn point_segment_distance(point: Coordinate, start: Coordinate, end: Coordinate) -> f64 {
+    let direction = end - start;
+    let length_2 = direction.dot(direction);
+    if length_2 == 0. {
+        return point.distance(start);
+    }
+    let t = ((point - start).dot(direction) / length_2).clamp(0., 1.);
+    point.distance(start.lerp(end, t))
+}
+
+/// Signed area of the closed loop of coords, positive if counter-clockwise.
+pub(crate) fn signed_area(coords: &[Coordinate]) -> f64 {
+    coords.windows(2).map(|c| c[0].cross(c[1])).sum::<f64>() / 2.
+}
+
+/// Whether the closed loop of coords turns the same way at every vertex,
+/// ignoring collinear vertices.  For a simple loop, this means it is convex.
+/// Empty loops are not convex.
+pub(crate) fn is_convex_loop(coords: &[Coordinate]) -> bool {
+    if coords.is_empty() {
+        return false;
+    }
+    // Drop the closing coordinate, so the vertices wrap around.
+    let coords = &coords[1..];
+    let n = coords.len();
+    let mut sign = 0.;
+    for i in 0..n {
+        let turn = orientation(coords[i], coords[(i + 1) % n], coords[(i + 2) % n]);
+        if turn == 0. {
+            continue;
+        }
+        if sign == 0. {
+            sign = turn.signum();
+        } else if turn.signum() != sign {
+            return false;
+        }
+    }
+    true
+}
+
 pub(crate) fn calculate_level_indices(degree: usize, num_items: usize) -> Vec<usize> {
     let mut level_indices: Vec<usize> = vec![0];
+    if num_items <= degree {
+        // All the items are children of the root, so don't pad the leaves.
+        if num_items > 1 {
+            level_indices.push(num_items);
+        }
+        return level_indices;
+    }
 
     let mut level = 0;
     let mut level_size = num_items;
@@ -37,12 +172,26 @@ pub(crate) fn winding_number(
     start: Coordinate,
     end: Coordinate,
 ) -> WindingPosition {
+    if start.y == end.y {
+        // A horizontal edge never crosses the ray, but it may run through
+        // the point, which is then on the boundary.
+        let (x_min, x_max) = (start.x.min(end.x), start.x.max(end.x));
+        if point.y == start.y && x_min <= point.x && point.x <= x_max {
+            return WindingPosition::On;
+        }
+        return WindingPosition::Off;
+    }
+
     // Calculate the two halves of the cross-product (= lx - rx)
     let lx = (end.x - start.x) * (point.y - start.y);
     let rx = (end.y - start.y) * (point.x - start.x);
 
     if lx == rx {
-        return WindingPosition::On;
+        // Collinear points off the segment can't be crossed by the ray.
+        if point_on_segment(point, start, end) {
+            return WindingPosition::On;
+        }
+        return WindingPosition::Off;
     }
 
     if start.y <= point.y {
@@ -65,9 +214,21 @@ pub(crate) fn copy_into_slice<T: Copy>(tree: &mut [T], index: usize, items: &[T]
     subtree.copy_from_slice(items);
 }
 
+pub(crate) fn lexicographic_order(a: Coordinate, b: Coordinate) -> (Coordinate, Coordinate) {
+    if (b.x, b.y) < (a.x, a.y) {
+        (b, a)
+    } else {
+        (a, b)
+    }
+}
+
 /**
  * Check the intersection of two segments A and B.
  *
+ * The intersection is returned as a (start, end) pair, which are equal
+ * unless the segments overlap.  For overlaps, start is the lexicographically
+ * smaller endpoint (by x, then y), regardless of the segments' directions.
+ *
  * NB: This does not do an initial check with Envelopes; the caller should do that.
  */
 pub(crate) fn intersect_segments(
@@ -76,8 +237,15 @@ pub(crate) fn intersect_segments(
     start_b: Coordinate,
     end_b: Coordinate,
 ) -> Option<(Coordinate, Coordinate)> {
+    // The arithmetic below would give NaNs, which fail every comparison.
+    if ![start_a, end_a, start_b, end_b]
+        .iter()
+        .all(|c| c.is_finite())
+    {
+        return None;
+    }
     if (start_a == start_b && end_a == end_b) || (start_a == end_b && end_a == start_b) {
-        return Some((start_a, end_a));
+        return Some(lexicographic_order(start_a, end_a));
     }
 
     let da = end_a - start_a; // The vector for segment A
@@ -108,7 +276,8 @@ pub(crate) fn intersect_segments(
                 // Else, the intersect
                 let start = start_a + da * t_min.max(0.);
                 let end = start_a + da * t_max.min(1.);
-                return Some((start, end));
+                debug_assert!(start.is_finite() && end.is_finite());
+                return Some(lexicographic_order(start, end));
             }
         }
     } else {
@@ -117,10 +286,213 @@ pub(crate) fn intersect_segments(
         // then the segments intersect.
         let ta = offset.cross(db) / da_x_db;
         let tb = offset_x_da / da_x_db;
-        if 0. <= ta && ta <= 1. && 0. <= tb && tb <= 1. {
+        if (0. ..=1.).contains(&ta) && (0. ..=1.).contains(&tb) {
             let intersection = start_a + da * ta;
+            debug_assert!(intersection.is_finite());
             return Some((intersection, intersection));
         }
     }
     None
 }
+
+/// A hash of coordinates into square cells, to merge coincident
+/// coordinates across many geometries, eg when snapping or noding.
+///
+/// Each coordinate is bucketed by its cell, whose side is the tolerance,
+/// so a query only checks the 9 cells around it.
+#[derive(Debug, Clone)]
+pub struct CoordinateIndex {
+    tolerance: f64,
+    cells: HashMap<(i64, i64), Vec<Coordinate>>,
+    len: usize,
+}
+
+impl CoordinateIndex {
+    /// An empty index, which merges coordinates within tolerance (which
+    /// must be positive) of each other.
+    pub fn new(tolerance: f64) -> Self {
+        assert!(
+            tolerance > 0.,
+            "Tolerance must be positive, got {}",
+            tolerance
+        );
+        CoordinateIndex {
+            tolerance,
+            cells: HashMap::new(),
+            len: 0,
+        }
+    }
+
+    /// The number of canonical coordinates.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The nearest canonical coordinate within tolerance of coord, if any.
+    pub fn find(&self, coord: Coordinate) -> Option<Coordinate> {
+        let (column, row) = self.cell_of(coord);
+        let mut best: Option<(f64, Coordinate)> = None;
+        for neighbor_row in row.saturating_sub(1)..=row.saturating_add(1) {
+            for neighbor_column in column.saturating_sub(1)..=column.saturating_add(1) {
+                let cell = match self.cells.get(&(neighbor_column, neighbor_row)) {
+                    Some(cell) => cell,
+                    None => continue,
+                };
+                for &candidate in cell {
+                    let distance = coord.distance(candidate);
+                    if distance <= self.tolerance
+                        && best.map_or(true, |(best_distance, _)| distance < best_distance)
+                    {
+                        best = Some((distance, candidate));
+                    }
+                }
+            }
+        }
+        best.map(|(_, candidate)| candidate)
+    }
+
+    /// The canonical coordinate for coord: the nearest one within tolerance,
+    /// or else coord itself, which becomes canonical.
+    pub fn insert(&mut self, coord: Coordinate) -> Coordinate {
+        if let Some(canonical) = self.find(coord) {
+            return canonical;
+        }
+        let cell = self.cell_of(coord);
+        self.cells.entry(cell).or_default().push(coord);
+        self.len += 1;
+        coord
+    }
+
+    fn cell_of(&self, coord: Coordinate) -> (i64, i64) {
+        // Out of range values saturate in the cast.
+        (
+            (coord.x / self.tolerance).floor() as i64,
+            (coord.y / self.tolerance).floor() as i64,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "exact-predicates")]
+    #[test]
+    fn test_orientation_robust() {
+        let ulp = f64::EPSILON / 2.;
+        let a = Coordinate::new(0.5 + 41. * ulp, 0.5 + 48. * ulp);
+        let b = Coordinate::new(12., 12.);
+        let c = Coordinate::new(24., 24.);
+        // a is just above the line y = x through b and c, so abc is
+        // counter-clockwise, but the rounding in the naive version flips it.
+        assert!(orientation(a, b, c) < 0.);
+        assert!(orientation_robust(a, b, c) > 0.);
+        assert!(orientation_robust(a, c, b) < 0.);
+
+        let on_line = Coordinate::new(0.5, 0.5);
+        assert_eq!(orientation_robust(on_line, b, c), 0.);
+        // Well-separated points take the fast path.
+        let d = Coordinate::new(0., 1.);
+        assert_eq!(orientation_robust(b, c, d), orientation(b, c, d));
+    }
+
+    #[test]
+    fn test_point_on_segment() {
+        let a = Coordinate::new(0., 0.);
+        let b = Coordinate::new(4., 2.);
+        // Endpoints and midpoint
+        assert!(point_on_segment(a, a, b));
+        assert!(point_on_segment(b, a, b));
+        assert!(point_on_segment((2., 1.).into(), a, b));
+        // Collinear, but outside
+        assert!(!point_on_segment((6., 3.).into(), a, b));
+        assert!(!point_on_segment((-2., -1.).into(), a, b));
+        // Off the line
+        assert!(!point_on_segment((2., 1.5).into(), a, b));
+    }
+
+    #[test]
+    fn test_intersect_segments_non_finite() {
+        let a = Coordinate::new(0., 0.);
+        let b = Coordinate::new(2., 2.);
+        let c = Coordinate::new(0., 2.);
+        let d = Coordinate::new(2., 0.);
+        let one = Coordinate::new(1., 1.);
+        assert_eq!(intersect_segments(a, b, c, d), Some((one, one)));
+
+        let infinite = Coordinate::new(f64::INFINITY, 0.);
+        assert_eq!(intersect_segments(a, infinite, c, d), None);
+        assert_eq!(intersect_segments(a, b, c, infinite), None);
+        let nan = Coordinate::new(1., f64::NAN);
+        assert_eq!(intersect_segments(nan, b, c, d), None);
+        // Even a segment with itself
+        assert_eq!(intersect_segments(a, infinite, a, infinite), None);
+    }
+
+    #[test]
+    fn test_coordinate_index() {
+        let mut index = CoordinateIndex::new(0.01);
+        assert!(index.is_empty());
+        let canonical = Coordinate::new(1., 1.);
+        assert_eq!(index.insert(canonical), canonical);
+        // Near-coincident points, including across cell boundaries
+        for &(x, y) in &[(1.005, 1.), (0.995, 0.998), (1., 1.009), (1.007, 0.993)] {
+            assert_eq!(index.insert(Coordinate::new(x, y)), canonical);
+        }
+        assert_eq!(index.len(), 1);
+
+        let other = Coordinate::new(1.02, 1.);
+        assert_eq!(index.find(other), None);
+        assert_eq!(index.insert(other), other);
+        assert_eq!(index.len(), 2);
+        // The nearest canonical coordinate wins.
+        assert_eq!(index.find(Coordinate::new(1.012, 1.)), Some(other));
+        assert_eq!(index.find(Coordinate::new(1.008, 1.)), Some(canonical));
+        assert_eq!(index.find(Coordinate::new(-1., -1.)), None);
+    }
+
+    #[test]
+    fn test_winding_number_horizontal() {
+        let start = Coordinate::new(2., 1.);
+        let end = Coordinate::new(0., 1.);
+        for &x in &[0., 0.5, 2.] {
+            assert!(matches!(
+                winding_number((x, 1.).into(), start, end),
+                WindingPosition::On
+            ));
+            assert!(matches!(
+                winding_number((x, 1.).into(), end, start),
+                WindingPosition::On
+            ));
+        }
+        // Beyond the ends, or above or below, the edge is not crossed.
+        for &(x, y) in &[(-1., 1.), (3., 1.), (1., 0.), (1., 2.)] {
+            assert!(matches!(
+                winding_number((x, y).into(), start, end),
+                WindingPosition::Off
+            ));
+        }
+    }
+
+    #[test]
+    fn test_winding_number_collinear() {
+        let start = Coordinate::new(0., 0.);
+        let end = Coordinate::new(0., 2.);
+        assert!(matches!(
+            winding_number((0., 1.).into(), start, end),
+            WindingPosition::On
+        ));
+        assert!(matches!(
+            winding_number((0., 3.).into(), start, end),
+            WindingPosition::Off
+        ));
+        assert!(matches!(
+            winding_number((-1., 1.).into(), start, end),
+            WindingPosition::Left
+        ));
+    }
+}
@@ -0,0 +1,11 @@
+#[path = "../benches/common/mod.rs"]
+mod common;
+
+use common::test_case_path;
+
+#[test]
+fn test_test_case_path() {
+    let path = test_case_path("plane");
+    assert!(path.starts_with(env!("CARGO_MANIFEST_DIR")));
+    assert!(path.ends_with("benches/testdata/plane.wkt"));
+}